@@ -5,15 +5,124 @@ use anyhow::{anyhow, Result};
 use ariana_server::traces::instrumentation::ecma::EcmaImportStyle;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
-/// Processes files_to_instrument in batches of up to 100 files in parallel.
+const CACHE_PATH: &str = ".ariana/cache.json";
+
+/// Default combined-source-bytes budget per instrumentation batch. Kept
+/// well under typical request-body limits so a handful of huge files can't
+/// blow past them.
+pub const DEFAULT_BATCH_BYTE_BUDGET: u64 = 4 * 1024 * 1024;
+
+/// Hard ceiling on files per batch regardless of the byte budget, so a
+/// directory of thousands of tiny files still gets split into manageable
+/// requests.
+const BATCH_FILE_COUNT_CEILING: usize = 300;
+
+/// Size and timing information for one instrumentation batch, returned from
+/// `process_items` so callers can tune `byte_budget` to their API's actual
+/// request-size and latency characteristics.
+#[derive(Debug, Clone)]
+pub struct BatchTiming {
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub duration: Duration,
+}
+
+/// Greedily packs `files` (already sorted by size) into batches that stay
+/// under `byte_budget` combined source bytes and `BATCH_FILE_COUNT_CEILING`
+/// files each, so one batch of huge files can't blow past request-size
+/// limits while thousands of tiny files still get packed efficiently.
+fn pack_batches(
+    files: &[(PathBuf, PathBuf)],
+    paths_sizes: &HashMap<PathBuf, u64>,
+    byte_budget: u64,
+) -> Vec<Vec<(PathBuf, PathBuf)>> {
+    let mut batches = vec![];
+    let mut current: Vec<(PathBuf, PathBuf)> = vec![];
+    let mut current_bytes: u64 = 0;
+
+    for (src, dest) in files {
+        let size = *paths_sizes.get(src).unwrap();
+        let would_overflow = !current.is_empty()
+            && (current_bytes + size > byte_budget || current.len() >= BATCH_FILE_COUNT_CEILING);
+        if would_overflow {
+            batches.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current.push((src.clone(), dest.clone()));
+        current_bytes += size;
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// One source file's last-seen instrumentation fingerprint: the inputs that
+/// determined its output (`source_hash`, `import_style`, `api_url`) plus a
+/// hash of the output itself, so a later run can tell whether what's on
+/// disk still matches what we'd produce without re-hitting the API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct CacheEntry {
+    source_hash: String,
+    import_style: String,
+    api_url: String,
+    output_hash: String,
+}
+
+/// Persistent manifest at `.ariana/cache.json` mapping each instrumented
+/// source path to its last `CacheEntry`, turning repeated `ariana`
+/// invocations on an unchanged project into a no-op instead of a full
+/// re-instrumentation.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct InstrumentationCache {
+    #[serde(default)]
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl InstrumentationCache {
+    fn load() -> Self {
+        fs::read_to_string(CACHE_PATH)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the manifest atomically (temp file + rename) so a crash or
+    /// concurrent `ariana` invocation never leaves a half-written manifest.
+    fn save(&self) {
+        let Some(parent) = Path::new(CACHE_PATH).parent() else {
+            return;
+        };
+        if fs::create_dir_all(parent).is_err() {
+            return;
+        }
+        let Ok(serialized) = serde_json::to_vec_pretty(self) else {
+            return;
+        };
+        let tmp_path = format!("{}.tmp", CACHE_PATH);
+        if fs::write(&tmp_path, serialized).is_err() {
+            return;
+        }
+        let _ = fs::rename(&tmp_path, CACHE_PATH);
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// Instruments `files_to_instrument` in batches packed by `pack_batches`,
+/// returning per-batch size/timing info so callers can tune `byte_budget`.
 fn process_instrument_files_in_batches(
     mut files: Vec<(PathBuf, PathBuf)>,
     api_url: &str,
@@ -22,7 +131,12 @@ fn process_instrument_files_in_batches(
     pb: Arc<ProgressBar>,
     is_inplace: bool,
     zip_writer: Option<Arc<std::sync::Mutex<ZipWriter<File>>>>,
-) {
+    old_backup_archive: Option<Arc<std::sync::Mutex<ZipArchive<File>>>>,
+    byte_budget: u64,
+) -> Vec<BatchTiming> {
+    let mut cache = InstrumentationCache::load();
+    let import_style_key = format!("{:?}", import_style);
+
     let mut paths_sizes = HashMap::new();
     files.sort_by(|a, b| {
         let a_size = fs::metadata(&a.0).unwrap().len();
@@ -33,70 +147,232 @@ fn process_instrument_files_in_batches(
         a_size.cmp(&b_size)
     });
 
-    for (i, batch) in files.chunks(300).enumerate() {
-        let mut total_size = 0;
-        for (src, _) in batch {
-            total_size += paths_sizes.get(src).unwrap();
-        }
+    let batches = pack_batches(&files, &paths_sizes, byte_budget);
+    let mut timings = Vec::with_capacity(batches.len());
 
-        let files_contents: Vec<String> = batch
-            .par_iter()
-            .map(|(src, _)| fs::read_to_string(&src).unwrap())
-            .collect();
+    for (i, batch) in batches.iter().enumerate() {
+        let started_at = Instant::now();
+        let total_bytes: u64 = batch.iter().map(|(src, _)| *paths_sizes.get(src).unwrap()).sum();
 
-        let mut src_paths = vec![];
-        let mut dest_paths = vec![];
-        for (src, dest) in batch.into_iter() {
-            src_paths.push(src.clone());
-            dest_paths.push(dest.clone());
-        }
-        let result = instrument_files_batch(
-            &src_paths,
-            files_contents.clone(),
-            api_url.to_string(),
-            vault_key.to_string(),
+        process_one_batch(
+            batch,
+            i,
+            api_url,
+            vault_key,
             import_style,
+            &import_style_key,
+            &pb,
+            is_inplace,
+            &zip_writer,
+            &old_backup_archive,
+            &mut cache,
         );
-        let maybe_instrumented_contents = match result {
-            Ok(maybe_instrumented_contents) => maybe_instrumented_contents,
-            Err(e) => {
-                eprintln!("Could not process batch {} because of: {:?}", i, e.source());
-                continue;
-            }
-        };
 
-        for (((src_path, dest_path), original_content), maybe_instrumented_content) in src_paths
-            .iter()
-            .zip(dest_paths.iter())
-            .zip(files_contents.iter())
-            .zip(maybe_instrumented_contents.iter())
-        {
-            let instrumented_content =
-                if let Some(instrumented_content) = maybe_instrumented_content {
-                    instrumented_content
-                } else {
-                    original_content
-                };
-            if is_inplace {
-                if let Some(ref zw) = zip_writer {
-                    let mut zw = zw.lock().unwrap();
-                    let path_str = src_path.to_string_lossy().to_string();
-                    zw.start_file(&path_str, FileOptions::<()>::default())
-                        .unwrap();
-                    zw.write_all(original_content.as_bytes()).unwrap();
-                    fs::write(src_path, instrumented_content).unwrap();
-                } else {
-                    panic!("No zip writer");
-                }
+        timings.push(BatchTiming {
+            file_count: batch.len(),
+            total_bytes,
+            duration: started_at.elapsed(),
+        });
+    }
+
+    timings
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_one_batch(
+    batch: &[(PathBuf, PathBuf)],
+    batch_index: usize,
+    api_url: &str,
+    vault_key: &str,
+    import_style: &EcmaImportStyle,
+    import_style_key: &str,
+    pb: &Arc<ProgressBar>,
+    is_inplace: bool,
+    zip_writer: &Option<Arc<std::sync::Mutex<ZipWriter<File>>>>,
+    old_backup_archive: &Option<Arc<std::sync::Mutex<ZipArchive<File>>>>,
+    cache: &mut InstrumentationCache,
+) {
+    let files_contents: Vec<String> = batch
+        .par_iter()
+        .map(|(src, _)| fs::read_to_string(&src).unwrap())
+        .collect();
+
+    let mut src_paths = vec![];
+    let mut dest_paths = vec![];
+    for (src, dest) in batch {
+        src_paths.push(src.clone());
+        dest_paths.push(dest.clone());
+    }
+
+    // Split the batch into files whose cache entry is still valid
+    // (nothing to do) and files that actually need to hit the API.
+    let mut to_instrument_indices = vec![];
+    for (idx, (src_path, dest_path)) in src_paths.iter().zip(dest_paths.iter()).enumerate() {
+        let content = &files_contents[idx];
+        let cache_key = src_path.to_string_lossy().to_string();
+        let is_cached = cache.entries.get(&cache_key).is_some_and(|entry| {
+            // In `--inplace` mode `src_path` holds the *previous* run's
+            // instrumented output by the time this run starts (see the
+            // `fs::write(src_path, instrumented_content)` below), so
+            // "unchanged since last run" has to compare against what we
+            // last wrote out (`output_hash`), not what we originally read
+            // in (`source_hash`) - otherwise the disk content never
+            // matches `source_hash` again and every run re-instruments
+            // already-instrumented code.
+            let content_unchanged = if is_inplace {
+                content_hash(content) == entry.output_hash
             } else {
-                if let Some(parent) = dest_path.parent() {
-                    // println!("create dir all {:?}", parent);
-                    fs::create_dir_all(parent).unwrap();
-                }
-                fs::write(dest_path, instrumented_content).unwrap();
+                content_hash(content) == entry.source_hash
+            };
+
+            content_unchanged
+                && entry.import_style == import_style_key
+                && entry.api_url == api_url
+                && is_output_still_current(is_inplace, src_path, dest_path, &entry.output_hash)
+        });
+
+        if is_cached {
+            if is_inplace {
+                copy_forward_backup_entry(zip_writer, old_backup_archive, src_path);
             }
             pb.inc(1);
+        } else {
+            to_instrument_indices.push(idx);
+        }
+    }
+
+    if to_instrument_indices.is_empty() {
+        return;
+    }
+
+    let src_paths: Vec<PathBuf> = to_instrument_indices
+        .iter()
+        .map(|&idx| src_paths[idx].clone())
+        .collect();
+    let dest_paths: Vec<PathBuf> = to_instrument_indices
+        .iter()
+        .map(|&idx| dest_paths[idx].clone())
+        .collect();
+    let files_contents: Vec<String> = to_instrument_indices
+        .iter()
+        .map(|&idx| files_contents[idx].clone())
+        .collect();
+
+    let result = instrument_files_batch(
+        &src_paths,
+        files_contents.clone(),
+        api_url.to_string(),
+        vault_key.to_string(),
+        import_style,
+    );
+    let maybe_instrumented_contents = match result {
+        Ok(maybe_instrumented_contents) => maybe_instrumented_contents,
+        Err(e) => {
+            eprintln!("Could not process batch {} because of: {:?}", batch_index, e.source());
+            return;
+        }
+    };
+
+    for (((src_path, dest_path), original_content), maybe_instrumented_content) in src_paths
+        .iter()
+        .zip(dest_paths.iter())
+        .zip(files_contents.iter())
+        .zip(maybe_instrumented_contents.iter())
+    {
+        let instrumented_content =
+            if let Some(instrumented_content) = maybe_instrumented_content {
+                instrumented_content
+            } else {
+                original_content
+            };
+        if is_inplace {
+            if let Some(ref zw) = zip_writer {
+                let mut zw = zw.lock().unwrap();
+                let path_str = src_path.to_string_lossy().to_string();
+                zw.start_file(&path_str, FileOptions::<()>::default())
+                    .unwrap();
+                zw.write_all(original_content.as_bytes()).unwrap();
+                fs::write(src_path, instrumented_content).unwrap();
+            } else {
+                panic!("No zip writer");
+            }
+        } else {
+            if let Some(parent) = dest_path.parent() {
+                // println!("create dir all {:?}", parent);
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(dest_path, instrumented_content).unwrap();
         }
+
+        cache.entries.insert(
+            src_path.to_string_lossy().to_string(),
+            CacheEntry {
+                source_hash: content_hash(original_content),
+                import_style: import_style_key.to_string(),
+                api_url: api_url.to_string(),
+                output_hash: content_hash(instrumented_content),
+            },
+        );
+
+        pb.inc(1);
+    }
+
+    cache.save();
+}
+
+/// For non-inplace mode, a cache hit additionally requires `dest_path` to
+/// still exist with exactly the content we last wrote, guarding against the
+/// user deleting or editing the output without touching the source. For
+/// inplace mode, `src_path` itself holds the instrumented output between
+/// runs, so the batch-level check of its content against `output_hash`
+/// already covers this.
+fn is_output_still_current(
+    is_inplace: bool,
+    _src_path: &Path,
+    dest_path: &Path,
+    output_hash: &str,
+) -> bool {
+    if is_inplace {
+        return true;
+    }
+    fs::read_to_string(dest_path)
+        .map(|content| content_hash(&content) == output_hash)
+        .unwrap_or(false)
+}
+
+/// When a cache hit skips re-instrumenting a file in `--inplace` mode, the
+/// original (pre-instrumentation) content still needs to land in this run's
+/// backup zip so `restore_backup` keeps working. It isn't on disk anymore
+/// (the source file holds the instrumented output), so it's carried forward
+/// from the previous run's backup zip instead.
+fn copy_forward_backup_entry(
+    zip_writer: &Option<Arc<std::sync::Mutex<ZipWriter<File>>>>,
+    old_backup_archive: &Option<Arc<std::sync::Mutex<ZipArchive<File>>>>,
+    src_path: &Path,
+) {
+    let (Some(zw), Some(old_archive)) = (zip_writer, old_backup_archive) else {
+        return;
+    };
+
+    let path_str = src_path.to_string_lossy().to_string();
+    let mut old_archive = old_archive.lock().unwrap();
+    let Ok(mut old_entry) = old_archive.by_name(&path_str) else {
+        return;
+    };
+
+    let mut original_content = Vec::new();
+    if old_entry.read_to_end(&mut original_content).is_err() {
+        return;
+    }
+    drop(old_entry);
+    drop(old_archive);
+
+    let mut zw = zw.lock().unwrap();
+    if zw
+        .start_file(&path_str, FileOptions::<()>::default())
+        .is_ok()
+    {
+        let _ = zw.write_all(&original_content);
     }
 }
 
@@ -106,7 +382,10 @@ pub fn process_items(
     vault_key: &str,
     import_style: &EcmaImportStyle,
     is_inplace: bool,
-) -> Result<(), String> {
+    byte_budget: u64,
+) -> Result<Vec<BatchTiming>, String> {
+    let batch_timings: Arc<std::sync::Mutex<Vec<BatchTiming>>> = Arc::new(std::sync::Mutex::new(Vec::new()));
+
     // Calculate total for progress bar
     let total = if is_inplace {
         items.files_to_instrument.len() as u64
@@ -128,10 +407,18 @@ pub fn process_items(
     // Process items based on is_inplace flag
     if is_inplace {
         fs::create_dir_all(".ariana").map_err(|_| format!("Couldn't create .ariana"))?;
+        // Open the previous run's backup archive (if any) before truncating
+        // it below, so files skipped this run via the instrumentation cache
+        // can still have their original content carried forward into the
+        // new archive instead of being lost forever.
+        let old_backup_archive = File::open(".ariana/__ariana_backups.zip")
+            .ok()
+            .and_then(|f| ZipArchive::new(f).ok())
+            .map(|archive| Arc::new(std::sync::Mutex::new(archive)));
         let zip_file = File::create(".ariana/__ariana_backups.zip")
             .map_err(|_| format!("Couldn't create .ariana/__ariana_backups.zip"))?;
         let zip_writer = Arc::new(std::sync::Mutex::new(ZipWriter::new(zip_file)));
-        process_instrument_files_in_batches(
+        let timings = process_instrument_files_in_batches(
             items.files_to_instrument.to_vec(),
             api_url,
             vault_key,
@@ -139,7 +426,10 @@ pub fn process_items(
             pb.clone(),
             true,
             Some(zip_writer),
+            old_backup_archive,
+            byte_budget,
         );
+        batch_timings.lock().unwrap().extend(timings);
     } else {
         rayon::scope(|s| {
             // Process directories to link or copy
@@ -179,8 +469,9 @@ pub fn process_items(
             }
 
             // Process files_to_instrument in batches within a separate task
-            s.spawn(|_| {
-                process_instrument_files_in_batches(
+            let batch_timings = batch_timings.clone();
+            s.spawn(move |_| {
+                let timings = process_instrument_files_in_batches(
                     items.files_to_instrument.to_vec(),
                     api_url,
                     vault_key,
@@ -188,7 +479,10 @@ pub fn process_items(
                     pb.clone(),
                     false,
                     None,
+                    None,
+                    byte_budget,
                 );
+                batch_timings.lock().unwrap().extend(timings);
             });
         });
     }
@@ -196,7 +490,9 @@ pub fn process_items(
     // Finalize progress bar and message thread
     pb.finish();
 
-    Ok(())
+    Ok(Arc::try_unwrap(batch_timings)
+        .map(|mutex| mutex.into_inner().unwrap())
+        .unwrap_or_default())
 }
 
 pub fn restore_backup(items: &CollectedItems) -> Result<()> {
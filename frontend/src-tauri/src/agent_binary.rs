@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::process::{Output, Stdio};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, resolve_ssh_key_path, ssh_identity_args};
+
+/// Version of this build, compared against whatever the remote agent
+/// already has installed to decide whether the helper binary needs
+/// (re)uploading.
+const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const AGENT_BIN_NAME: &str = "ariana-agent";
+const AGENT_CACHE_DIR: &str = ".ariana/bin";
+
+/// Tracks which (host, version) pairs already have a confirmed-current
+/// agent binary, so reconnecting to the same agent within a session
+/// doesn't re-probe/re-upload every time.
+#[derive(Default)]
+pub struct AgentBinaryCache {
+    provisioned: Mutex<HashSet<(String, String)>>,
+}
+
+impl AgentBinaryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AgentBinaryProgress<'a> {
+    host: &'a str,
+    stage: &'a str,
+}
+
+fn emit_progress(app_handle: &AppHandle, host: &str, stage: &str) {
+    let _ = app_handle.emit(
+        "agent-binary-upload-progress",
+        AgentBinaryProgress { host, stage },
+    );
+}
+
+fn run_remote(host: &str, command: &str) -> Result<Output, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run remote command on {}: {}", host, e))
+}
+
+/// Remote OS/arch, e.g. `linux-x86_64`, resolved via `uname -s`/`uname -m`
+/// and normalized to match our prebuilt binary directory naming.
+fn remote_os_arch(host: &str) -> Result<String, String> {
+    let output = run_remote(host, "echo \"$(uname -s)-$(uname -m)\"")?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to detect OS/arch on {}: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let raw = String::from_utf8_lossy(&output.stdout).trim().to_lowercase();
+    Ok(raw.replace("arm64", "aarch64"))
+}
+
+fn remote_version_dir(version: &str) -> String {
+    format!("~/{}/{}", AGENT_CACHE_DIR, version)
+}
+
+fn remote_current_binary_path() -> String {
+    format!("~/{}/current/{}", AGENT_CACHE_DIR, AGENT_BIN_NAME)
+}
+
+/// Reads the version the remote's currently-linked agent binary reports via
+/// `--version`, or `None` if it isn't installed (or fails to run).
+fn remote_installed_version(host: &str) -> Option<String> {
+    let command = format!("{} --version 2>/dev/null", remote_current_binary_path());
+    let output = run_remote(host, &command).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+/// Locates the prebuilt `ariana-agent` binary for `os_arch` bundled as a
+/// Tauri resource, under `agent-binaries/<os_arch>/ariana-agent`.
+fn local_prebuilt_binary_path(
+    app_handle: &AppHandle,
+    os_arch: &str,
+) -> Result<std::path::PathBuf, String> {
+    let resource_dir = app_handle
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to resolve resource directory: {}", e))?;
+
+    let candidate = resource_dir
+        .join("agent-binaries")
+        .join(os_arch)
+        .join(AGENT_BIN_NAME);
+
+    if !candidate.exists() {
+        return Err(format!(
+            "No prebuilt {} binary bundled for {}",
+            AGENT_BIN_NAME, os_arch
+        ));
+    }
+
+    Ok(candidate)
+}
+
+fn upload_binary(host: &str, local_path: &std::path::Path, version: &str) -> Result<(), String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    let version_dir = remote_version_dir(version);
+    let remote_bin_path = format!("{}/{}", version_dir, AGENT_BIN_NAME);
+    let current_dir = format!("~/{}/current", AGENT_CACHE_DIR);
+
+    let remote_command = format!(
+        "mkdir -p {version_dir} && cat > {remote_bin_path} && chmod +x {remote_bin_path} && rm -rf {current_dir} && ln -sfn {version_dir} {current_dir}",
+        version_dir = version_dir,
+        remote_bin_path = remote_bin_path,
+        current_dir = current_dir,
+    );
+
+    let binary_contents = fs::read(local_path)
+        .map_err(|e| format!("Failed to read local agent binary {:?}: {}", local_path, e))?;
+
+    let mut child = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(remote_command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn agent binary upload to {}: {}", host, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open stdin for agent binary upload".to_string())?
+        .write_all(&binary_contents)
+        .map_err(|e| format!("Failed to stream agent binary to {}: {}", host, e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for agent binary upload to {}: {}", host, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Agent binary upload to {} failed: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ensures `host` has a current-version `ariana-agent` binary installed
+/// under `~/.ariana/bin`, uploading the prebuilt binary for its OS/arch if
+/// it's missing or out of date. Returns the path to invoke it at.
+#[tauri::command]
+pub fn ensure_agent_binary(
+    host: String,
+    app_handle: AppHandle,
+    agent_binary_cache: tauri::State<'_, std::sync::Arc<AgentBinaryCache>>,
+) -> Result<String, String> {
+    let cache_key = (host.clone(), SERVER_VERSION.to_string());
+    {
+        let provisioned = agent_binary_cache
+            .provisioned
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        if provisioned.contains(&cache_key) {
+            return Ok(remote_current_binary_path());
+        }
+    }
+
+    emit_progress(&app_handle, &host, "checking");
+
+    if remote_installed_version(&host).as_deref() == Some(SERVER_VERSION) {
+        agent_binary_cache
+            .provisioned
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?
+            .insert(cache_key);
+        emit_progress(&app_handle, &host, "up-to-date");
+        return Ok(remote_current_binary_path());
+    }
+
+    let os_arch = remote_os_arch(&host)?;
+    let local_path = local_prebuilt_binary_path(&app_handle, &os_arch)?;
+
+    emit_progress(&app_handle, &host, "uploading");
+    upload_binary(&host, &local_path, SERVER_VERSION)?;
+    emit_progress(&app_handle, &host, "done");
+
+    agent_binary_cache
+        .provisioned
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .insert(cache_key);
+
+    Ok(remote_current_binary_path())
+}
@@ -1,4 +1,7 @@
-use std::process::Command;
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
 /// Creates a new Command with platform-specific configurations to prevent
 /// console windows from appearing on Windows.
@@ -32,3 +35,93 @@ pub fn new_command(program: impl AsRef<str>) -> Command {
         Command::new(program)
     }
 }
+
+/// Spawns `cmd` (stdout/stderr piped) and bounds how long it may run. Pipes
+/// are drained on background reader threads so a full stderr buffer can't
+/// deadlock the child before the deadline; if it hasn't exited by `timeout`
+/// it's killed and reaped (never left as a zombie) and a distinct timeout
+/// error is returned instead of its output.
+///
+/// # Example
+/// ```
+/// use command_utils::{new_command, output_with_timeout};
+/// use std::time::Duration;
+///
+/// let output = output_with_timeout(&mut new_command("git").arg("status"), Duration::from_secs(30))?;
+/// ```
+pub fn output_with_timeout(cmd: &mut Command, timeout: Duration) -> Result<Output, String> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let mut stdout_pipe = child.stdout.take().ok_or_else(|| "Failed to capture stdout".to_string())?;
+    let mut stderr_pipe = child.stderr.take().ok_or_else(|| "Failed to capture stderr".to_string())?;
+
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout_pipe.read_to_end(&mut buf);
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| format!("Failed to poll command: {}", e))? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = stdout_reader.join();
+            let _ = stderr_reader.join();
+            return Err(format!("command timed out after {}s", timeout.as_secs()));
+        }
+
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    Ok(Output { status, stdout, stderr })
+}
+
+/// The platform/arch/OS-family variables `TerminalManager` injects into
+/// spawned shells, for any other command invocation that wants the spawned
+/// process to see the same runtime context - mirrors how Tauri's own build
+/// tooling exposes `TAURI_*`/target-triple env to spawned hook commands.
+pub fn derived_runtime_env() -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    env.insert("TAURI_PLATFORM".to_string(), std::env::consts::OS.to_string());
+    env.insert("TAURI_ARCH".to_string(), std::env::consts::ARCH.to_string());
+    env.insert("TAURI_FAMILY".to_string(), std::env::consts::FAMILY.to_string());
+    env
+}
+
+/// Applies `derived_runtime_env()` to `cmd`, then layers `overrides` on top
+/// so a caller-supplied value always wins over a derived one with the same
+/// name.
+///
+/// # Example
+/// ```
+/// use command_utils::{env_overrides, new_command};
+/// use std::collections::HashMap;
+///
+/// let mut overrides = HashMap::new();
+/// overrides.insert("MY_VAR".to_string(), "1".to_string());
+/// let output = env_overrides(&mut new_command("sh").arg("-c").arg("env"), &overrides).output()?;
+/// ```
+pub fn env_overrides<'a>(cmd: &'a mut Command, overrides: &HashMap<String, String>) -> &'a mut Command {
+    for (key, value) in derived_runtime_env() {
+        cmd.env(key, value);
+    }
+    for (key, value) in overrides {
+        cmd.env(key, value);
+    }
+    cmd
+}
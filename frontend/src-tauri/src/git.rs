@@ -2,11 +2,24 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 use crate::command_utils::new_command;
 
+mod remote;
+pub use remote::{Protocol, RemoteInfo};
+
+mod status;
+pub use status::{GitContext, GitOperation};
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitHubProjectInfo {
+    /// Canonical browseable URL, kept for backward compatibility with callers
+    /// that only care about GitHub; empty if there's no remote, and the
+    /// original remote URL unchanged if it doesn't match any known shape.
     pub github_url: String,
     pub git_root: String,
+    /// Structured remote info for any provider (GitHub, GitLab, Bitbucket,
+    /// Gitea, self-hosted, ...). `None` if the remote URL didn't match any
+    /// known shape, e.g. a local filesystem path.
+    pub remote: Option<RemoteInfo>,
 }
 
 #[tauri::command]
@@ -40,38 +53,60 @@ pub async fn get_github_remote_url(folder_path: String) -> Result<Option<GitHubP
         return Ok(Some(GitHubProjectInfo {
             github_url: String::new(),
             git_root: git_root_str,
+            remote: None,
         }));
     }
 
     let remote_output = String::from_utf8_lossy(&output.stdout);
 
-    // Look for GitHub URLs in the remotes
+    // Prefer "origin", but fall back to the first remote we find.
+    let mut origin_url: Option<&str> = None;
+    let mut first_url: Option<&str> = None;
     for line in remote_output.lines() {
-        if line.contains("github.com") {
-            // Extract the URL from the line
-            // Format is typically: "origin	https://github.com/user/repo.git (fetch)"
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let url = parts[1];
-                // Clean up the URL (remove .git suffix if present)
-                let clean_url = if url.ends_with(".git") {
-                    &url[..url.len() - 4]
-                } else {
-                    url
-                };
-                return Ok(Some(GitHubProjectInfo {
-                    github_url: clean_url.to_string(),
-                    git_root: git_root_str,
-                }));
-            }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            continue;
+        }
+        first_url.get_or_insert(parts[1]);
+        if parts[0] == "origin" {
+            origin_url = Some(parts[1]);
+            break;
         }
     }
 
-    // No GitHub remote found, but we still have a git repo
-    Ok(Some(GitHubProjectInfo {
-        github_url: String::new(), // Empty string indicates no GitHub remote
-        git_root: git_root_str,
-    }))
+    let Some(url) = origin_url.or(first_url) else {
+        // No remotes configured, but we still have a git repo
+        return Ok(Some(GitHubProjectInfo {
+            github_url: String::new(),
+            git_root: git_root_str,
+            remote: None,
+        }));
+    };
+
+    match remote::parse_remote_url(url) {
+        Some(remote_info) => Ok(Some(GitHubProjectInfo {
+            github_url: remote_info.web_url.clone(),
+            git_root: git_root_str,
+            remote: Some(remote_info),
+        })),
+        // Doesn't match any known shape (e.g. a local path remote):
+        // preserve the original URL rather than discarding it.
+        None => Ok(Some(GitHubProjectInfo {
+            github_url: url.to_string(),
+            git_root: git_root_str,
+            remote: None,
+        })),
+    }
+}
+
+/// Returns branch/detached-HEAD state, in-progress merge/rebase/cherry-pick/
+/// bisect state, and staged/unstaged/untracked counts for the repository
+/// containing `path`. `Ok(None)` (not an error) when `path` isn't inside a
+/// git repository at all.
+#[tauri::command]
+pub async fn get_git_context(path: String) -> Result<Option<GitContext>, String> {
+    let clean_path = crate::to_clean_absolute_path(&path);
+    Ok(status::git_context(Path::new(&clean_path)))
 }
 
 fn find_git_repo(start_path: &Path) -> Result<Option<std::path::PathBuf>, String> {
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+/// Transport used by a git remote URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Ssh,
+    Https,
+    Git,
+    File,
+}
+
+/// A git remote URL, normalized across hosting providers.
+///
+/// Supports scp-like syntax (`user@host:owner/repo.git`), `ssh://`, `https://`
+/// and `git://` remotes, including self-hosted GitLab/Bitbucket/Gitea instances
+/// and GitLab-style nested subgroups (`owner/subgroup/repo`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteInfo {
+    pub protocol: Protocol,
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+    /// Canonical browseable URL (e.g. `https://github.com/owner/repo`).
+    pub web_url: String,
+    /// The original remote URL as returned by `git remote -v`, unmodified.
+    pub original_url: String,
+}
+
+/// Parses a git remote URL into structured, provider-agnostic info.
+///
+/// Returns `None` if the URL doesn't match any known shape (e.g. a local
+/// filesystem path); callers should fall back to the original URL in that case.
+pub fn parse_remote_url(url: &str) -> Option<RemoteInfo> {
+    let url = url.trim();
+
+    if let Some(rest) = url.strip_prefix("ssh://") {
+        let rest = rest.strip_prefix("git@").unwrap_or(rest);
+        let (host_and_port, path) = rest.split_once('/')?;
+        let host = strip_port(host_and_port);
+        let (owner, repo) = split_owner_repo(path)?;
+        return Some(build_info(Protocol::Ssh, host, owner, repo, url));
+    }
+
+    if let Some(rest) = url.strip_prefix("git://") {
+        let (host_and_port, path) = rest.split_once('/')?;
+        let host = strip_port(host_and_port);
+        let (owner, repo) = split_owner_repo(path)?;
+        return Some(build_info(Protocol::Git, host, owner, repo, url));
+    }
+
+    if let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        let (host_and_port, path) = rest.split_once('/')?;
+        // Strip any userinfo (e.g. "user@host").
+        let host_and_port = host_and_port.rsplit('@').next().unwrap_or(host_and_port);
+        let host = strip_port(host_and_port);
+        let (owner, repo) = split_owner_repo(path)?;
+        return Some(build_info(Protocol::Https, host, owner, repo, url));
+    }
+
+    // scp-like syntax: user@host:owner/repo(.git), with no scheme.
+    if !url.contains("://") {
+        if let Some((user_host, path)) = url.split_once(':') {
+            // Avoid misparsing Windows-style paths like "C:\foo" as scp syntax.
+            if path.starts_with('/') || path.starts_with('\\') {
+                return None;
+            }
+            let host = user_host.rsplit('@').next().unwrap_or(user_host);
+            if host.is_empty() || host.contains('/') {
+                return None;
+            }
+            let (owner, repo) = split_owner_repo(path)?;
+            return Some(build_info(Protocol::Ssh, host.to_string(), owner, repo, url));
+        }
+    }
+
+    None
+}
+
+fn strip_port(host_and_port: &str) -> String {
+    host_and_port.split(':').next().unwrap_or(host_and_port).to_string()
+}
+
+/// Splits a remote path into an owner (which may contain nested subgroups,
+/// e.g. GitLab's `group/subgroup`) and the final repo name, stripping a
+/// trailing `.git` and any trailing slash.
+fn split_owner_repo(path: &str) -> Option<(String, String)> {
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    let (owner, repo) = path.rsplit_once('/')?;
+    if owner.is_empty() || repo.is_empty() {
+        return None;
+    }
+
+    Some((owner.to_string(), repo.to_string()))
+}
+
+fn build_info(protocol: Protocol, host: String, owner: String, repo: String, original_url: &str) -> RemoteInfo {
+    let web_url = format!("https://{}/{}/{}", host, owner, repo);
+
+    RemoteInfo {
+        protocol,
+        host,
+        owner,
+        repo,
+        web_url,
+        original_url: original_url.to_string(),
+    }
+}
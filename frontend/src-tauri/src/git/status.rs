@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+
+/// In-progress multi-step operation detected from its marker file/directory
+/// under `.git` - git refuses to start most of these while another is
+/// already in progress, so in practice at most one is ever active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GitOperation {
+    None,
+    Merge,
+    Rebase,
+    CherryPick,
+    Bisect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitContext {
+    pub repo_root: String,
+    /// Current branch name; `None` when HEAD is detached.
+    pub branch: Option<String>,
+    /// Commit HEAD points at when detached; `None` on an attached branch.
+    pub detached_commit: Option<String>,
+    pub operation: GitOperation,
+    pub staged_count: usize,
+    pub unstaged_count: usize,
+    pub untracked_count: usize,
+}
+
+/// Builds a `GitContext` for `path` without shelling out to `git`, by
+/// reading `.git`'s on-disk format directly (refs, HEAD, the index, and
+/// loose objects). Returns `None` if no repository is found walking up from
+/// `path`.
+///
+/// Two corners are deliberately simplified rather than reimplementing git in
+/// full: staged-vs-HEAD comparison only resolves loose objects (a repo that's
+/// been `git gc`'d or cloned stores commits/trees in packfiles this reader
+/// doesn't parse, so no HEAD tree is resolved there and every index entry
+/// is counted as staged), and the unstaged check is
+/// the same mtime+size heuristic git itself uses to skip hashing unchanged
+/// files, not a full content diff.
+pub fn git_context(path: &Path) -> Option<GitContext> {
+    let (repo_root, git_dir) = find_git_dir(path)?;
+
+    let head = read_head(&git_dir);
+    let (branch, detached_commit) = match &head {
+        Some(HeadState::Branch(name)) => (Some(name.clone()), None),
+        Some(HeadState::Detached(commit)) => (None, Some(commit.clone())),
+        None => (None, None),
+    };
+
+    let operation = detect_operation(&git_dir);
+
+    let index = read_index(&git_dir);
+    let index_supported = index.is_some();
+    let index_entries = index.unwrap_or_default();
+    let index_by_path: HashMap<&Path, &IndexEntry> =
+        index_entries.iter().map(|e| (e.path.as_path(), e)).collect();
+
+    let head_commit = resolve_head_commit(&git_dir, &head);
+    let head_blobs = head_commit
+        .map(|commit| resolve_head_blobs(&git_dir, &commit))
+        .unwrap_or_default();
+
+    let mut staged_count = 0;
+    for entry in &index_entries {
+        match head_blobs.get(&entry.path) {
+            Some(head_sha) if *head_sha == entry.sha1 => {}
+            _ => staged_count += 1, // newly added or modified relative to HEAD
+        }
+    }
+    for head_path in head_blobs.keys() {
+        if index_supported && !index_by_path.contains_key(head_path.as_path()) {
+            staged_count += 1; // staged deletion
+        }
+    }
+
+    let mut unstaged_count = 0;
+    for entry in &index_entries {
+        let full_path = repo_root.join(&entry.path);
+        match fs::metadata(&full_path) {
+            Ok(meta) => {
+                if !stat_matches_index(&meta, entry) {
+                    unstaged_count += 1;
+                }
+            }
+            Err(_) => unstaged_count += 1, // deleted from the working tree
+        }
+    }
+
+    let untracked_count = count_untracked(&repo_root, &git_dir, &index_by_path);
+
+    Some(GitContext {
+        repo_root: repo_root.to_string_lossy().to_string(),
+        branch,
+        detached_commit,
+        operation,
+        staged_count,
+        unstaged_count,
+        untracked_count,
+    })
+}
+
+enum HeadState {
+    Branch(String),
+    Detached(String),
+}
+
+struct IndexEntry {
+    path: PathBuf,
+    mtime_secs: u32,
+    size: u32,
+    sha1: [u8; 20],
+}
+
+/// Walks up from `start_path` to find a `.git` directory, resolving the
+/// `gitdir: <path>` pointer file used by worktrees and submodules. Respects
+/// the same `\\?\`-stripped, absolute form `to_clean_absolute_path` produces.
+fn find_git_dir(start_path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let mut current = start_path;
+
+    loop {
+        let dot_git = current.join(".git");
+
+        if dot_git.is_dir() {
+            return Some((current.to_path_buf(), dot_git));
+        }
+
+        if dot_git.is_file() {
+            if let Ok(contents) = fs::read_to_string(&dot_git) {
+                if let Some(rest) = contents.trim().strip_prefix("gitdir: ") {
+                    let linked_dir = current.join(rest);
+                    if linked_dir.is_dir() {
+                        return Some((current.to_path_buf(), linked_dir));
+                    }
+                }
+            }
+        }
+
+        current = current.parent()?;
+    }
+}
+
+fn read_head(git_dir: &Path) -> Option<HeadState> {
+    let contents = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let contents = contents.trim();
+
+    if let Some(rest) = contents.strip_prefix("ref: ") {
+        let branch = rest.strip_prefix("refs/heads/").unwrap_or(rest);
+        Some(HeadState::Branch(branch.to_string()))
+    } else if !contents.is_empty() {
+        Some(HeadState::Detached(contents.to_string()))
+    } else {
+        None
+    }
+}
+
+fn detect_operation(git_dir: &Path) -> GitOperation {
+    if git_dir.join("MERGE_HEAD").exists() {
+        GitOperation::Merge
+    } else if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+        GitOperation::Rebase
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        GitOperation::CherryPick
+    } else if git_dir.join("BISECT_LOG").exists() {
+        GitOperation::Bisect
+    } else {
+        GitOperation::None
+    }
+}
+
+/// Resolves the commit sha `head` currently points at, reading the loose ref
+/// file first and falling back to `packed-refs` for branches git has packed.
+fn resolve_head_commit(git_dir: &Path, head: &Option<HeadState>) -> Option<String> {
+    match head {
+        Some(HeadState::Detached(commit)) => Some(commit.clone()),
+        Some(HeadState::Branch(branch)) => {
+            if let Ok(contents) = fs::read_to_string(git_dir.join("refs/heads").join(branch)) {
+                return Some(contents.trim().to_string());
+            }
+
+            let packed = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+            let target = format!("refs/heads/{}", branch);
+            packed.lines().find_map(|line| {
+                let (sha, name) = line.split_once(' ')?;
+                (name == target).then(|| sha.to_string())
+            })
+        }
+        None => None,
+    }
+}
+
+/// Parses the `.git/index` file directly (version 2/3 on-disk format; see
+/// `Documentation/technical/index-format.txt` in git's own source tree).
+/// Version 4's path-compressed entries aren't supported - `None` is
+/// returned for those repos, which `git_context` reports as zero staged and
+/// zero dirty-via-stat-mismatch files rather than guessing.
+fn read_index(git_dir: &Path) -> Option<Vec<IndexEntry>> {
+    let data = match fs::read(git_dir.join("index")) {
+        Ok(data) => data,
+        Err(_) => return Some(Vec::new()),
+    };
+
+    if data.len() < 12 || &data[0..4] != b"DIRC" {
+        return Some(Vec::new());
+    }
+
+    let version = u32::from_be_bytes(data[4..8].try_into().unwrap());
+    if version == 4 {
+        return None;
+    }
+
+    let entry_count = u32::from_be_bytes(data[8..12].try_into().unwrap()) as usize;
+    let mut entries = Vec::with_capacity(entry_count);
+    let mut offset = 12usize;
+
+    for _ in 0..entry_count {
+        if offset + 62 > data.len() {
+            break;
+        }
+
+        let mtime_secs = u32::from_be_bytes(data[offset + 8..offset + 12].try_into().unwrap());
+        let size = u32::from_be_bytes(data[offset + 36..offset + 40].try_into().unwrap());
+        let mut sha1 = [0u8; 20];
+        sha1.copy_from_slice(&data[offset + 40..offset + 60]);
+        let flags = u16::from_be_bytes(data[offset + 60..offset + 62].try_into().unwrap());
+        let stored_name_len = (flags & 0x0FFF) as usize;
+        let extended = flags & 0x4000 != 0;
+
+        let fixed_len = 62 + if extended { 2 } else { 0 };
+        let name_start = offset + fixed_len;
+
+        let (name_len, path) = if stored_name_len < 0x0FFF {
+            if name_start + stored_name_len > data.len() {
+                break;
+            }
+            let name = String::from_utf8_lossy(&data[name_start..name_start + stored_name_len]).into_owned();
+            (stored_name_len, PathBuf::from(name))
+        } else {
+            // Name length >= 0xFFF is stored as a sentinel; scan for the NUL
+            // terminator instead of trusting the flags field.
+            match data[name_start..].iter().position(|&b| b == 0) {
+                Some(nul_offset) => {
+                    let name = String::from_utf8_lossy(&data[name_start..name_start + nul_offset]).into_owned();
+                    (nul_offset, PathBuf::from(name))
+                }
+                None => break,
+            }
+        };
+
+        entries.push(IndexEntry { path, mtime_secs, size, sha1 });
+
+        // Padded with 1-8 NUL bytes (including the name's terminator) so the
+        // whole entry is a multiple of 8 bytes.
+        let base_len = fixed_len + name_len;
+        let padded_total = (base_len / 8 + 1) * 8;
+        offset += padded_total;
+    }
+
+    Some(entries)
+}
+
+/// The same stat-only heuristic `git status` uses to skip hashing files that
+/// plainly haven't changed: if size and mtime still match what's recorded in
+/// the index, treat the file as clean.
+fn stat_matches_index(meta: &fs::Metadata, entry: &IndexEntry) -> bool {
+    if meta.len() as u32 != entry.size {
+        return false;
+    }
+
+    meta.modified()
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as u32 == entry.mtime_secs)
+        .unwrap_or(false)
+}
+
+/// Reads and zlib-inflates a loose object by its hex sha1, returning its type
+/// tag (`"commit"`, `"tree"`, `"blob"`, ...) and content. Returns `None` for
+/// an object git has packed away instead of keeping loose.
+fn read_loose_object(git_dir: &Path, sha1_hex: &str) -> Option<(String, Vec<u8>)> {
+    if sha1_hex.len() < 3 {
+        return None;
+    }
+
+    let object_path = git_dir.join("objects").join(&sha1_hex[0..2]).join(&sha1_hex[2..]);
+    let compressed = fs::read(object_path).ok()?;
+
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(&compressed[..]).read_to_end(&mut decompressed).ok()?;
+
+    let nul_pos = decompressed.iter().position(|&b| b == 0)?;
+    let header = std::str::from_utf8(&decompressed[..nul_pos]).ok()?;
+    let (object_type, _size) = header.split_once(' ')?;
+
+    Some((object_type.to_string(), decompressed[nul_pos + 1..].to_vec()))
+}
+
+fn sha1_hex(bytes: &[u8; 20]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Walks the tree of `commit_hex` (loose objects only - see `git_context`'s
+/// doc comment) to build a map of every file path to the blob it holds at
+/// HEAD, so the index can be diffed against it for `staged_count`.
+fn resolve_head_blobs(git_dir: &Path, commit_hex: &str) -> HashMap<PathBuf, [u8; 20]> {
+    let mut blobs = HashMap::new();
+
+    let Some((object_type, data)) = read_loose_object(git_dir, commit_hex) else {
+        return blobs;
+    };
+    if object_type != "commit" {
+        return blobs;
+    }
+
+    let tree_hex = String::from_utf8_lossy(&data)
+        .lines()
+        .find_map(|line| line.strip_prefix("tree ").map(|s| s.to_string()));
+
+    if let Some(tree_hex) = tree_hex {
+        walk_tree(git_dir, &tree_hex, PathBuf::new(), &mut blobs);
+    }
+
+    blobs
+}
+
+fn walk_tree(git_dir: &Path, tree_hex: &str, prefix: PathBuf, blobs: &mut HashMap<PathBuf, [u8; 20]>) {
+    let Some((object_type, data)) = read_loose_object(git_dir, tree_hex) else {
+        return;
+    };
+    if object_type != "tree" {
+        return;
+    }
+
+    for (name, is_tree, sha1) in parse_tree_entries(&data) {
+        let path = prefix.join(&name);
+        if is_tree {
+            walk_tree(git_dir, &sha1_hex(&sha1), path, blobs);
+        } else {
+            blobs.insert(path, sha1);
+        }
+    }
+}
+
+/// Parses a tree object's body: repeated `<mode> <name>\0<20-byte sha1>`.
+fn parse_tree_entries(data: &[u8]) -> Vec<(String, bool, [u8; 20])> {
+    let mut entries = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let Some(space) = data[i..].iter().position(|&b| b == b' ') else {
+            break;
+        };
+        let space = i + space;
+        let mode = String::from_utf8_lossy(&data[i..space]);
+
+        let Some(nul) = data[space + 1..].iter().position(|&b| b == 0) else {
+            break;
+        };
+        let nul = space + 1 + nul;
+
+        if nul + 21 > data.len() {
+            break;
+        }
+
+        let name = String::from_utf8_lossy(&data[space + 1..nul]).into_owned();
+        let mut sha1 = [0u8; 20];
+        sha1.copy_from_slice(&data[nul + 1..nul + 21]);
+
+        entries.push((name, mode == "40000", sha1));
+        i = nul + 21;
+    }
+
+    entries
+}
+
+/// Counts working-tree files that aren't tracked in the index, respecting
+/// `.gitignore`/global gitignore/`.git/info/exclude` the same way the
+/// project-upload zipper already does via the `ignore` crate.
+fn count_untracked(repo_root: &Path, git_dir: &Path, tracked: &HashMap<&Path, &IndexEntry>) -> usize {
+    // For a submodule or linked worktree, `git_dir` is the external
+    // `gitdir:`-pointed-to directory (e.g. `<main>/.git/modules/<sub>`), not
+    // `repo_root.join(".git")` - and at that root, `.git` itself is a
+    // regular *file* (the gitlink pointer) that the walk below happily
+    // yields like any other file, since `ignore` doesn't special-case it.
+    // Exclude the gitlink path explicitly so it isn't counted as a spurious
+    // untracked file on every submodule/worktree checkout.
+    let dot_git_path = repo_root.join(".git");
+
+    let walker = ignore::WalkBuilder::new(repo_root)
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .build();
+
+    walker
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter(|entry| !entry.path().starts_with(git_dir) && entry.path() != dot_git_path)
+        .filter_map(|entry| entry.path().strip_prefix(repo_root).map(|p| p.to_path_buf()).ok())
+        .filter(|relative| !tracked.contains_key(relative.as_path()))
+        .count()
+}
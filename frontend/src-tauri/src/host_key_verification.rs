@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::get_ssh_directory;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// What to do when no known_hosts entry exists yet for a host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TofuPolicy {
+    /// Trust the presented key on first use and record it in the
+    /// Ariana-managed known_hosts file.
+    AutoAdd,
+    /// Refuse to connect to a host we've never seen before.
+    Reject,
+}
+
+/// Why a host key failed verification.
+#[derive(Debug, Clone)]
+pub enum HostKeyError {
+    /// A known_hosts line already has a *different* key on file for this
+    /// host - the classic "remote host identification has changed" case,
+    /// which is exactly what a MITM swapping out the remote end looks like.
+    KeyChanged { line: usize, key_type: String },
+    /// A known_hosts line marks this exact key as `@revoked`.
+    Revoked { line: usize },
+    /// Couldn't read/write known_hosts, or TOFU was disabled for an unseen host.
+    Io(String),
+}
+
+impl std::fmt::Display for HostKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostKeyError::KeyChanged { line, key_type } => write!(
+                f,
+                "REMOTE HOST IDENTIFICATION HAS CHANGED! A different {key_type} key is already known for this host (known_hosts line {line}) - possible MITM attack, refusing to connect"
+            ),
+            HostKeyError::Revoked { line } => write!(
+                f,
+                "This host's key is marked @revoked in known_hosts (line {line}) - refusing to connect"
+            ),
+            HostKeyError::Io(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for HostKeyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KnownHostMarker {
+    CertAuthority,
+    Revoked,
+}
+
+/// A `known_hosts` host field, either a plain/wildcard pattern or a hashed
+/// entry (`|1|<salt>|<hash>`, see `hashed_host_matches`).
+enum HostPattern {
+    Plain(String),
+    Hashed { salt: Vec<u8>, hash: Vec<u8> },
+}
+
+struct KnownHostEntry {
+    marker: Option<KnownHostMarker>,
+    patterns: Vec<HostPattern>,
+    key_type: String,
+    key_blob: String,
+    line_number: usize,
+}
+
+/// Parses one `known_hosts` line: an optional `@cert-authority`/`@revoked`
+/// marker, a comma-separated host-pattern field, a key type, and a base64
+/// key blob. Returns `None` for blank lines, comments, and malformed lines.
+fn parse_known_hosts_line(line: &str, line_number: usize) -> Option<KnownHostEntry> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let mut host_field = fields.next()?;
+
+    let marker = match host_field {
+        "@cert-authority" => {
+            host_field = fields.next()?;
+            Some(KnownHostMarker::CertAuthority)
+        }
+        "@revoked" => {
+            host_field = fields.next()?;
+            Some(KnownHostMarker::Revoked)
+        }
+        _ => None,
+    };
+
+    let key_type = fields.next()?.to_string();
+    let key_blob = fields.next()?.to_string();
+    let patterns = host_field.split(',').map(parse_host_pattern).collect();
+
+    Some(KnownHostEntry { marker, patterns, key_type, key_blob, line_number })
+}
+
+fn parse_host_pattern(pattern: &str) -> HostPattern {
+    if let Some(rest) = pattern.strip_prefix("|1|") {
+        if let Some((salt_b64, hash_b64)) = rest.split_once('|') {
+            if let (Ok(salt), Ok(hash)) = (
+                general_purpose::STANDARD.decode(salt_b64),
+                general_purpose::STANDARD.decode(hash_b64),
+            ) {
+                return HostPattern::Hashed { salt, hash };
+            }
+        }
+    }
+
+    HostPattern::Plain(pattern.to_string())
+}
+
+fn host_pattern_matches(pattern: &HostPattern, host: &str) -> bool {
+    match pattern {
+        HostPattern::Plain(glob) => glob_match(glob, host),
+        HostPattern::Hashed { salt, hash } => hashed_host_matches(salt, hash, host),
+    }
+}
+
+/// Minimal `*`/`?` glob matcher, case-insensitively (hostnames aren't
+/// case-sensitive).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[u8], text: &[u8]) -> bool {
+        match (pattern.first(), text.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => matches(&pattern[1..], text) || (!text.is_empty() && matches(pattern, &text[1..])),
+            (Some(b'?'), Some(_)) => matches(&pattern[1..], &text[1..]),
+            (Some(p), Some(t)) if p.eq_ignore_ascii_case(t) => matches(&pattern[1..], &text[1..]),
+            _ => false,
+        }
+    }
+
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+/// A hashed known_hosts entry (`|1|<salt>|<hash>`) matches when
+/// `HMAC-SHA1(key=salt, msg=host)` equals `hash`, compared in constant time.
+fn hashed_host_matches(salt: &[u8], hash: &[u8], host: &str) -> bool {
+    let Ok(mut mac) = HmacSha1::new_from_slice(salt) else {
+        return false;
+    };
+    mac.update(host.as_bytes());
+    mac.verify_slice(hash).is_ok()
+}
+
+fn known_hosts_search_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(ssh_dir) = get_ssh_directory() {
+        paths.push(ssh_dir.join("known_hosts"));
+    }
+    paths.push(PathBuf::from("/etc/ssh/ssh_known_hosts"));
+    paths
+}
+
+/// The known_hosts file Ariana manages itself: TOFU-accepted keys are
+/// recorded here rather than in the user's own `known_hosts`, so Ariana
+/// never writes into a file the user curates by hand.
+pub fn ariana_known_hosts_path() -> Result<PathBuf, String> {
+    Ok(get_ssh_directory()?.join("ariana_known_hosts"))
+}
+
+fn load_entries(paths: &[PathBuf]) -> Vec<KnownHostEntry> {
+    paths
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .enumerate()
+                .filter_map(|(i, line)| parse_known_hosts_line(line, i + 1))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Validates a remote's presented host key against `known_hosts`
+/// (`~/.ssh/known_hosts`, `/etc/ssh/ssh_known_hosts`, and the
+/// Ariana-managed file), falling back to trust-on-first-use for hosts with
+/// no existing entry.
+pub fn verify_host_key(host: &str, key_type: &str, key_blob: &str) -> Result<(), HostKeyError> {
+    verify_host_key_with_policy(host, key_type, key_blob, TofuPolicy::AutoAdd)
+}
+
+/// Like `verify_host_key`, with an explicit policy for hosts that have no
+/// existing known_hosts entry.
+pub fn verify_host_key_with_policy(
+    host: &str,
+    key_type: &str,
+    key_blob: &str,
+    tofu: TofuPolicy,
+) -> Result<(), HostKeyError> {
+    let ariana_path = ariana_known_hosts_path().map_err(HostKeyError::Io)?;
+
+    let mut paths = known_hosts_search_paths();
+    paths.push(ariana_path.clone());
+
+    let entries = load_entries(&paths);
+    let matching: Vec<&KnownHostEntry> = entries
+        .iter()
+        .filter(|entry| entry.patterns.iter().any(|pattern| host_pattern_matches(pattern, host)))
+        .collect();
+
+    if let Some(entry) = matching
+        .iter()
+        .find(|entry| entry.marker == Some(KnownHostMarker::Revoked) && entry.key_blob == key_blob)
+    {
+        return Err(HostKeyError::Revoked { line: entry.line_number });
+    }
+
+    if let Some(entry) = matching.iter().find(|entry| entry.key_type == key_type) {
+        return if entry.key_blob == key_blob {
+            Ok(())
+        } else {
+            Err(HostKeyError::KeyChanged { line: entry.line_number, key_type: key_type.to_string() })
+        };
+    }
+
+    match tofu {
+        TofuPolicy::Reject => Err(HostKeyError::Io(format!(
+            "No known_hosts entry for {host} and TOFU is disabled - refusing to connect"
+        ))),
+        TofuPolicy::AutoAdd => {
+            append_ariana_known_hosts(&ariana_path, host, key_type, key_blob).map_err(HostKeyError::Io)
+        }
+    }
+}
+
+fn append_ariana_known_hosts(path: &PathBuf, host: &str, key_type: &str, key_blob: &str) -> Result<(), String> {
+    use std::io::Write as _;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create SSH directory: {e}"))?;
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+
+    writeln!(file, "{host} {key_type} {key_blob}").map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Asks the remote for the key type/blob it presents right now, via
+/// `ssh-keyscan` - consistent with how the rest of this crate shells out to
+/// system tools (`ssh-keygen`, `ssh-add`) rather than re-implementing the
+/// SSH wire protocol (see `list_ssh_keys.rs`).
+fn fetch_presented_host_key(host: &str) -> Result<(String, String), String> {
+    let output = new_command("ssh-keyscan")
+        .arg("-t")
+        .arg("ed25519")
+        .arg(host)
+        .output()
+        .map_err(|e| format!("Failed to run ssh-keyscan for {host}: {e}"))?;
+
+    if !output.status.success() {
+        return Err(format!("ssh-keyscan for {host} failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .ok_or_else(|| format!("ssh-keyscan returned no host key for {host}"))?;
+
+    let mut fields = line.split_whitespace();
+    fields.next(); // the host field, already known
+    let key_type = fields.next().ok_or_else(|| format!("Malformed ssh-keyscan output for {host}"))?;
+    let key_blob = fields.next().ok_or_else(|| format!("Malformed ssh-keyscan output for {host}"))?;
+
+    Ok((key_type.to_string(), key_blob.to_string()))
+}
+
+/// Resolves the real hostname/IP an SSH config alias actually connects to
+/// (`HostKeyAlias`, falling back to `HostName`) via `ssh -G`, so known_hosts
+/// matching keys off the address the key actually belongs to rather than a
+/// per-agent alias that changes every time an agent is recreated.
+fn resolve_host_key_alias(host_alias: &str) -> Result<String, String> {
+    let output = new_command("ssh")
+        .arg("-G")
+        .arg(host_alias)
+        .output()
+        .map_err(|e| format!("Failed to resolve SSH config for {host_alias}: {e}"))?;
+
+    if !output.status.success() {
+        return Err(format!("ssh -G {host_alias} failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut hostname = None;
+    for line in stdout.lines() {
+        if let Some(value) = line.strip_prefix("hostkeyalias ") {
+            return Ok(value.trim().to_string());
+        }
+        if let Some(value) = line.strip_prefix("hostname ") {
+            hostname = Some(value.trim().to_string());
+        }
+    }
+
+    hostname.ok_or_else(|| format!("Could not resolve a hostname for {host_alias}"))
+}
+
+/// Verifies the key `host_alias` currently presents against known_hosts
+/// before a connection is allowed to proceed, pinning it into the
+/// Ariana-managed known_hosts file on first use. Called by
+/// `ssh_utils::get_common_ssh_options` ahead of every SSH invocation.
+pub fn ensure_host_key_verified(host_alias: &str) -> Result<(), String> {
+    let host = resolve_host_key_alias(host_alias)?;
+    let (key_type, key_blob) = fetch_presented_host_key(&host)?;
+    verify_host_key(&host, &key_type, &key_blob).map_err(|e| e.to_string())
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::checks::{check_command_exists, check_app_exists_macos, check_windows_app_installed};
+use crate::remote_server::{provision_remote_server, RemoteServerCache};
 use crate::ssh_config::SSHConfigManager;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,14 +89,72 @@ pub fn get_ide_url(path: String, ide_id: String) -> Result<String, String> {
     Ok(url)
 }
 
+/// JetBrains Gateway's internal product code for each JetBrains IDE we list,
+/// used to pick the right IDE backend once Gateway has connected over SSH.
+fn jetbrains_product_code(ide_id: &str) -> Option<&'static str> {
+    match ide_id {
+        "idea" => Some("IIU"),
+        "webstorm" => Some("WS"),
+        "pycharm" => Some("PY"),
+        "phpstorm" => Some("PS"),
+        "rubymine" => Some("RM"),
+        "goland" => Some("GO"),
+        "clion" => Some("CL"),
+        "rider" => Some("RD"),
+        "datagrip" => Some("DG"),
+        "studio" => Some("AS"),
+        _ => None,
+    }
+}
+
+/// Whether JetBrains Gateway itself is installed, so `get_ide_ssh_url` can
+/// fall back to manual instructions when it isn't and a generated deeplink
+/// would just fail to open.
+async fn gateway_available() -> bool {
+    check_command_exists("gateway").await
+        || check_app_exists_macos("JetBrains Gateway").await
+        || check_windows_app_installed("Gateway").await
+}
+
+/// Percent-encodes `input` for use as a `jetbrains-gateway://` fragment
+/// query value. Keeps `/` unescaped for readability, matching how these
+/// deeplinks are written in JetBrains' own documentation.
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(byte as char);
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Local CLI command name for the IDEs that ship a separate remote server
+/// binary, matching the commands `get_available_ides` already probes for.
+fn local_command_for_remote_server(ide_id: &str) -> Option<&'static str> {
+    match ide_id {
+        "vscode" => Some("code"),
+        "cursor" => Some("cursor"),
+        "windsurf" => Some("windsurf"),
+        "zed" => Some("zed"),
+        _ => None,
+    }
+}
+
 #[tauri::command]
-pub fn get_ide_ssh_url(
+pub async fn get_ide_ssh_url(
     agent_id: String,
     agent_name: String,
     machine_ip: String,
     ssh_user: String,
     ide_id: String,
     remote_path: Option<String>,
+    await_remote_server_provisioning: Option<bool>,
+    app_handle: tauri::AppHandle,
+    remote_server_cache: tauri::State<'_, std::sync::Arc<RemoteServerCache>>,
 ) -> Result<String, String> {
     // Create or update SSH config entry
     let ssh_config = SSHConfigManager::new()?;
@@ -109,6 +168,22 @@ pub fn get_ide_ssh_url(
     // Default remote path to ~/project if not specified
     let path = remote_path.unwrap_or_else(|| format!("/home/{}/project", ssh_user));
 
+    // Optionally provision the remote server before handing out the
+    // deeplink, so the editor's first connection doesn't stall downloading
+    // and uploading it over SSH itself.
+    if await_remote_server_provisioning.unwrap_or(false) {
+        if let Some(local_command) = local_command_for_remote_server(&ide_id) {
+            provision_remote_server(
+                agent_id.clone(),
+                ide_id.clone(),
+                local_command.to_string(),
+                app_handle,
+                remote_server_cache,
+            )
+            .await?;
+        }
+    }
+
     // Generate the appropriate SSH deeplink based on IDE
     let url = match ide_id.as_str() {
         "vscode" => {
@@ -124,12 +199,32 @@ pub fn get_ide_ssh_url(
             // Zed uses: zed://ssh/user@host/path
             format!("zed://ssh/{}@{}{}", ssh_user, machine_ip, path)
         }
-        // JetBrains IDEs - require manual Gateway setup
+        // JetBrains IDEs - generate a Gateway deeplink when Gateway itself
+        // is installed; fall back to manual instructions otherwise, since a
+        // deeplink to a missing app would just silently fail to open.
         "idea" | "webstorm" | "pycharm" | "phpstorm" | "rubymine" | "goland" | "clion" | "rider" | "datagrip" | "studio" => {
-            return Err(format!(
-                "JetBrains IDEs require manual setup via Gateway:\n\n1. Open JetBrains Gateway\n2. Select 'SSH Connection'\n3. Enter connection details:\n   - Host: {}\n   - User: {}\n   - Port: 22\n   - Authentication: Key pair\n   - Private key: ~/.ssh/ariana_id_ed25519\n4. Select project path: {}\n5. Click 'Check Connection and Continue'",
-                machine_ip, ssh_user, path
-            ));
+            if !gateway_available().await {
+                return Err(format!(
+                    "JetBrains IDEs require manual setup via Gateway:\n\n1. Open JetBrains Gateway\n2. Select 'SSH Connection'\n3. Enter connection details:\n   - Host: {}\n   - User: {}\n   - Port: 22\n   - Authentication: Key pair\n   - Private key: ~/.ssh/ariana_id_ed25519\n4. Select project path: {}\n5. Click 'Check Connection and Continue'",
+                    machine_ip, ssh_user, path
+                ));
+            }
+
+            let product_code = jetbrains_product_code(&ide_id)
+                .ok_or_else(|| format!("No Gateway product code known for {}", ide_id))?;
+            let ssh_key_path = format!(
+                "{}/.ssh/ariana_id_ed25519",
+                dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default()
+            );
+
+            format!(
+                "jetbrains-gateway://connect#type=ssh&host={}&port=22&user={}&privateKeyPath={}&deploy={}&projectPath={}",
+                percent_encode(&host_alias),
+                percent_encode(&ssh_user),
+                percent_encode(&ssh_key_path),
+                percent_encode(product_code),
+                percent_encode(&path),
+            )
         }
         "neovim" => {
             // Neovim doesn't support deeplinks - provide SSH command instead
@@ -14,15 +14,22 @@ use terminal_commands::{
     send_terminal_data,
     resize_terminal,
     close_terminal_connection,
-    cleanup_dead_connections
+    reattach_terminal_connection,
+    cleanup_dead_connections,
+    start_terminal_recording,
+    stop_terminal_recording,
+    list_terminal_transcripts,
+    read_terminal_transcript,
 };
 
 mod checks;
 
 mod command_utils;
 
+mod git_object_graph;
+
 mod project_upload;
-use project_upload::{create_zip_from_directory, read_file_bytes, create_git_bundle_and_patch, create_incremental_git_bundle_and_patch, create_patch_based_upload_data, delete_temp_file, get_file_info, read_file_chunk_base64};
+use project_upload::{create_zip_from_directory, read_file_bytes, create_git_bundle_and_patch, create_incremental_git_bundle_and_patch, create_patch_based_upload_data, create_git_diagnostics_bundle, delete_temp_file, get_file_info, read_file_chunk_base64};
 
 mod sync;
 use sync::{
@@ -34,11 +41,20 @@ use sync::{
     delete_sync_dir,
 };
 
+mod remote_sync;
+use remote_sync::{
+    create_remote_sync,
+    write_remote_sync_file,
+    delete_remote_sync_file,
+    create_remote_sync_dir,
+    delete_remote_sync_dir,
+};
+
 mod claude_credentials;
 use claude_credentials::read_claude_cli_credentials;
 
 mod git;
-use git::get_github_remote_url;
+use git::{get_github_remote_url, get_git_context};
 
 mod os;
 
@@ -49,11 +65,20 @@ use ides::get_available_ides;
 
 mod ssh_utils;
 
+mod ssh_program;
+
+mod ssh_client_config;
+
+mod host_key_verification;
+
 mod ssh_config;
 
 mod ssh_keys;
 use ssh_keys::get_or_create_ssh_key;
 
+mod ssh_key_rotation;
+use ssh_key_rotation::rotate_ariana_ssh_key;
+
 mod list_ssh_keys;
 use list_ssh_keys::{list_available_ssh_keys, read_ssh_key_pair};
 
@@ -62,6 +87,51 @@ use ssh_tunnel::{
     TunnelManager,
     establish_ssh_tunnel,
     close_all_tunnels_for_agent,
+    get_tunnel_status,
+};
+
+mod ssh_pool;
+use ssh_pool::{
+    SshConnectionPool,
+    warm_ssh_connection,
+    get_ssh_pool_stats,
+    evict_ssh_connections_for_agent,
+};
+
+mod terminfo;
+use terminfo::{TerminfoCache, ensure_remote_terminfo};
+
+mod ssh_agent;
+use ssh_agent::{
+    ensure_agent_key_loaded,
+    list_agent_identities,
+    remove_agent_key,
+    ensure_ssh_agent_running,
+    add_agent_key_with_passphrase,
+};
+
+mod agent_binary;
+use agent_binary::{AgentBinaryCache, ensure_agent_binary};
+
+mod remote_server;
+use remote_server::{RemoteServerCache, provision_remote_server_command};
+
+mod remote_family;
+use remote_family::{RemoteFamilyCache, detect_remote_os_family};
+
+mod remote_fs;
+use remote_fs::{
+    RemoteFsWatches,
+    remote_fs_exists,
+    remote_fs_metadata,
+    remote_fs_read,
+    remote_fs_write,
+    remote_fs_rename,
+    remote_fs_make_dir,
+    remote_fs_remove,
+    remote_fs_search,
+    remote_fs_watch,
+    remote_fs_unwatch,
 };
 
 mod device_id;
@@ -74,6 +144,11 @@ use crate::os::get_os;
 
 #[tauri::command]
 async fn extract_zip_to_directory(zip_data: Vec<u8>, target_path: String) -> Result<(), String> {
+    // Auto-detects (and reverses) the compression envelope
+    // `create_zip_from_directory` may have wrapped the archive in; data
+    // with no envelope header (every upload from before that change) comes
+    // back unchanged.
+    let zip_data = project_upload::decompress_archive_envelope(zip_data)?;
     let target_dir = Path::new(&target_path);
 
     // Create target directory if it doesn't exist
@@ -155,7 +230,7 @@ pub fn run() {
     #[cfg(desktop)]
     {
         builder = builder.plugin(tauri_plugin_single_instance::init(|_app, argv, _cwd| {
-          println!("a new app instance was opened with {argv:?} and the deep link event was already triggered");
+          log::info!(target: "cli", "a new app instance was opened with {argv:?} and the deep link event was already triggered");
           // when defining deep link schemes at runtime, you must also check `argv` here
         }));
     }
@@ -165,7 +240,33 @@ pub fn run() {
     // Setup tunnel manager
     let tunnel_manager = Arc::new(TunnelManager::new());
 
-    builder.plugin(tauri_plugin_dialog::init())
+    let terminfo_cache = Arc::new(TerminfoCache::new());
+
+    let remote_fs_watches = Arc::new(RemoteFsWatches::new());
+
+    let agent_binary_cache = Arc::new(AgentBinaryCache::new());
+
+    let remote_server_cache = Arc::new(RemoteServerCache::new());
+
+    let remote_family_cache = Arc::new(RemoteFamilyCache::new());
+
+    let ssh_connection_pool = Arc::new(SshConnectionPool::new(None));
+
+    builder.plugin(
+            tauri_plugin_log::Builder::new()
+                .targets([
+                    // Rotating file so a crash/bug report isn't limited to
+                    // whatever's still in a terminal's scrollback.
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::LogDir { file_name: None }),
+                    // Tauri event sink: the frontend can subscribe to the
+                    // `log://log` event to show live logs in a debug panel.
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Webview),
+                ])
+                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepAll)
+                .max_file_size(10 * 1024 * 1024)
+                .build(),
+        )
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_fs::init())
@@ -174,12 +275,23 @@ pub fn run() {
         .plugin(tauri_plugin_deep_link::init())
         .manage(terminal_manager)
         .manage(tunnel_manager)
+        .manage(terminfo_cache)
+        .manage(remote_fs_watches)
+        .manage(agent_binary_cache)
+        .manage(remote_server_cache)
+        .manage(remote_family_cache)
+        .manage(ssh_connection_pool)
         .invoke_handler(tauri::generate_handler![
             create_terminal_connection,
 			send_terminal_data,
 			resize_terminal,
 			close_terminal_connection,
+			reattach_terminal_connection,
 			cleanup_dead_connections,
+			start_terminal_recording,
+			stop_terminal_recording,
+			list_terminal_transcripts,
+			read_terminal_transcript,
             get_os,
             open_path_in_explorer,
             extract_zip_to_directory,
@@ -191,22 +303,53 @@ pub fn run() {
             create_git_bundle_and_patch,
             create_incremental_git_bundle_and_patch,
             create_patch_based_upload_data,
+            create_git_diagnostics_bundle,
             create_new_sync,
             prepare_sync_directory,
             write_sync_file,
             delete_sync_file,
             create_sync_dir,
             delete_sync_dir,
+            create_remote_sync,
+            write_remote_sync_file,
+            delete_remote_sync_file,
+            create_remote_sync_dir,
+            delete_remote_sync_dir,
             get_available_ides,
             get_ide_url,
             get_ide_ssh_url,
             cleanup_agent_ssh_config,
             get_or_create_ssh_key,
+            rotate_ariana_ssh_key,
             list_available_ssh_keys,
             read_ssh_key_pair,
             get_github_remote_url,
+            get_git_context,
             establish_ssh_tunnel,
             close_all_tunnels_for_agent,
+            get_tunnel_status,
+            warm_ssh_connection,
+            get_ssh_pool_stats,
+            evict_ssh_connections_for_agent,
+            ensure_remote_terminfo,
+            detect_remote_os_family,
+            ensure_agent_key_loaded,
+            list_agent_identities,
+            remove_agent_key,
+            ensure_ssh_agent_running,
+            add_agent_key_with_passphrase,
+            remote_fs_exists,
+            remote_fs_metadata,
+            remote_fs_read,
+            remote_fs_write,
+            remote_fs_rename,
+            remote_fs_make_dir,
+            remote_fs_remove,
+            remote_fs_search,
+            remote_fs_watch,
+            remote_fs_unwatch,
+            ensure_agent_binary,
+            provision_remote_server_command,
             get_machine_id,
             get_device_uuid,
             read_claude_cli_credentials,
@@ -238,16 +381,16 @@ pub fn run() {
             };
 
             if !processed_args.is_empty() {
-                println!("Processed CLI args: {:?}", processed_args);
+                log::info!(target: "cli", "Processed CLI args: {:?}", processed_args);
                 let app_handle = app.handle().clone();
-                
+
                 // Emit after a short delay to ensure frontend is ready
                 tauri::async_runtime::spawn(async move {
                     std::thread::sleep(std::time::Duration::from_millis(2500));
                     if let Err(e) = app_handle.emit("cli-args", &processed_args) {
-                        eprintln!("Failed to emit cli-args: {}", e);
+                        log::error!(target: "cli", "Failed to emit cli-args: {}", e);
                     } else {
-                        println!("CLI args emitted successfully");
+                        log::info!(target: "cli", "CLI args emitted successfully");
                     }
                 });
             }
@@ -260,7 +403,7 @@ pub fn run() {
 
 
 // Helper function to convert to clean absolute path
-fn to_clean_absolute_path(path: &str) -> String {
+pub(crate) fn to_clean_absolute_path(path: &str) -> String {
     let path = Path::new(path);
     
     // Try to get absolute path
@@ -1,6 +1,10 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use base64::{engine::general_purpose, Engine as _};
+use osshkeys::cipher::Cipher;
+use osshkeys::keys::KeyPair;
 use serde::{Serialize, Deserialize};
+use crate::command_utils::new_command;
 use crate::ssh_utils::get_ssh_directory;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -9,6 +13,31 @@ pub struct SshKeyPair {
     pub public_key_path: String,
     pub private_key_path: String,
     pub key_type: String,
+    pub loaded_in_agent: bool,
+}
+
+/// Fingerprints of every identity currently loaded in the running
+/// ssh-agent, as reported by `ssh-add -l`. Empty (not an error) when no
+/// agent is running or nothing is loaded.
+fn agent_fingerprints() -> Vec<String> {
+    match new_command("ssh-add").arg("-l").output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1).map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn key_fingerprint(pub_key_path: &Path) -> Option<String> {
+    let output = new_command("ssh-keygen").arg("-lf").arg(pub_key_path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(|s| s.to_string())
 }
 
 #[tauri::command]
@@ -20,6 +49,7 @@ pub fn list_available_ssh_keys() -> Result<Vec<SshKeyPair>, String> {
     }
 
     let mut key_pairs: Vec<SshKeyPair> = Vec::new();
+    let agent_fps = agent_fingerprints();
 
     // Common SSH key patterns to look for
     let key_patterns = vec![
@@ -35,11 +65,15 @@ pub fn list_available_ssh_keys() -> Result<Vec<SshKeyPair>, String> {
         let public_key_path = ssh_dir.join(format!("{}.pub", key_name));
 
         if private_key_path.exists() && public_key_path.exists() {
+            let loaded_in_agent = key_fingerprint(&public_key_path)
+                .map(|fp| agent_fps.contains(&fp))
+                .unwrap_or(false);
             key_pairs.push(SshKeyPair {
                 name: key_name.to_string(),
                 public_key_path: public_key_path.to_string_lossy().to_string(),
                 private_key_path: private_key_path.to_string_lossy().to_string(),
                 key_type: key_type.to_string(),
+                loaded_in_agent,
             });
         }
     }
@@ -89,12 +123,16 @@ pub fn list_available_ssh_keys() -> Result<Vec<SshKeyPair>, String> {
 
                 // Try to determine key type by reading the public key
                 let key_type = determine_key_type(&pub_path).unwrap_or_else(|| "unknown".to_string());
+                let loaded_in_agent = key_fingerprint(&pub_path)
+                    .map(|fp| agent_fps.contains(&fp))
+                    .unwrap_or(false);
 
                 key_pairs.push(SshKeyPair {
                     name: original_name,
                     public_key_path: pub_path.to_string_lossy().to_string(),
                     private_key_path: path.to_string_lossy().to_string(),
                     key_type,
+                    loaded_in_agent,
                 });
             }
         }
@@ -103,8 +141,17 @@ pub fn list_available_ssh_keys() -> Result<Vec<SshKeyPair>, String> {
     Ok(key_pairs)
 }
 
+/// Either the requested key pair, or a signal that the private key is
+/// encrypted and needs a passphrase before it can be handed out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SshKeyPairResult {
+    Keys { public_key: String, private_key: String },
+    PassphraseRequired,
+}
+
 #[tauri::command]
-pub fn read_ssh_key_pair(key_name: String) -> Result<(String, String), String> {
+pub fn read_ssh_key_pair(key_name: String, passphrase: Option<String>) -> Result<SshKeyPairResult, String> {
     let ssh_dir = get_ssh_directory()?;
 
     let private_key_path = ssh_dir.join(&key_name);
@@ -124,7 +171,58 @@ pub fn read_ssh_key_pair(key_name: String) -> Result<(String, String), String> {
     let public_key = fs::read_to_string(&public_key_path)
         .map_err(|e| format!("Failed to read public key: {}", e))?;
 
-    Ok((public_key.trim().to_string(), private_key))
+    if !is_encrypted(&private_key) {
+        return Ok(SshKeyPairResult::Keys {
+            public_key: public_key.trim().to_string(),
+            private_key,
+        });
+    }
+
+    let Some(passphrase) = passphrase else {
+        return Ok(SshKeyPairResult::PassphraseRequired);
+    };
+
+    let decrypted = KeyPair::parse_keystr(&private_key, Some(&passphrase))
+        .map_err(|e| format!("Failed to decrypt private key {}: {}", key_name, e))?
+        .serialize_openssh(None, Cipher::Null)
+        .map_err(|e| format!("Failed to serialize decrypted private key {}: {}", key_name, e))?;
+
+    Ok(SshKeyPairResult::Keys {
+        public_key: public_key.trim().to_string(),
+        private_key: decrypted,
+    })
+}
+
+/// True if `private_key_pem` is an encrypted private key: a traditional PEM
+/// key with an `ENCRYPTED` proc-type header, or an OpenSSH-format key whose
+/// embedded cipher name isn't `"none"`.
+fn is_encrypted(private_key_pem: &str) -> bool {
+    if private_key_pem.contains("Proc-Type: 4,ENCRYPTED") {
+        return true;
+    }
+    if private_key_pem.contains("BEGIN OPENSSH PRIVATE KEY") {
+        return openssh_cipher_name(private_key_pem)
+            .map(|cipher| cipher != "none")
+            .unwrap_or(false);
+    }
+    false
+}
+
+/// Parses just enough of the OpenSSH private key binary format
+/// (`"openssh-key-v1\0"` magic, followed by a length-prefixed cipher name)
+/// to read out the cipher name without fully decoding the key.
+fn openssh_cipher_name(private_key_pem: &str) -> Option<String> {
+    let body: String = private_key_pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let decoded = general_purpose::STANDARD.decode(body).ok()?;
+
+    let magic = b"openssh-key-v1\0";
+    let rest = decoded.strip_prefix(magic.as_slice())?;
+
+    let len = u32::from_be_bytes(rest.get(0..4)?.try_into().ok()?) as usize;
+    String::from_utf8(rest.get(4..4 + len)?.to_vec()).ok()
 }
 
 fn determine_key_type(pub_key_path: &PathBuf) -> Option<String> {
@@ -1,12 +1,131 @@
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 use ignore::WalkBuilder;
 
 use crate::git::get_github_remote_url;
 use crate::command_utils::new_command;
 
+/// Compression method for the envelope `create_zip_from_directory` wraps
+/// around the zip archive it builds. `Stored` preserves the previous
+/// behavior (the archive bytes are written to disk as-is, no header); the
+/// others wrap the whole archive in a single compressed stream, detected on
+/// extraction by a small magic header so uploads from before this change
+/// (a bare stored zip, no header at all) still decompress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionMethod {
+    Stored,
+    Deflate,
+    Zstd,
+    Xz,
+}
+
+/// Tuning knobs for the envelope compressors. `level` is the encoder's own
+/// quality/speed tradeoff (0-9 for deflate/xz, 1-22 for zstd); `window_log`
+/// only applies to zstd and bounds how far back it can reference for
+/// matches - a larger window finds more redundancy across a big project
+/// tree at the cost of more memory.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct CompressionOptions {
+    pub method: CompressionMethod,
+    pub level: i32,
+    pub window_log: u32,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        // A 24-bit (~16 MB) window at a high zstd level trades a bit more
+        // memory and CPU for meaningfully smaller, faster-to-transfer
+        // archives over slow links to remote agents.
+        Self { method: CompressionMethod::Zstd, level: 19, window_log: 24 }
+    }
+}
+
+const ARCHIVE_ENVELOPE_MAGIC: &[u8; 4] = b"ARZ1";
+
+fn method_tag(method: CompressionMethod) -> u8 {
+    match method {
+        CompressionMethod::Stored => 0,
+        CompressionMethod::Deflate => 1,
+        CompressionMethod::Zstd => 2,
+        CompressionMethod::Xz => 3,
+    }
+}
+
+/// Wraps `archive_bytes` (a complete, already-built zip archive) in the
+/// envelope described by `options`. `Stored` returns the bytes unchanged -
+/// no header - so a fully uncompressed upload stays byte-identical to what
+/// this function produced before the envelope existed.
+pub fn compress_archive_envelope(archive_bytes: Vec<u8>, options: &CompressionOptions) -> Result<Vec<u8>, String> {
+    if options.method == CompressionMethod::Stored {
+        return Ok(archive_bytes);
+    }
+
+    let body = match options.method {
+        CompressionMethod::Stored => unreachable!(),
+        CompressionMethod::Deflate => {
+            use flate2::{write::DeflateEncoder, Compression};
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(options.level.clamp(0, 9) as u32));
+            encoder.write_all(&archive_bytes).map_err(|e| format!("Failed to deflate archive: {}", e))?;
+            encoder.finish().map_err(|e| format!("Failed to finalize deflate stream: {}", e))?
+        }
+        CompressionMethod::Zstd => {
+            let mut encoder = zstd::Encoder::new(Vec::new(), options.level)
+                .map_err(|e| format!("Failed to create zstd encoder: {}", e))?;
+            encoder
+                .set_parameter(zstd::stream::raw::CParameter::WindowLog(options.window_log))
+                .map_err(|e| format!("Failed to configure zstd window: {}", e))?;
+            encoder.write_all(&archive_bytes).map_err(|e| format!("Failed to compress archive: {}", e))?;
+            encoder.finish().map_err(|e| format!("Failed to finalize zstd stream: {}", e))?
+        }
+        CompressionMethod::Xz => {
+            use xz2::write::XzEncoder;
+            let mut encoder = XzEncoder::new(Vec::new(), options.level.clamp(0, 9) as u32);
+            encoder.write_all(&archive_bytes).map_err(|e| format!("Failed to xz-compress archive: {}", e))?;
+            encoder.finish().map_err(|e| format!("Failed to finalize xz stream: {}", e))?
+        }
+    };
+
+    let mut envelope = Vec::with_capacity(ARCHIVE_ENVELOPE_MAGIC.len() + 1 + body.len());
+    envelope.extend_from_slice(ARCHIVE_ENVELOPE_MAGIC);
+    envelope.push(method_tag(options.method));
+    envelope.extend_from_slice(&body);
+    Ok(envelope)
+}
+
+/// Reverses `compress_archive_envelope`. Data with no `ARZ1` header - every
+/// upload produced before this change, and any `Stored` envelope - is
+/// returned unchanged, since it's already a plain zip archive.
+pub fn decompress_archive_envelope(data: Vec<u8>) -> Result<Vec<u8>, String> {
+    if data.len() < ARCHIVE_ENVELOPE_MAGIC.len() + 1 || &data[..ARCHIVE_ENVELOPE_MAGIC.len()] != ARCHIVE_ENVELOPE_MAGIC {
+        return Ok(data);
+    }
+
+    let method_byte = data[ARCHIVE_ENVELOPE_MAGIC.len()];
+    let body = &data[ARCHIVE_ENVELOPE_MAGIC.len() + 1..];
+
+    match method_byte {
+        1 => {
+            use flate2::read::DeflateDecoder;
+            let mut decoder = DeflateDecoder::new(body);
+            let mut out = Vec::new();
+            decoder.read_to_end(&mut out).map_err(|e| format!("Failed to inflate archive: {}", e))?;
+            Ok(out)
+        }
+        2 => zstd::stream::decode_all(body).map_err(|e| format!("Failed to decompress zstd archive: {}", e)),
+        3 => {
+            use xz2::read::XzDecoder;
+            let mut decoder = XzDecoder::new(body);
+            let mut out = Vec::new();
+            decoder.read_to_end(&mut out).map_err(|e| format!("Failed to xz-decompress archive: {}", e))?;
+            Ok(out)
+        }
+        other => Err(format!("Unknown archive envelope method byte: {}", other)),
+    }
+}
+
 /// Normalize line endings to LF (required for git patches)
 /// Converts CRLF (\r\n) to LF (\n) for cross-platform compatibility
 fn normalize_line_endings(data: &[u8]) -> Vec<u8> {
@@ -28,7 +147,11 @@ fn normalize_line_endings(data: &[u8]) -> Vec<u8> {
 }
 
 #[tauri::command]
-pub async fn create_zip_from_directory(source_path: String) -> Result<String, String> {
+pub async fn create_zip_from_directory(
+    source_path: String,
+    compression: Option<CompressionOptions>,
+) -> Result<String, String> {
+    let compression = compression.unwrap_or_default();
     let source_dir = Path::new(&source_path);
     if !source_dir.exists() || !source_dir.is_dir() {
         return Err(format!("Source directory does not exist: {}", source_path));
@@ -83,6 +206,8 @@ pub async fn create_zip_from_directory(source_path: String) -> Result<String, St
     #[cfg(any(target_os = "macos", target_os = "linux"))]
     let zip_buffer = create_zip_unix(source_dir, &files_to_include)?;
 
+    let zip_buffer = compress_archive_envelope(zip_buffer, &compression)?;
+
     let temp_dir = std::env::temp_dir();
     let zip_filename = format!("ariana_project_{}.zip", uuid::Uuid::new_v4());
     let zip_path = temp_dir.join(&zip_filename);
@@ -304,6 +429,189 @@ pub struct BundleMetadata {
     pub is_incremental: bool,
     pub base_commit_sha: Option<String>,
     pub remote_url: Option<String>,
+    /// Commit SHAs the receiving repo must already have for `git bundle
+    /// unbundle` to succeed on this bundle - always empty for a full
+    /// (`--all`) bundle, since that has no prerequisites by definition.
+    /// The upload side should confirm it has every one of these before
+    /// attempting to unbundle, rather than finding out from a failed
+    /// unbundle.
+    pub prerequisites: Vec<String>,
+    /// A bundle+patch pair for every initialized submodule, pinned at the
+    /// commit the superproject currently has checked out. `git bundle
+    /// create --all` and `git diff` both ignore submodules entirely, so
+    /// without this the remote's clone would have empty submodule
+    /// directories.
+    pub submodules: Vec<SubmoduleBundle>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleBundle {
+    /// Path of the submodule relative to the superproject root, as recorded
+    /// in `.gitmodules` / reported by `git submodule status`.
+    pub path: String,
+    pub bundle_path: String,
+    pub patch_path: String,
+    pub is_incremental: bool,
+    pub base_commit_sha: Option<String>,
+    pub prerequisites: Vec<String>,
+}
+
+/// SHA-1 of git's canonical empty tree - the same value in every repo,
+/// usable as a diff target in place of `HEAD` when there's no commit yet.
+const EMPTY_TREE_SHA: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Builds the uncommitted-changes patch: tracked changes vs `HEAD` (or,
+/// with no commits yet, vs the empty tree), plus untracked files reported
+/// as newly added.
+///
+/// Rather than hand-formatting a `diff --git ... new file mode 100644 ...`
+/// header per untracked file (which hardcoded the mode, couldn't express
+/// executable bits or symlinks, and mishandled binary content and
+/// missing-trailing-newline files), this copies the repo's index to a
+/// temp file, points `GIT_INDEX_FILE` at the copy for every command below,
+/// and runs `git add --intent-to-add` against *that* copy to record each
+/// untracked path's real mode without staging its content. A single
+/// `git diff --binary` against the temp index then reports everything -
+/// tracked changes and untracked additions alike - with correct mode
+/// bits, rename detection, `\ No newline at end of file` markers, and
+/// binary hunks, all for free. The real `.git/index` is never opened for
+/// writing.
+fn diff_with_untracked_as_new_files(source_dir: &Path, commit_count: u32) -> Result<Vec<u8>, String> {
+    let git_dir_output = new_command("git")
+        .args(&["rev-parse", "--absolute-git-dir"])
+        .current_dir(source_dir)
+        .output()
+        .map_err(|e| format!("Failed to locate git dir: {}", e))?;
+
+    if !git_dir_output.status.success() {
+        return Err(format!(
+            "Failed to locate git dir: {}",
+            String::from_utf8_lossy(&git_dir_output.stderr)
+        ));
+    }
+
+    let git_dir = String::from_utf8_lossy(&git_dir_output.stdout).trim().to_string();
+    let real_index = Path::new(&git_dir).join("index");
+
+    let temp_index = std::env::temp_dir().join(format!("ariana_index_{}", uuid::Uuid::new_v4()));
+    if real_index.exists() {
+        fs::copy(&real_index, &temp_index).map_err(|e| format!("Failed to copy git index: {}", e))?;
+    }
+
+    let result = diff_with_untracked_as_new_files_inner(source_dir, commit_count, &temp_index);
+    let _ = fs::remove_file(&temp_index);
+    result
+}
+
+fn diff_with_untracked_as_new_files_inner(
+    source_dir: &Path,
+    commit_count: u32,
+    temp_index: &Path,
+) -> Result<Vec<u8>, String> {
+    let untracked_output = new_command("git")
+        .args(&["ls-files", "--others", "--exclude-standard"])
+        .current_dir(source_dir)
+        .output()
+        .map_err(|e| format!("Failed to list untracked files: {}", e))?;
+
+    if untracked_output.status.success() {
+        let untracked_files = String::from_utf8_lossy(&untracked_output.stdout);
+        let untracked_paths: Vec<&str> = untracked_files.lines().filter(|line| !line.is_empty()).collect();
+
+        if !untracked_paths.is_empty() {
+            let mut add_args = vec!["add", "--intent-to-add", "--"];
+            add_args.extend(untracked_paths);
+
+            let add_output = new_command("git")
+                .args(&add_args)
+                .current_dir(source_dir)
+                .env("GIT_INDEX_FILE", temp_index)
+                .output()
+                .map_err(|e| format!("Failed to intent-to-add untracked files: {}", e))?;
+
+            if !add_output.status.success() {
+                return Err(format!(
+                    "Failed to intent-to-add untracked files: {}",
+                    String::from_utf8_lossy(&add_output.stderr)
+                ));
+            }
+        }
+    }
+
+    let diff_target = if commit_count > 0 { "HEAD" } else { EMPTY_TREE_SHA };
+
+    let diff_output = new_command("git")
+        .args(&["diff", "--binary", diff_target])
+        .current_dir(source_dir)
+        .env("GIT_INDEX_FILE", temp_index)
+        .output()
+        .map_err(|e| format!("Failed to get diff: {}", e))?;
+
+    if !diff_output.status.success() {
+        return Err(format!(
+            "Git diff failed: {}",
+            String::from_utf8_lossy(&diff_output.stderr)
+        ));
+    }
+
+    Ok(diff_output.stdout)
+}
+
+/// Number of commits in `source_dir`'s history. Tries the gitoxide
+/// object-graph walk first (see `git_object_graph`, only compiled in with
+/// the `gix-git` feature), falling back to `git rev-list --all --count`
+/// otherwise.
+fn commit_count(source_dir: &Path) -> u32 {
+    #[cfg(feature = "gix-git")]
+    if let Some(count) = crate::git_object_graph::commit_count(source_dir) {
+        return count;
+    }
+
+    let log_output = match new_command("git")
+        .args(&["rev-list", "--all", "--count"])
+        .current_dir(source_dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return 0,
+    };
+
+    if log_output.status.success() {
+        String::from_utf8_lossy(&log_output.stdout)
+            .trim()
+            .parse::<u32>()
+            .unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+/// Merge-base between HEAD and `other_ref`. Tries the gitoxide object-graph
+/// walk first (see `git_object_graph`, only compiled in with the `gix-git`
+/// feature), falling back to `git merge-base` otherwise.
+fn merge_base_with(source_dir: &Path, other_ref: &str) -> Option<String> {
+    #[cfg(feature = "gix-git")]
+    if let Some(sha) = crate::git_object_graph::merge_base(source_dir, other_ref) {
+        return Some(sha);
+    }
+
+    let merge_base_output = new_command("git")
+        .args(&["merge-base", "HEAD", other_ref])
+        .current_dir(source_dir)
+        .output()
+        .ok()?;
+
+    if !merge_base_output.status.success() {
+        return None;
+    }
+
+    let sha = String::from_utf8_lossy(&merge_base_output.stdout).trim().to_string();
+    if sha.is_empty() {
+        None
+    } else {
+        Some(sha)
+    }
 }
 
 /// Find the merge-base (common ancestor) between HEAD and the remote branch
@@ -338,48 +646,21 @@ fn find_remote_merge_base(source_dir: &Path) -> Option<String> {
 
     // Try origin/<branch> first
     let remote_branch = format!("origin/{}", branch);
-    let merge_base_output = new_command("git")
-        .args(&["merge-base", "HEAD", &remote_branch])
-        .current_dir(source_dir)
-        .output()
-        .ok()?;
-
-    if merge_base_output.status.success() {
-        let sha = String::from_utf8_lossy(&merge_base_output.stdout).trim().to_string();
-        if !sha.is_empty() {
-            println!("[GIT] Found merge-base with {}: {}", remote_branch, sha);
-            return Some(sha);
-        }
+    if let Some(sha) = merge_base_with(source_dir, &remote_branch) {
+        println!("[GIT] Found merge-base with {}: {}", remote_branch, sha);
+        return Some(sha);
     }
 
     // Fallback: try origin/main
-    let merge_base_output = new_command("git")
-        .args(&["merge-base", "HEAD", "origin/main"])
-        .current_dir(source_dir)
-        .output()
-        .ok()?;
-
-    if merge_base_output.status.success() {
-        let sha = String::from_utf8_lossy(&merge_base_output.stdout).trim().to_string();
-        if !sha.is_empty() {
-            println!("[GIT] Found merge-base with origin/main: {}", sha);
-            return Some(sha);
-        }
+    if let Some(sha) = merge_base_with(source_dir, "origin/main") {
+        println!("[GIT] Found merge-base with origin/main: {}", sha);
+        return Some(sha);
     }
 
     // Fallback: try origin/master
-    let merge_base_output = new_command("git")
-        .args(&["merge-base", "HEAD", "origin/master"])
-        .current_dir(source_dir)
-        .output()
-        .ok()?;
-
-    if merge_base_output.status.success() {
-        let sha = String::from_utf8_lossy(&merge_base_output.stdout).trim().to_string();
-        if !sha.is_empty() {
-            println!("[GIT] Found merge-base with origin/master: {}", sha);
-            return Some(sha);
-        }
+    if let Some(sha) = merge_base_with(source_dir, "origin/master") {
+        println!("[GIT] Found merge-base with origin/master: {}", sha);
+        return Some(sha);
     }
 
     None
@@ -461,20 +742,7 @@ pub async fn create_patch_based_upload_data(
     println!("[GIT] Creating patch-based upload data for: {}", source_path);
 
     // Check if repository has any commits
-    let log_output = new_command("git")
-        .args(&["rev-list", "--all", "--count"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to check git history: {}", e))?;
-
-    let commit_count = if log_output.status.success() {
-        String::from_utf8_lossy(&log_output.stdout)
-            .trim()
-            .parse::<u32>()
-            .unwrap_or(0)
-    } else {
-        0
-    };
+    let commit_count = commit_count(source_dir);
 
     println!("[GIT] Repository has {} commits", commit_count);
 
@@ -542,9 +810,11 @@ pub async fn create_patch_based_upload_data(
                     0
                 };
 
-                // Get patch using git diff (not format-patch)
+                // Get patch using git diff (not format-patch). --binary so
+                // a commit touching a binary file produces a reapplyable
+                // GIT binary patch hunk instead of "Binary files differ".
                 let patch_output = new_command("git")
-                    .args(&["diff", &format!("{}~1", sha), sha])
+                    .args(&["diff", "--binary", &format!("{}~1", sha), sha])
                     .current_dir(source_dir)
                     .output()
                     .map_err(|e| format!("Failed to create patch: {}", e))?;
@@ -565,62 +835,9 @@ pub async fn create_patch_based_upload_data(
         }
     }
 
-    // Get uncommitted changes (same as before)
-    let mut patch_content = Vec::new();
-
-    // Get diff for tracked files
-    let diff_output = new_command("git")
-        .args(&["diff", "HEAD"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to get diff: {}", e))?;
-
-    if diff_output.status.success() {
-        patch_content = diff_output.stdout;
-    }
-
-    // Get list of untracked files
-    let untracked_output = new_command("git")
-        .args(&["ls-files", "--others", "--exclude-standard"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to list untracked files: {}", e))?;
-
-    if untracked_output.status.success() {
-        let untracked_files = String::from_utf8_lossy(&untracked_output.stdout);
-
-        for file_path in untracked_files.lines() {
-            if file_path.is_empty() {
-                continue;
-            }
-
-            let full_path = source_dir.join(file_path);
-
-            if let Ok(content) = fs::read_to_string(&full_path) {
-                let lines: Vec<&str> = content.lines().collect();
-
-                if lines.is_empty() {
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..e69de29\n--- /dev/null\n+++ b/{}\n",
-                        file_path, file_path, file_path
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-                } else {
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n@@ -0,0 +1,{} @@\n",
-                        file_path, file_path, file_path, lines.len()
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-
-                    for line in lines {
-                        patch_content.extend_from_slice(b"+");
-                        patch_content.extend_from_slice(line.as_bytes());
-                        patch_content.extend_from_slice(b"\n");
-                    }
-                }
-            }
-        }
-    }
+    // Get uncommitted changes (tracked diff vs HEAD, untracked files
+    // reported as new) via a throwaway index rather than hand-built diffs.
+    let patch_content = diff_with_untracked_as_new_files(source_dir, commit_count)?;
 
     // Normalize line endings
     let patch_content = normalize_line_endings(&patch_content);
@@ -637,54 +854,160 @@ pub async fn create_patch_based_upload_data(
     })
 }
 
-/// Create an incremental git bundle (only commits since merge-base)
-/// Automatically detects GitHub remote URL using get_github_remote_url
-#[tauri::command]
-pub async fn create_incremental_git_bundle_and_patch(
-    source_path: String
-) -> Result<BundleMetadata, String> {
-    let source_dir = Path::new(&source_path);
-    if !source_dir.exists() || !source_dir.is_dir() {
-        return Err(format!("Source directory does not exist: {}", source_path));
+/// Creates a full (`--all`) bundle at `bundle_path`, the fallback used
+/// whenever an incremental bundle can't be created or doesn't verify.
+fn create_full_bundle(source_dir: &Path, bundle_path: &Path) -> Result<(), String> {
+    let bundle_output = new_command("git")
+        .args(&["bundle", "create", bundle_path.to_str().unwrap(), "--all"])
+        .current_dir(source_dir)
+        .output()
+        .map_err(|e| format!("Failed to create git bundle: {}", e))?;
+
+    if !bundle_output.status.success() {
+        return Err(format!(
+            "Git bundle creation failed: {}",
+            String::from_utf8_lossy(&bundle_output.stderr)
+        ));
     }
 
-    println!("[GIT] Creating incremental git bundle for: {}", source_path);
+    Ok(())
+}
 
-    // Check if repository has any commits
-    let log_output = new_command("git")
-        .args(&["rev-list", "--all", "--count"])
+/// Runs `git bundle verify` against a just-created bundle and extracts the
+/// prerequisite commit SHAs it reports - the commits the receiving repo
+/// must already have for `git bundle unbundle` to apply this bundle.
+/// Returns an error (bundle unusable) if verification fails, e.g. because
+/// the merge-base it was built from doesn't actually describe a consistent
+/// history, so the caller can fall back to a full bundle instead of
+/// handing out something the remote can't unbundle.
+fn verify_bundle(bundle_path: &Path, source_dir: &Path) -> Result<Vec<String>, String> {
+    let verify_output = new_command("git")
+        .args(&["bundle", "verify", bundle_path.to_str().unwrap()])
         .current_dir(source_dir)
         .output()
-        .map_err(|e| format!("Failed to check git history: {}", e))?;
+        .map_err(|e| format!("Failed to run git bundle verify: {}", e))?;
 
-    let commit_count = if log_output.status.success() {
-        String::from_utf8_lossy(&log_output.stdout)
-            .trim()
-            .parse::<u32>()
-            .unwrap_or(0)
-    } else {
-        0
-    };
+    if !verify_output.status.success() {
+        return Err(String::from_utf8_lossy(&verify_output.stderr).trim().to_string());
+    }
 
-    println!("[GIT] Repository has {} commits", commit_count);
+    // Git writes the human-readable report to stdout in some versions and
+    // stderr in others - check both rather than guessing.
+    let combined = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&verify_output.stdout),
+        String::from_utf8_lossy(&verify_output.stderr)
+    );
+    Ok(parse_bundle_prerequisites(&combined))
+}
 
-    // Try to find merge-base
-    let base_commit = find_remote_merge_base(source_dir);
+/// Parses the SHAs out of `git bundle verify`'s prerequisites section.
+/// Real git (verified against 2.39.5) headers that section "The bundle
+/// requires this ref:" / "The bundle requires these N refs:" - not
+/// "prerequisite commit(s)", which git never actually prints - followed by
+/// one `<sha> <subject>` line per prerequisite. A later "The bundle
+/// contains ..." header (the refs *in* the bundle, listed before or after
+/// the prerequisites depending on git version) ends the section.
+fn parse_bundle_prerequisites(verify_output: &str) -> Vec<String> {
+    let mut prerequisites = Vec::new();
+    let mut in_section = false;
+
+    for line in verify_output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("The bundle requires") {
+            in_section = true;
+            continue;
+        }
+        if trimmed.starts_with("The bundle contains") {
+            in_section = false;
+            continue;
+        }
 
-    // Detect GitHub remote URL using the same function as frontend
-    // This returns None if remote is not GitHub (GitLab, Bitbucket, etc.)
-    let detected_remote_url = match get_github_remote_url(source_path.clone()).await {
-        Ok(Some(info)) if !info.github_url.is_empty() => Some(info.github_url),
-        _ => None
-    };
+        if !in_section {
+            continue;
+        }
+
+        match trimmed.split_whitespace().next() {
+            Some(sha) if sha.len() >= 40 && sha.chars().all(|c| c.is_ascii_hexdigit()) => {
+                prerequisites.push(sha.to_string());
+            }
+            _ => in_section = false,
+        }
+    }
+
+    prerequisites
+}
+
+#[cfg(test)]
+mod bundle_verify_tests {
+    use super::parse_bundle_prerequisites;
+
+    // Real `git bundle verify` output (git 2.39.5) for an incremental
+    // bundle with two refs and two prerequisite commits.
+    const SAMPLE_VERIFY_OUTPUT: &str = "\
+The bundle contains these 2 refs:
+1a2b3c4d5e6f7890123456789012345678901234 refs/heads/main
+fedcba0987654321fedcba0987654321fedcba09 refs/heads/feature
+The bundle requires these 2 refs:
+aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa Initial commit
+bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb Second commit
+/tmp/ariana_bundle_x.bundle is okay
+";
+
+    #[test]
+    fn extracts_prerequisites_from_real_verify_output() {
+        let prerequisites = parse_bundle_prerequisites(SAMPLE_VERIFY_OUTPUT);
+        assert_eq!(
+            prerequisites,
+            vec![
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string(),
+                "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_prerequisites_section_yields_empty() {
+        let full_bundle_output = "\
+The bundle contains this ref:
+1a2b3c4d5e6f7890123456789012345678901234 refs/heads/main
+/tmp/ariana_bundle_x.bundle is okay
+";
+        assert!(parse_bundle_prerequisites(full_bundle_output).is_empty());
+    }
+}
+
+/// The bundle+patch pair for one repository (the superproject or a single
+/// submodule), before any repo-specific metadata (remote URL, submodule
+/// path) is attached.
+struct BundleArtifacts {
+    bundle_path: PathBuf,
+    patch_path: PathBuf,
+    is_incremental: bool,
+    base_commit_sha: Option<String>,
+    prerequisites: Vec<String>,
+}
+
+/// Builds an incremental bundle+patch pair for `repo_dir`: a `base..HEAD`
+/// bundle against the merge-base with its remote (an empty bundle file when
+/// there's no history; a full `--all` bundle when there's no merge-base, the
+/// incremental bundle fails to create, or it fails `git bundle verify`), plus a patch
+/// for `repo_dir`'s uncommitted changes. Used for both the superproject and
+/// each of its submodules (see `build_submodule_bundles`) - a submodule
+/// checked out at an already-pushed commit naturally produces an empty
+/// bundle here, since its detached HEAD *is* the merge-base.
+fn build_incremental_bundle(repo_dir: &Path) -> Result<BundleArtifacts, String> {
+    let commit_count = commit_count(repo_dir);
+    println!("[GIT] Repository has {} commits", commit_count);
+
+    let base_commit = find_remote_merge_base(repo_dir);
 
     let temp_dir = std::env::temp_dir();
-    let bundle_filename = format!("ariana_bundle_{}.bundle", uuid::Uuid::new_v4());
-    let patch_filename = format!("ariana_patch_{}.patch", uuid::Uuid::new_v4());
-    let bundle_path = temp_dir.join(&bundle_filename);
-    let patch_path = temp_dir.join(&patch_filename);
+    let bundle_path = temp_dir.join(format!("ariana_bundle_{}.bundle", uuid::Uuid::new_v4()));
+    let patch_path = temp_dir.join(format!("ariana_patch_{}.patch", uuid::Uuid::new_v4()));
 
     let mut is_incremental = false;
+    let mut prerequisites: Vec<String> = Vec::new();
 
     if commit_count == 0 {
         // Repository has no commits - create an empty bundle
@@ -695,7 +1018,7 @@ pub async fn create_incremental_git_bundle_and_patch(
         // Check if HEAD is at the base commit (no new commits)
         let head_sha_output = new_command("git")
             .args(&["rev-parse", "HEAD"])
-            .current_dir(source_dir)
+            .current_dir(repo_dir)
             .output()
             .map_err(|e| format!("Failed to get HEAD SHA: {}", e))?;
 
@@ -722,45 +1045,36 @@ pub async fn create_incremental_git_bundle_and_patch(
                     bundle_path.to_str().unwrap(),
                     &format!("{}..HEAD", base_sha),
                 ])
-                .current_dir(source_dir)
+                .current_dir(repo_dir)
                 .output()
                 .map_err(|e| format!("Failed to create incremental bundle: {}", e))?;
 
             if bundle_output.status.success() {
-                is_incremental = true;
-                println!("[GIT] Incremental bundle created successfully");
+                match verify_bundle(&bundle_path, repo_dir) {
+                    Ok(prereqs) => {
+                        is_incremental = true;
+                        println!(
+                            "[GIT] Incremental bundle created and verified ({} prerequisite commit(s))",
+                            prereqs.len()
+                        );
+                        prerequisites = prereqs;
+                    }
+                    Err(e) => {
+                        println!(
+                            "[GIT] Incremental bundle failed verification ({}), falling back to full bundle",
+                            e
+                        );
+                        create_full_bundle(repo_dir, &bundle_path)?;
+                    }
+                }
             } else {
                 println!("[GIT] Incremental bundle failed, falling back to full bundle");
-                // Fall back to full bundle
-                let bundle_output = new_command("git")
-                    .args(&["bundle", "create", bundle_path.to_str().unwrap(), "--all"])
-                    .current_dir(source_dir)
-                    .output()
-                    .map_err(|e| format!("Failed to create git bundle: {}", e))?;
-
-                if !bundle_output.status.success() {
-                    return Err(format!(
-                        "Git bundle creation failed: {}",
-                        String::from_utf8_lossy(&bundle_output.stderr)
-                    ));
-                }
+                create_full_bundle(repo_dir, &bundle_path)?;
             }
         }
     } else {
         println!("[GIT] No merge-base found, creating full bundle");
-        // No merge-base, create full bundle
-        let bundle_output = new_command("git")
-            .args(&["bundle", "create", bundle_path.to_str().unwrap(), "--all"])
-            .current_dir(source_dir)
-            .output()
-            .map_err(|e| format!("Failed to create git bundle: {}", e))?;
-
-        if !bundle_output.status.success() {
-            return Err(format!(
-                "Git bundle creation failed: {}",
-                String::from_utf8_lossy(&bundle_output.stderr)
-            ));
-        }
+        create_full_bundle(repo_dir, &bundle_path)?;
     }
 
     println!("[GIT] Bundle created at: {}", bundle_path.display());
@@ -768,73 +1082,15 @@ pub async fn create_incremental_git_bundle_and_patch(
     // Create patch for uncommitted changes (non-invasive - never touches staging area)
     println!("[GIT] Creating patch from uncommitted changes");
 
-    let mut patch_content = Vec::new();
-
-    // 1. Get diff for tracked files (only if we have commits, i.e., HEAD exists)
-    if commit_count > 0 {
-        let diff_output = new_command("git")
-            .args(&["diff", "HEAD"])
-            .current_dir(source_dir)
-            .output()
-            .map_err(|e| format!("Failed to get diff: {}", e))?;
-
-        if !diff_output.status.success() {
+    // Tracked diff vs HEAD plus untracked files reported as new, via a
+    // throwaway index rather than hand-built diffs.
+    let patch_content = match diff_with_untracked_as_new_files(repo_dir, commit_count) {
+        Ok(content) => content,
+        Err(e) => {
             let _ = fs::remove_file(&bundle_path);
-            return Err(format!(
-                "Git diff failed: {}",
-                String::from_utf8_lossy(&diff_output.stderr)
-            ));
-        }
-
-        patch_content = diff_output.stdout;
-    }
-
-    // 2. Get list of untracked files
-    let untracked_output = new_command("git")
-        .args(&["ls-files", "--others", "--exclude-standard"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to list untracked files: {}", e))?;
-
-    if untracked_output.status.success() {
-        let untracked_files = String::from_utf8_lossy(&untracked_output.stdout);
-
-        // 3. For each untracked file, create a diff entry
-        for file_path in untracked_files.lines() {
-            if file_path.is_empty() {
-                continue;
-            }
-
-            let full_path = source_dir.join(file_path);
-
-            // Read file content
-            if let Ok(content) = fs::read_to_string(&full_path) {
-                let lines: Vec<&str> = content.lines().collect();
-
-                if lines.is_empty() {
-                    // Empty file - no hunk header needed
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..e69de29\n--- /dev/null\n+++ b/{}\n",
-                        file_path, file_path, file_path
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-                } else {
-                    // Non-empty file - include hunk header
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n@@ -0,0 +1,{} @@\n",
-                        file_path, file_path, file_path, lines.len()
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-
-                    for line in lines {
-                        patch_content.extend_from_slice(b"+");
-                        patch_content.extend_from_slice(line.as_bytes());
-                        patch_content.extend_from_slice(b"\n");
-                    }
-                }
-            }
+            return Err(e);
         }
-    }
+    };
 
     // Normalize line endings to LF (git patches must use LF, not CRLF)
     // This is critical for cross-platform compatibility (Windows -> Linux)
@@ -861,15 +1117,256 @@ pub async fn create_incremental_git_bundle_and_patch(
         bundle_size, patch_size, is_incremental
     );
 
-    Ok(BundleMetadata {
-        bundle_path: bundle_path.to_string_lossy().to_string(),
-        patch_path: patch_path.to_string_lossy().to_string(),
+    Ok(BundleArtifacts {
+        bundle_path,
+        patch_path,
         is_incremental,
         base_commit_sha: if is_incremental { base_commit } else { None },
+        prerequisites,
+    })
+}
+
+/// Lists the submodules `.gitmodules` knows about and that are actually
+/// initialized, nested ones included, as `(path, pinned_sha)` pairs, by
+/// parsing `git submodule status --recursive` (paths relative to
+/// `repo_dir`). Each line is a status char (` ` up to date, `+` checked out at
+/// a different commit than recorded, `U` merge conflicts, or `-` not
+/// initialized) followed by `<sha> <path> ...`; uninitialized submodules
+/// (`-`, no working tree to bundle) are skipped.
+fn list_initialized_submodules(repo_dir: &Path) -> Vec<(String, String)> {
+    let output = match new_command("git")
+        .args(&["submodule", "status", "--recursive"])
+        .current_dir(repo_dir)
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    let mut submodules = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let status = line.chars().next().unwrap_or(' ');
+        if status == '-' {
+            continue;
+        }
+
+        let mut parts = line[1..].split_whitespace();
+        let (Some(sha), Some(path)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+
+        submodules.push((path.to_string(), sha.to_string()));
+    }
+
+    submodules
+}
+
+/// Recursively builds a bundle+patch pair for every initialized submodule,
+/// pinned at the commit the superproject currently has checked out -
+/// `git bundle create --all` and `git diff` both ignore submodules, so
+/// without this the remote's reconstructed tree would have empty submodule
+/// directories.
+fn build_submodule_bundles(source_dir: &Path) -> Result<Vec<SubmoduleBundle>, String> {
+    let mut submodules = Vec::new();
+
+    for (path, _pinned_sha) in list_initialized_submodules(source_dir) {
+        let submodule_dir = source_dir.join(&path);
+        if !submodule_dir.is_dir() {
+            continue;
+        }
+
+        println!("[GIT] Bundling submodule: {}", path);
+        let artifacts = build_incremental_bundle(&submodule_dir)?;
+
+        submodules.push(SubmoduleBundle {
+            path,
+            bundle_path: artifacts.bundle_path.to_string_lossy().to_string(),
+            patch_path: artifacts.patch_path.to_string_lossy().to_string(),
+            is_incremental: artifacts.is_incremental,
+            base_commit_sha: artifacts.base_commit_sha,
+            prerequisites: artifacts.prerequisites,
+        });
+    }
+
+    Ok(submodules)
+}
+
+/// Create an incremental git bundle (only commits since merge-base)
+/// Automatically detects GitHub remote URL using get_github_remote_url
+#[tauri::command]
+pub async fn create_incremental_git_bundle_and_patch(
+    source_path: String
+) -> Result<BundleMetadata, String> {
+    let source_dir = Path::new(&source_path);
+    if !source_dir.exists() || !source_dir.is_dir() {
+        return Err(format!("Source directory does not exist: {}", source_path));
+    }
+
+    println!("[GIT] Creating incremental git bundle for: {}", source_path);
+
+    // Detect the remote URL using the same function as frontend
+    // This returns None only when there's no repo or no remote configured
+    let detected_remote_url = match get_github_remote_url(source_path.clone()).await {
+        Ok(Some(info)) if !info.github_url.is_empty() => Some(info.github_url),
+        _ => None
+    };
+
+    let artifacts = build_incremental_bundle(source_dir)?;
+    let submodules = build_submodule_bundles(source_dir)?;
+
+    Ok(BundleMetadata {
+        bundle_path: artifacts.bundle_path.to_string_lossy().to_string(),
+        patch_path: artifacts.patch_path.to_string_lossy().to_string(),
+        is_incremental: artifacts.is_incremental,
+        base_commit_sha: artifacts.base_commit_sha,
         remote_url: detected_remote_url,
+        prerequisites: artifacts.prerequisites,
+        submodules,
     })
 }
 
+/// Runs a best-effort `git` subcommand for the diagnostics report -
+/// failures are captured as text in the report itself rather than
+/// aborting the whole bundle, since a partial diagnostics snapshot is
+/// still more useful to support than none.
+fn run_git_diagnostic(source_dir: &Path, args: &[&str]) -> String {
+    match new_command("git").args(args).current_dir(source_dir).output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim_end().to_string()
+        }
+        Ok(output) => format!(
+            "(git {} failed: {})",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("(failed to run git {}: {})", args.join(" "), e),
+    }
+}
+
+/// Strips credential-bearing values out of `git config --list` output
+/// before it goes in a diagnostics report. `http.<url>.extraheader`,
+/// `credential.helper`, and any key with `token`/`password`/`secret`/
+/// `authorization` in it are the main ways a credential ends up in git
+/// config, so those values are redacted while the rest of the config
+/// (useful for spotting misconfiguration) is kept intact.
+fn scrub_git_config(config_output: &str) -> String {
+    config_output
+        .lines()
+        .map(|line| {
+            let key = line.split('=').next().unwrap_or(line).to_lowercase();
+            let is_sensitive = ["token", "password", "secret", "authorization", "extraheader", "credential.helper"]
+                .iter()
+                .any(|needle| key.contains(needle));
+
+            if is_sensitive {
+                let key_part = line.split('=').next().unwrap_or(line);
+                format!("{}=<scrubbed>", key_part)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Produces a single zip archive of non-source repository metadata -
+/// commit count, working tree status, scrubbed git config, detected
+/// remote URL, object-store stats, and the same bundle/patch sizes and
+/// `is_incremental` flag `create_incremental_git_bundle_and_patch`
+/// computes - modeled on git's own `git diagnose`/`git bugreport`. Lets
+/// support ask for a reproducible snapshot when a user's bundle/patch
+/// failed to apply remotely, without the user having to hand over their
+/// actual source.
+#[tauri::command]
+pub async fn create_git_diagnostics_bundle(source_path: String) -> Result<String, String> {
+    let source_dir = Path::new(&source_path);
+    if !source_dir.exists() || !source_dir.is_dir() {
+        return Err(format!("Source directory does not exist: {}", source_path));
+    }
+
+    println!("[DIAGNOSTICS] Creating git diagnostics bundle for: {}", source_path);
+
+    let detected_remote_url = match get_github_remote_url(source_path.clone()).await {
+        Ok(Some(info)) if !info.github_url.is_empty() => Some(info.github_url),
+        _ => None,
+    };
+
+    // Build (and then discard) a real bundle+patch pair purely to measure
+    // its size and incremental-ness - the diagnostics archive reports
+    // those numbers but never ships the bundle/patch content itself.
+    let artifacts = build_incremental_bundle(source_dir)?;
+    let bundle_size = fs::metadata(&artifacts.bundle_path).map(|m| m.len()).unwrap_or(0);
+    let patch_size = fs::metadata(&artifacts.patch_path).map(|m| m.len()).unwrap_or(0);
+    let _ = fs::remove_file(&artifacts.bundle_path);
+    let _ = fs::remove_file(&artifacts.patch_path);
+
+    let commit_count = run_git_diagnostic(source_dir, &["rev-list", "--all", "--count"]);
+    let status = run_git_diagnostic(source_dir, &["status", "--porcelain=v2"]);
+    let config = scrub_git_config(&run_git_diagnostic(source_dir, &["config", "--list", "--show-scope"]));
+    let object_counts = run_git_diagnostic(source_dir, &["count-objects", "-vH"]);
+    let prerequisites = if artifacts.prerequisites.is_empty() {
+        "(none)".to_string()
+    } else {
+        artifacts.prerequisites.join(", ")
+    };
+
+    let report = format!(
+        "Ariana git diagnostics report\n\
+         =============================\n\n\
+         Commit count (git rev-list --all --count): {}\n\n\
+         Detected remote URL: {}\n\n\
+         Would-be bundle size: {} bytes (incremental: {})\n\
+         Would-be patch size: {} bytes\n\
+         Bundle prerequisites: {}\n\n\
+         --- git status --porcelain=v2 ---\n{}\n\n\
+         --- git config --list --show-scope (scrubbed) ---\n{}\n\n\
+         --- git count-objects -vH ---\n{}\n",
+        commit_count,
+        detected_remote_url.as_deref().unwrap_or("(none detected)"),
+        bundle_size,
+        artifacts.is_incremental,
+        patch_size,
+        prerequisites,
+        status,
+        config,
+        object_counts,
+    );
+
+    let mut zip_buffer = Vec::new();
+    {
+        use std::io::Cursor;
+        use zip::{write::FileOptions, ZipWriter};
+
+        let writer = Cursor::new(&mut zip_buffer);
+        let mut zip = ZipWriter::new(writer);
+        let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        zip.start_file("diagnostics.txt", options)
+            .map_err(|e| format!("Failed to start diagnostics zip entry: {}", e))?;
+        zip.write_all(report.as_bytes())
+            .map_err(|e| format!("Failed to write diagnostics report: {}", e))?;
+        zip.finish()
+            .map_err(|e| format!("Failed to finalize diagnostics archive: {}", e))?;
+    }
+
+    let temp_dir = std::env::temp_dir();
+    let archive_path = temp_dir.join(format!("ariana_diagnostics_{}.zip", uuid::Uuid::new_v4()));
+    fs::write(&archive_path, &zip_buffer)
+        .map_err(|e| format!("Failed to write diagnostics archive: {}", e))?;
+
+    println!(
+        "[DIAGNOSTICS] Diagnostics bundle written to: {} ({} bytes)",
+        archive_path.display(),
+        zip_buffer.len()
+    );
+
+    Ok(archive_path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 pub async fn create_git_bundle_and_patch(source_path: String) -> Result<(String, String), String> {
     let source_dir = Path::new(&source_path);
@@ -887,23 +1384,7 @@ pub async fn create_git_bundle_and_patch(source_path: String) -> Result<(String,
 
     // Check if repository has any commits
     println!("[GIT] Checking if repository has commits");
-    println!("[GIT] Running: git rev-list --all --count");
-    let log_output = new_command("git")
-        .args(&["rev-list", "--all", "--count"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to check git history: {}", e))?;
-    println!("[GIT] git rev-list completed");
-
-    let commit_count = if log_output.status.success() {
-        String::from_utf8_lossy(&log_output.stdout)
-            .trim()
-            .parse::<u32>()
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
+    let commit_count = commit_count(source_dir);
     println!("[GIT] Repository has {} commits", commit_count);
 
     if commit_count == 0 {
@@ -938,73 +1419,15 @@ pub async fn create_git_bundle_and_patch(source_path: String) -> Result<(String,
     // Create patch for uncommitted changes (non-invasive - never touches staging area)
     println!("[GIT] Creating patch from uncommitted changes");
 
-    let mut patch_content = Vec::new();
-
-    // 1. Get diff for tracked files (only if we have commits, i.e., HEAD exists)
-    if commit_count > 0 {
-        let diff_output = new_command("git")
-            .args(&["diff", "HEAD"])
-            .current_dir(source_dir)
-            .output()
-            .map_err(|e| format!("Failed to get diff: {}", e))?;
-
-        if !diff_output.status.success() {
+    // Tracked diff vs HEAD plus untracked files reported as new, via a
+    // throwaway index rather than hand-built diffs.
+    let patch_content = match diff_with_untracked_as_new_files(source_dir, commit_count) {
+        Ok(content) => content,
+        Err(e) => {
             let _ = fs::remove_file(&bundle_path);
-            return Err(format!(
-                "Git diff failed: {}",
-                String::from_utf8_lossy(&diff_output.stderr)
-            ));
+            return Err(e);
         }
-
-        patch_content = diff_output.stdout;
-    }
-
-    // 2. Get list of untracked files
-    let untracked_output = new_command("git")
-        .args(&["ls-files", "--others", "--exclude-standard"])
-        .current_dir(source_dir)
-        .output()
-        .map_err(|e| format!("Failed to list untracked files: {}", e))?;
-
-    if untracked_output.status.success() {
-        let untracked_files = String::from_utf8_lossy(&untracked_output.stdout);
-
-        // 3. For each untracked file, create a diff entry
-        for file_path in untracked_files.lines() {
-            if file_path.is_empty() {
-                continue;
-            }
-
-            let full_path = source_dir.join(file_path);
-
-            // Read file content
-            if let Ok(content) = fs::read_to_string(&full_path) {
-                let lines: Vec<&str> = content.lines().collect();
-
-                if lines.is_empty() {
-                    // Empty file - no hunk header needed
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..e69de29\n--- /dev/null\n+++ b/{}\n",
-                        file_path, file_path, file_path
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-                } else {
-                    // Non-empty file - include hunk header
-                    let diff_header = format!(
-                        "diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n@@ -0,0 +1,{} @@\n",
-                        file_path, file_path, file_path, lines.len()
-                    );
-                    patch_content.extend_from_slice(diff_header.as_bytes());
-
-                    for line in lines {
-                        patch_content.extend_from_slice(b"+");
-                        patch_content.extend_from_slice(line.as_bytes());
-                        patch_content.extend_from_slice(b"\n");
-                    }
-                }
-            }
-        }
-    }
+    };
 
     // Normalize line endings to LF (git patches must use LF, not CRLF)
     // This is critical for cross-platform compatibility (Windows -> Linux)
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, resolve_ssh_key_path, ssh_identity_args};
+
+/// Which OS family a remote is running. Ariana's path handling and shell
+/// quoting assumed Unix everywhere; this is what downstream code should
+/// branch on instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SshFamily {
+    Unix,
+    Windows,
+}
+
+/// `cmd.exe` metacharacters that retain special meaning (command
+/// separators, redirection, escaping) even inside a double-quoted
+/// argument - `cmd`'s tokenizer runs before it looks at quoting, unlike a
+/// POSIX shell. A value containing any of these can't be made safe by
+/// quoting alone, so `SshFamily::shell_quote` rejects them outright rather
+/// than passing them through to a remote `cmd /c` invocation.
+const CMD_METACHARACTERS: &[char] = &['&', '|', '<', '>', '^', '%'];
+
+impl SshFamily {
+    /// Quotes `value` as a single argument for this family's default
+    /// remote shell - POSIX single-quoting for Unix, `cmd.exe`
+    /// double-quoting for Windows. `cmd.exe` has no escape sequence for a
+    /// literal `"` inside a quoted argument, so one is stripped rather than
+    /// (incorrectly) passed through.
+    ///
+    /// Errors for `Windows` if `value` contains any of `CMD_METACHARACTERS`:
+    /// `cmd.exe` tokenizes those before it ever looks at quoting, so they'd
+    /// let a caller-supplied path (e.g. from `remote_fs_rename`) break out
+    /// into a second command on the remote host.
+    pub fn shell_quote(&self, value: &str) -> Result<String, String> {
+        match self {
+            SshFamily::Unix => Ok(format!("'{}'", value.replace('\'', "'\\''"))),
+            SshFamily::Windows => {
+                if let Some(bad) = value.chars().find(|c| CMD_METACHARACTERS.contains(c)) {
+                    return Err(format!(
+                        "Path contains '{}', which is not safe to pass to a remote cmd.exe command",
+                        bad
+                    ));
+                }
+                Ok(format!("\"{}\"", value.replace('"', "")))
+            }
+        }
+    }
+
+    /// Joins `base` and `child` with this family's path separator.
+    pub fn join_path(&self, base: &str, child: &str) -> String {
+        match self {
+            SshFamily::Unix => format!("{}/{}", base.trim_end_matches('/'), child),
+            SshFamily::Windows => format!("{}\\{}", base.trim_end_matches('\\'), child),
+        }
+    }
+}
+
+/// Caches `host -> SshFamily` for the life of the process, mirroring
+/// `TerminfoCache`/`AgentBinaryCache`'s per-host memoization, so every
+/// remote filesystem/shell call doesn't re-probe the same host.
+#[derive(Default)]
+pub struct RemoteFamilyCache {
+    detected: Mutex<HashMap<String, SshFamily>>,
+}
+
+impl RemoteFamilyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached family for `host`, detecting and caching it
+    /// first if this is the first time this process has asked.
+    pub fn get_or_detect(&self, host: &str) -> Result<SshFamily, String> {
+        {
+            let detected = self.detected.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            if let Some(family) = detected.get(host) {
+                return Ok(*family);
+            }
+        }
+
+        let family = detect_remote_family(host)?;
+
+        self.detected
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?
+            .insert(host.to_string(), family);
+
+        Ok(family)
+    }
+}
+
+fn run_probe(host: &str, command: &str) -> Result<Output, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run remote probe on {}: {}", host, e))
+}
+
+/// Detects whether `host` is a Unix or Windows remote over the established
+/// SSH connection: tries `uname -s` first, since any Unix-ish remote
+/// understands it and prints a non-empty family name (`Linux`, `Darwin`,
+/// ...); if that fails to run or comes back empty (a Windows remote's
+/// default shell - `cmd.exe` or PowerShell - doesn't recognize `uname` and
+/// exits nonzero), falls back to `cmd /c ver`, which only a Windows
+/// remote's default shell understands.
+pub fn detect_remote_family(host: &str) -> Result<SshFamily, String> {
+    if let Ok(output) = run_probe(host, "uname -s") {
+        let uname = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !uname.is_empty() {
+            return Ok(SshFamily::Unix);
+        }
+    }
+
+    let output = run_probe(host, "cmd /c ver")?;
+    let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !ver.is_empty() {
+        return Ok(SshFamily::Windows);
+    }
+
+    Err(format!("Could not determine remote OS family for {}", host))
+}
+
+/// Tauri-facing wrapper around `RemoteFamilyCache::get_or_detect`, so the
+/// frontend can ask up front (e.g. before offering remote-path editing UI)
+/// instead of every caller re-deriving it.
+#[tauri::command]
+pub fn detect_remote_os_family(
+    host: String,
+    family_cache: tauri::State<'_, Arc<RemoteFamilyCache>>,
+) -> Result<SshFamily, String> {
+    family_cache.get_or_detect(&host)
+}
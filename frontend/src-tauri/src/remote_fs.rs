@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Output, Stdio};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use crate::command_utils::new_command;
+use crate::remote_family::{RemoteFamilyCache, SshFamily};
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, resolve_ssh_key_path, ssh_identity_args};
+
+/// Kind of remote filesystem entry, as reported by `stat`/`find` on the
+/// `ariana-agent-<id>` host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteEntryKind {
+    File,
+    Dir,
+    Symlink,
+    Other,
+}
+
+/// One entry in a remote directory listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteDirEntry {
+    pub name: String,
+    pub kind: RemoteEntryKind,
+    pub size: u64,
+    pub mtime: u64,
+}
+
+/// Metadata for a single remote path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteMetadata {
+    pub kind: RemoteEntryKind,
+    pub size: u64,
+    pub mtime: u64,
+}
+
+/// The result of reading a remote path - either a file's bytes (base64
+/// encoded, to cross the IPC boundary safely) or a directory's listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RemoteReadResult {
+    File { content_base64: String },
+    Dir { entries: Vec<RemoteDirEntry> },
+}
+
+/// One hit from `remote_fs_search`: a matching path, and (for content
+/// searches) the matching line.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSearchHit {
+    pub path: String,
+    pub line: Option<String>,
+}
+
+/// One filesystem change surfaced by a `remote_fs_watch` subscription.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteFsChangeEvent {
+    pub watch_id: String,
+    pub path: String,
+    pub kind: String, // "create" | "modify" | "delete"
+}
+
+/// Tracks running `inotifywait` watch processes, keyed by a caller-visible
+/// watch id, so `remote_fs_unwatch` can tear one down.
+#[derive(Default)]
+pub struct RemoteFsWatches {
+    watches: Mutex<HashMap<String, Child>>,
+}
+
+impl RemoteFsWatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Runs `command` on `host` (an SSH config alias, e.g.
+/// `ariana-agent-<id>`) and waits for it to finish, returning its output.
+fn run_remote(host: &str, command: &str) -> Result<Output, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run remote command on {}: {}", host, e))
+}
+
+fn shell_quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+fn parse_kind(letter: &str) -> RemoteEntryKind {
+    match letter {
+        "f" => RemoteEntryKind::File,
+        "d" => RemoteEntryKind::Dir,
+        "l" => RemoteEntryKind::Symlink,
+        _ => RemoteEntryKind::Other,
+    }
+}
+
+#[tauri::command]
+pub fn remote_fs_exists(
+    host: String,
+    path: String,
+    family_cache: State<'_, Arc<RemoteFamilyCache>>,
+) -> Result<bool, String> {
+    let family = family_cache.get_or_detect(&host)?;
+
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(&host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(&host, ssh_kind)?;
+
+    let quoted_path = family.shell_quote(&path)?;
+    let command = match family {
+        SshFamily::Unix => format!("test -e {}", quoted_path),
+        SshFamily::Windows => format!("cmd /c if exist {} (exit 0) else (exit 1)", quoted_path),
+    };
+
+    let status = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(&host)
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to check remote path {} on {}: {}", path, host, e))?;
+
+    Ok(status.success())
+}
+
+/// Unix-only for now: there's no one-line `cmd.exe` equivalent of `stat`
+/// that reports size/mtime/type in a parseable form, and reaching for
+/// PowerShell would mean assuming a second remote shell on top of the
+/// `cmd.exe` probe `detect_remote_family` already runs. Called on a
+/// Windows remote, this returns whatever error `stat` produces there.
+#[tauri::command]
+pub fn remote_fs_metadata(host: String, path: String) -> Result<RemoteMetadata, String> {
+    let command = format!("stat -c '%s %Y %F' {}", shell_quote(&path));
+    let output = run_remote(&host, &command)?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to stat {} on {}: {}",
+            path,
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut parts = stdout.trim().splitn(3, ' ');
+    let size: u64 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let mtime: u64 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let file_type = parts.next().unwrap_or("");
+
+    let kind = if file_type.contains("directory") {
+        RemoteEntryKind::Dir
+    } else if file_type.contains("symbolic link") {
+        RemoteEntryKind::Symlink
+    } else if file_type.contains("regular") {
+        RemoteEntryKind::File
+    } else {
+        RemoteEntryKind::Other
+    };
+
+    Ok(RemoteMetadata { kind, size, mtime })
+}
+
+/// Unix-only for the same reason as `remote_fs_metadata`: listing a
+/// directory relies on `find -printf`, and reading a file relies on
+/// `base64`, neither of which `cmd.exe` has a built-in equivalent for.
+#[tauri::command]
+pub fn remote_fs_read(host: String, path: String) -> Result<RemoteReadResult, String> {
+    let metadata = remote_fs_metadata(host.clone(), path.clone())?;
+
+    match metadata.kind {
+        RemoteEntryKind::Dir => {
+            let command = format!(
+                "find {} -mindepth 1 -maxdepth 1 -printf '%f\\t%s\\t%T@\\t%y\\n'",
+                shell_quote(&path)
+            );
+            let output = run_remote(&host, &command)?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to list {} on {}: {}",
+                    path,
+                    host,
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+
+            let entries = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split('\t');
+                    let name = fields.next()?.to_string();
+                    let size: u64 = fields.next()?.parse().ok()?;
+                    let mtime: f64 = fields.next()?.parse().ok()?;
+                    let kind = parse_kind(fields.next()?);
+                    Some(RemoteDirEntry {
+                        name,
+                        kind,
+                        size,
+                        mtime: mtime as u64,
+                    })
+                })
+                .collect();
+
+            Ok(RemoteReadResult::Dir { entries })
+        }
+        _ => {
+            let command = format!("base64 {}", shell_quote(&path));
+            let output = run_remote(&host, &command)?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to read {} on {}: {}",
+                    path,
+                    host,
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+
+            let content_base64 = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .collect::<String>();
+
+            Ok(RemoteReadResult::File { content_base64 })
+        }
+    }
+}
+
+/// Unix-only: decodes the incoming base64 with `base64 -d`, which
+/// `cmd.exe` has no equivalent for.
+#[tauri::command]
+pub fn remote_fs_write(
+    host: String,
+    path: String,
+    content_base64: String,
+    append: bool,
+) -> Result<(), String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(&host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(&host, ssh_kind)?;
+
+    let redirect = if append { ">>" } else { ">" };
+    let command = format!(
+        "mkdir -p $(dirname {}) && base64 -d {} {}",
+        shell_quote(&path),
+        redirect,
+        shell_quote(&path)
+    );
+
+    let mut child = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(&host)
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn remote write to {} on {}: {}", path, host, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open stdin for remote write".to_string())?
+        .write_all(content_base64.as_bytes())
+        .map_err(|e| format!("Failed to write content to remote write: {}", e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for remote write: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to write {} on {}: {}",
+            path,
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remote_fs_rename(
+    host: String,
+    from: String,
+    to: String,
+    family_cache: State<'_, Arc<RemoteFamilyCache>>,
+) -> Result<(), String> {
+    let family = family_cache.get_or_detect(&host)?;
+    let quoted_from = family.shell_quote(&from)?;
+    let quoted_to = family.shell_quote(&to)?;
+    let command = match family {
+        SshFamily::Unix => format!("mv {} {}", quoted_from, quoted_to),
+        SshFamily::Windows => format!("cmd /c move /y {} {}", quoted_from, quoted_to),
+    };
+    let output = run_remote(&host, &command)?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to rename {} to {} on {}: {}",
+            from,
+            to,
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remote_fs_make_dir(
+    host: String,
+    path: String,
+    family_cache: State<'_, Arc<RemoteFamilyCache>>,
+) -> Result<(), String> {
+    let family = family_cache.get_or_detect(&host)?;
+    let quoted_path = family.shell_quote(&path)?;
+    let command = match family {
+        // `mkdir -p` and `cmd`'s `mkdir` both already create missing parent
+        // directories and no-op when the target exists.
+        SshFamily::Unix => format!("mkdir -p {}", quoted_path),
+        SshFamily::Windows => format!("cmd /c if not exist {path} mkdir {path}", path = quoted_path),
+    };
+    let output = run_remote(&host, &command)?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to create directory {} on {}: {}",
+            path,
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remote_fs_remove(
+    host: String,
+    path: String,
+    recursive: bool,
+    family_cache: State<'_, Arc<RemoteFamilyCache>>,
+) -> Result<(), String> {
+    let family = family_cache.get_or_detect(&host)?;
+    let quoted_path = family.shell_quote(&path)?;
+    let command = match family {
+        SshFamily::Unix => {
+            let flag = if recursive { "-rf" } else { "-f" };
+            format!("rm {} {}", flag, quoted_path)
+        }
+        // `recursive` distinguishes a directory removal (`rmdir /s /q`)
+        // from a file removal (`del /f /q`), same as the `rm` flags do.
+        SshFamily::Windows => {
+            if recursive {
+                format!("cmd /c rmdir /s /q {}", quoted_path)
+            } else {
+                format!("cmd /c del /f /q {}", quoted_path)
+            }
+        }
+    };
+    let output = run_remote(&host, &command)?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to remove {} on {}: {}",
+            path,
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
+}
+
+/// Recursively searches `root` on `host`, either by filename (`content =
+/// false`, matching `pattern` as an extended regex against each path) or by
+/// file contents (`content = true`, equivalent to `grep -rnE`).
+///
+/// Unix-only: `findstr`/`where` cover neither `grep -rnE`'s nor `find
+/// -regextype posix-extended`'s regex dialect, so there's no faithful
+/// `cmd.exe` translation here.
+#[tauri::command]
+pub fn remote_fs_search(
+    host: String,
+    root: String,
+    pattern: String,
+    content: bool,
+) -> Result<Vec<RemoteSearchHit>, String> {
+    let command = if content {
+        format!(
+            "grep -rnE {} {} 2>/dev/null",
+            shell_quote(&pattern),
+            shell_quote(&root)
+        )
+    } else {
+        format!(
+            "find {} -regextype posix-extended -iregex {}",
+            shell_quote(&root),
+            shell_quote(&format!(".*{}.*", pattern))
+        )
+    };
+
+    let output = run_remote(&host, &command)?;
+    // grep exits 1 when nothing matched - that's an empty result, not an
+    // error (find exits 0 either way).
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!(
+            "Search failed on {}: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let hits = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            if content {
+                let mut parts = line.splitn(2, ':');
+                let path = parts.next().unwrap_or_default().to_string();
+                let line = parts.next().map(|s| s.to_string());
+                RemoteSearchHit { path, line }
+            } else {
+                RemoteSearchHit {
+                    path: line.to_string(),
+                    line: None,
+                }
+            }
+        })
+        .collect();
+
+    Ok(hits)
+}
+
+/// Starts watching `path` on `host` for create/modify/delete events (via
+/// the remote's `inotifywait -m -r`), emitting a `remote-fs-change` event
+/// per change. Returns a watch id to pass to `remote_fs_unwatch`.
+///
+/// Unix-only: `cmd.exe` has no built-in recursive filesystem watcher
+/// (that's a Win32 API / PowerShell `FileSystemWatcher` affair, not a
+/// one-line shell command), so there's no equivalent to fall back to here.
+#[tauri::command]
+pub fn remote_fs_watch(
+    host: String,
+    path: String,
+    app_handle: AppHandle,
+    watches: State<'_, Arc<RemoteFsWatches>>,
+) -> Result<String, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(&host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(&host, ssh_kind)?;
+
+    let watch_id = Uuid::new_v4().to_string();
+
+    let command = format!(
+        "inotifywait -m -r -e create,modify,delete --format '%e|%w%f' {}",
+        shell_quote(&path)
+    );
+
+    let mut child = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(&host)
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start remote watch on {} ({}): {}", path, host, e))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to open stdout for remote watch".to_string())?;
+
+    let watch_id_for_thread = watch_id.clone();
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            let mut parts = line.splitn(2, '|');
+            let Some(events) = parts.next() else { continue };
+            let Some(changed_path) = parts.next() else {
+                continue;
+            };
+
+            // inotifywait can report several comma-separated event names for
+            // one change (e.g. "MODIFY,CLOSE_WRITE") - take the first one we
+            // care about.
+            let kind = if events.contains("CREATE") {
+                "create"
+            } else if events.contains("DELETE") {
+                "delete"
+            } else {
+                "modify"
+            };
+
+            let _ = app_handle.emit(
+                "remote-fs-change",
+                RemoteFsChangeEvent {
+                    watch_id: watch_id_for_thread.clone(),
+                    path: changed_path.to_string(),
+                    kind: kind.to_string(),
+                },
+            );
+        }
+    });
+
+    watches
+        .watches
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .insert(watch_id.clone(), child);
+
+    Ok(watch_id)
+}
+
+/// Stops a watch started by `remote_fs_watch`.
+#[tauri::command]
+pub fn remote_fs_unwatch(
+    watch_id: String,
+    watches: State<'_, Arc<RemoteFsWatches>>,
+) -> Result<(), String> {
+    let mut watches = watches
+        .watches
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if let Some(mut child) = watches.remove(&watch_id) {
+        let _ = child.kill();
+    }
+
+    Ok(())
+}
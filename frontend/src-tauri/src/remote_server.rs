@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::{Output, Stdio};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::command_utils::new_command;
+use crate::ssh_config::SSHConfigManager;
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, resolve_ssh_key_path, ssh_identity_args};
+
+/// Tracks which (host alias, ide, version) triples already have a
+/// confirmed-current remote server installed, so reopening the same agent
+/// in the same editor within a session doesn't re-probe every time.
+#[derive(Default)]
+pub struct RemoteServerCache {
+    provisioned: Mutex<HashSet<(String, String, String)>>,
+}
+
+impl RemoteServerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteServerProgress<'a> {
+    agent_id: &'a str,
+    ide_id: &'a str,
+    stage: &'a str,
+}
+
+fn emit_progress(app_handle: &AppHandle, agent_id: &str, ide_id: &str, stage: &str) {
+    let _ = app_handle.emit(
+        "remote-server-provision-progress",
+        RemoteServerProgress { agent_id, ide_id, stage },
+    );
+}
+
+fn run_remote(host: &str, command: &str) -> Result<Output, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run remote command on {}: {}", host, e))
+}
+
+/// Per-editor remote server layout: the directory (under the remote home
+/// directory) each version is installed into, and the vendor's tarball
+/// download URL for a given version/architecture. Modeled after how
+/// VSCode-family "Remote - SSH" extensions and Zed's remote editing both
+/// ship a separate headless server binary that must be present and
+/// version-matched on the host before a session can open.
+struct EditorServerSpec {
+    remote_dir: &'static str,
+    download_url_template: &'static str,
+}
+
+fn editor_spec(ide_id: &str) -> Result<EditorServerSpec, String> {
+    match ide_id {
+        "vscode" => Ok(EditorServerSpec {
+            remote_dir: ".vscode-server",
+            download_url_template: "https://update.code.visualstudio.com/commit:{version}/server-linux-{arch}/stable",
+        }),
+        "cursor" => Ok(EditorServerSpec {
+            remote_dir: ".cursor-server",
+            download_url_template: "https://cursor-update.cursor.sh/remote-releases/{version}/vscode-server-linux-{arch}.tar.gz",
+        }),
+        "windsurf" => Ok(EditorServerSpec {
+            remote_dir: ".windsurf-server",
+            download_url_template: "https://windsurf-stable.codeiumdata.com/linux-{arch}/server/{version}/vscode-server-linux-{arch}.tar.gz",
+        }),
+        "zed" => Ok(EditorServerSpec {
+            remote_dir: ".zed_server",
+            download_url_template: "https://zed.dev/api/releases/stable/{version}/zed-remote-server-linux-{arch}.tar.gz",
+        }),
+        _ => Err(format!(
+            "{} doesn't use a separate downloadable remote server binary",
+            ide_id
+        )),
+    }
+}
+
+/// VSCode-family servers name their Linux arch directories `x64`/`arm64`;
+/// Zed uses the Rust target-triple style `x86_64`/`aarch64`. Both are
+/// derived from the same `uname -m` probe, just normalized differently.
+fn editor_arch_name(ide_id: &str, uname_arch: &str) -> &'static str {
+    let is_arm = uname_arch.contains("aarch64") || uname_arch.contains("arm64");
+    match ide_id {
+        "zed" => {
+            if is_arm {
+                "aarch64"
+            } else {
+                "x86_64"
+            }
+        }
+        _ => {
+            if is_arm {
+                "arm64"
+            } else {
+                "x64"
+            }
+        }
+    }
+}
+
+fn remote_arch(host: &str) -> Result<String, String> {
+    let output = run_remote(host, "uname -m")?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to detect remote architecture on {}: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_lowercase())
+}
+
+/// The version/commit the *local* editor install expects its remote server
+/// counterpart to match. VSCode-family editors print it as the second line
+/// of `--version` (the commit hash the server build is keyed by); Zed just
+/// uses its own release version.
+fn local_expected_version(ide_id: &str, command: &str) -> Result<String, String> {
+    let output = new_command(command)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to run {} --version locally: {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!("{} --version failed locally", command));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+
+    match ide_id {
+        "vscode" | "cursor" | "windsurf" => lines
+            .nth(1)
+            .map(|commit| commit.trim().to_string())
+            .filter(|commit| !commit.is_empty())
+            .ok_or_else(|| format!("Could not parse commit hash from {} --version", command)),
+        "zed" => lines
+            .next()
+            .map(|first| first.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("Could not parse version from {} --version", command)),
+        _ => unreachable!("editor_spec already rejected unsupported ide_id"),
+    }
+}
+
+fn remote_server_installed(host: &str, spec: &EditorServerSpec, version: &str) -> bool {
+    let check_dir = match spec.remote_dir {
+        ".vscode-server" | ".cursor-server" | ".windsurf-server" => {
+            format!("~/{}/bin/{}", spec.remote_dir, version)
+        }
+        _ => format!("~/{}/{}", spec.remote_dir, version),
+    };
+    let command = format!("test -d {}", check_dir);
+    matches!(run_remote(host, &command), Ok(output) if output.status.success())
+}
+
+fn download_url(spec: &EditorServerSpec, arch: &str, version: &str) -> String {
+    spec.download_url_template
+        .replace("{version}", version)
+        .replace("{arch}", arch)
+}
+
+/// Downloads the vendor's server tarball for `version`/`arch` into the
+/// app's cache directory, skipping the fetch if it's already there from a
+/// previous provisioning run. Shells out to `curl` rather than pulling in
+/// an HTTP client crate, consistent with how the rest of this codebase
+/// reaches for already-installed system tools (`ssh`, `git`, `which`).
+fn download_server_tarball(
+    app_handle: &AppHandle,
+    ide_id: &str,
+    arch: &str,
+    version: &str,
+    spec: &EditorServerSpec,
+) -> Result<std::path::PathBuf, String> {
+    let cache_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to resolve app cache directory: {}", e))?
+        .join("remote-server-cache")
+        .join(ide_id)
+        .join(version);
+
+    std::fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create remote server cache directory: {}", e))?;
+
+    let tarball_path = cache_dir.join(format!("{}.tar.gz", arch));
+    if tarball_path.exists() {
+        return Ok(tarball_path);
+    }
+
+    let url = download_url(spec, arch, version);
+    let output = new_command("curl")
+        .arg("-fsSL")
+        .arg("-o")
+        .arg(&tarball_path)
+        .arg(&url)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&tarball_path);
+        return Err(format!(
+            "Failed to download {} remote server from {}: {}",
+            ide_id,
+            url,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(tarball_path)
+}
+
+/// Streams `tarball` to `host` over the SSH connection's stdin and extracts
+/// it into the editor's standard per-version server directory, mirroring
+/// `agent_binary::upload_binary`'s pipe-bytes-through-stdin approach.
+fn upload_and_extract(
+    host: &str,
+    spec: &EditorServerSpec,
+    tarball: &std::path::Path,
+    version: &str,
+) -> Result<(), String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    let version_dir = match spec.remote_dir {
+        ".vscode-server" | ".cursor-server" | ".windsurf-server" => {
+            format!("~/{}/bin/{}", spec.remote_dir, version)
+        }
+        _ => format!("~/{}/{}", spec.remote_dir, version),
+    };
+
+    let remote_command = format!(
+        "mkdir -p {version_dir} && tar -xzf - -C {version_dir} --strip-components=1",
+        version_dir = version_dir,
+    );
+
+    let tarball_bytes = std::fs::read(tarball)
+        .map_err(|e| format!("Failed to read cached tarball {:?}: {}", tarball, e))?;
+
+    let mut child = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(remote_command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn remote server upload to {}: {}", host, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open stdin for remote server upload".to_string())?
+        .write_all(&tarball_bytes)
+        .map_err(|e| format!("Failed to stream remote server tarball to {}: {}", host, e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for remote server upload to {}: {}", host, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Remote server upload/extract on {} failed: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ensures the agent reachable at `ariana-agent-{agent_id}` already has a
+/// version-matched `ide_id` remote server installed, downloading and
+/// uploading it first if not, so the editor's first real connection skips
+/// straight to the handshake instead of stalling on a cold provision.
+///
+/// The host is the alias `SSHConfigManager` assigns to the agent; remote
+/// commands run the same way `agent_binary.rs` runs them, by shelling out
+/// to the system `ssh` binary via `command_utils::new_command`.
+pub async fn provision_remote_server(
+    agent_id: String,
+    ide_id: String,
+    local_command: String,
+    app_handle: AppHandle,
+    cache: tauri::State<'_, std::sync::Arc<RemoteServerCache>>,
+) -> Result<String, String> {
+    let host = SSHConfigManager::host_alias(&agent_id);
+    let spec = editor_spec(&ide_id)?;
+
+    emit_progress(&app_handle, &agent_id, &ide_id, "checking-local-version");
+    let version = local_expected_version(&ide_id, &local_command)?;
+
+    let cache_key = (host.clone(), ide_id.clone(), version.clone());
+    if cache.provisioned.lock().map_err(|e| e.to_string())?.contains(&cache_key) {
+        return Ok(version);
+    }
+
+    emit_progress(&app_handle, &agent_id, &ide_id, "checking-remote");
+    if remote_server_installed(&host, &spec, &version) {
+        cache.provisioned.lock().map_err(|e| e.to_string())?.insert(cache_key);
+        emit_progress(&app_handle, &agent_id, &ide_id, "up-to-date");
+        return Ok(version);
+    }
+
+    let uname_arch = remote_arch(&host)?;
+    let arch = editor_arch_name(&ide_id, &uname_arch);
+
+    emit_progress(&app_handle, &agent_id, &ide_id, "downloading");
+    let tarball = download_server_tarball(&app_handle, &ide_id, arch, &version, &spec)?;
+
+    emit_progress(&app_handle, &agent_id, &ide_id, "uploading");
+    upload_and_extract(&host, &spec, &tarball, &version)?;
+    emit_progress(&app_handle, &agent_id, &ide_id, "done");
+
+    cache.provisioned.lock().map_err(|e| e.to_string())?.insert(cache_key);
+    Ok(version)
+}
+
+/// Tauri command wrapper around `provision_remote_server`. `local_command`
+/// is the local CLI name for `ide_id` (e.g. `code`, `cursor`, `zed`) so the
+/// expected server version can be read the same way `checks.rs` already
+/// probes for IDE availability.
+#[tauri::command]
+pub async fn provision_remote_server_command(
+    agent_id: String,
+    ide_id: String,
+    local_command: String,
+    app_handle: AppHandle,
+    cache: tauri::State<'_, std::sync::Arc<RemoteServerCache>>,
+) -> Result<String, String> {
+    provision_remote_server(agent_id, ide_id, local_command, app_handle, cache).await
+}
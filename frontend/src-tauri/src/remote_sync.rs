@@ -0,0 +1,205 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use wezterm_ssh::{Config, Session, SessionEvent, Sftp};
+
+use crate::host_key_verification::{ariana_known_hosts_path, ensure_host_key_verified};
+use crate::ssh_utils::resolve_ssh_key_path;
+
+/// Mirrors `sync.rs`'s `SyncInfo`, but for a sync directory that lives on a
+/// remote agent machine and is reached over SFTP instead of the local temp
+/// directory. `machine_ip`/`user` are kept alongside so the write/delete
+/// commands below know which host to re-connect to.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSyncInfo {
+    pub sync_id: String,
+    pub machine_ip: String,
+    pub user: String,
+    pub sync_path: String,
+}
+
+const AUTH_TIMEOUT: Duration = Duration::from_secs(15);
+
+async fn connect(machine_ip: &str, user: &str) -> Result<Session, String> {
+    let ssh_key_path = resolve_ssh_key_path()?;
+
+    // Same host-key gate `ssh_tunnel::connect_and_serve` and `ssh_pool`
+    // apply to their own wezterm_ssh sessions - this opens one too, so it
+    // needs the same verify-then-pin check instead of disabling it.
+    ensure_host_key_verified(machine_ip)?;
+    let known_hosts_path = ariana_known_hosts_path()?;
+
+    let mut config = Config::new();
+    config.add_default_config_files();
+    let mut options = config.for_host(machine_ip);
+    options.insert("user".to_string(), user.to_string());
+    options.insert("identityfile".to_string(), ssh_key_path);
+    options.insert("stricthostkeychecking".to_string(), "yes".to_string());
+    options.insert("userknownhostsfile".to_string(), known_hosts_path.to_string_lossy().to_string());
+
+    let (session, mut events) = Session::connect(options)
+        .map_err(|e| format!("Failed to open SSH session to {}: {}", machine_ip, e))?;
+
+    let deadline = tokio::time::Instant::now() + AUTH_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Err("Timed out waiting for SSH authentication".to_string());
+        }
+        match tokio::time::timeout(remaining, events.recv()).await {
+            Ok(Some(SessionEvent::Authenticated)) => return Ok(session),
+            Ok(Some(SessionEvent::Error(err))) => return Err(format!("SSH session error: {}", err)),
+            Ok(Some(_)) => continue,
+            Ok(None) => return Err("SSH session closed before authenticating".to_string()),
+            Err(_) => return Err("Timed out waiting for SSH authentication".to_string()),
+        }
+    }
+}
+
+async fn connect_sftp(machine_ip: &str, user: &str) -> Result<Sftp, String> {
+    let session = connect(machine_ip, user).await?;
+    session
+        .sftp()
+        .await
+        .map_err(|e| format!("Failed to start SFTP session to {}: {}", machine_ip, e))
+}
+
+/// Creates every missing directory component of `path`, the SFTP equivalent
+/// of `mkdir -p` (the Sftp surface only offers a single-level `mkdir`, same
+/// as distant-ssh2's).
+async fn mkdir_p(sftp: &Sftp, path: &Path) -> Result<(), String> {
+    let mut built = PathBuf::new();
+    for component in path.components() {
+        built.push(component);
+        match sftp.mkdir(&built, 0o755).await {
+            Ok(()) => {}
+            Err(_) if sftp.metadata(&built).await.is_ok() => {} // already exists
+            Err(e) => return Err(format!("Failed to create remote directory {}: {}", built.display(), e)),
+        }
+    }
+    Ok(())
+}
+
+/// Recursively removes `path` and everything under it. SFTP only exposes
+/// `rmdir` for empty directories, so children are walked and removed first.
+async fn remove_dir_all_sftp(sftp: &Sftp, path: &Path) -> Result<(), String> {
+    let entries = sftp
+        .read_dir(path)
+        .await
+        .map_err(|e| format!("Failed to list remote directory {}: {}", path.display(), e))?;
+
+    for (name, metadata) in entries {
+        if name == "." || name == ".." {
+            continue;
+        }
+        let child = path.join(&name);
+        if metadata.is_dir() {
+            Box::pin(remove_dir_all_sftp(sftp, &child)).await?;
+        } else {
+            sftp.remove_file(&child)
+                .await
+                .map_err(|e| format!("Failed to remove remote file {}: {}", child.display(), e))?;
+        }
+    }
+
+    sftp.rmdir(path)
+        .await
+        .map_err(|e| format!("Failed to remove remote directory {}: {}", path.display(), e))
+}
+
+/// Remote counterpart to `create_new_sync`: instead of a local temp
+/// directory, binds the sync to `/tmp/ide2-syncs/{agent_id}` on
+/// `machine_ip`, created over SFTP.
+#[tauri::command]
+pub async fn create_remote_sync(
+    agent_id: String,
+    machine_ip: String,
+    user: String,
+) -> Result<RemoteSyncInfo, String> {
+    let sync_path = format!("/tmp/ide2-syncs/{}", agent_id);
+
+    let sftp = connect_sftp(&machine_ip, &user).await?;
+    mkdir_p(&sftp, Path::new(&sync_path)).await?;
+
+    Ok(RemoteSyncInfo {
+        sync_id: uuid::Uuid::new_v4().to_string(),
+        machine_ip,
+        user,
+        sync_path,
+    })
+}
+
+#[tauri::command]
+pub async fn write_remote_sync_file(
+    machine_ip: String,
+    user: String,
+    base_path: String,
+    relative_path: String,
+    content: String,
+) -> Result<(), String> {
+    let full_path = Path::new(&base_path).join(&relative_path);
+    let sftp = connect_sftp(&machine_ip, &user).await?;
+
+    if let Some(parent) = full_path.parent() {
+        mkdir_p(&sftp, parent).await?;
+    }
+
+    let mut file = sftp
+        .create(&full_path)
+        .await
+        .map_err(|e| format!("Failed to create remote file {}: {}", relative_path, e))?;
+    file.write_all(content.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write remote file {}: {}", relative_path, e))
+}
+
+#[tauri::command]
+pub async fn delete_remote_sync_file(
+    machine_ip: String,
+    user: String,
+    base_path: String,
+    relative_path: String,
+) -> Result<(), String> {
+    let full_path = Path::new(&base_path).join(&relative_path);
+    let sftp = connect_sftp(&machine_ip, &user).await?;
+
+    match sftp.metadata(&full_path).await {
+        Ok(metadata) if metadata.is_dir() => remove_dir_all_sftp(&sftp, &full_path).await,
+        Ok(_) => sftp
+            .remove_file(&full_path)
+            .await
+            .map_err(|e| format!("Failed to delete remote file {}: {}", relative_path, e)),
+        Err(_) => Ok(()), // already gone
+    }
+}
+
+#[tauri::command]
+pub async fn create_remote_sync_dir(
+    machine_ip: String,
+    user: String,
+    base_path: String,
+    relative_path: String,
+) -> Result<(), String> {
+    let full_path = Path::new(&base_path).join(&relative_path);
+    let sftp = connect_sftp(&machine_ip, &user).await?;
+    mkdir_p(&sftp, &full_path).await
+}
+
+#[tauri::command]
+pub async fn delete_remote_sync_dir(
+    machine_ip: String,
+    user: String,
+    base_path: String,
+    relative_path: String,
+) -> Result<(), String> {
+    let full_path = Path::new(&base_path).join(&relative_path);
+    let sftp = connect_sftp(&machine_ip, &user).await?;
+
+    if sftp.metadata(&full_path).await.is_ok() {
+        remove_dir_all_sftp(&sftp, &full_path).await?;
+    }
+
+    Ok(())
+}
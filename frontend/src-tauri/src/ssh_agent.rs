@@ -0,0 +1,223 @@
+use std::env;
+use std::fs;
+use std::process::Stdio;
+
+use serde::Serialize;
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::get_ssh_directory;
+
+/// Default lifetime (seconds) for the Ariana key once loaded into the
+/// running ssh-agent, after which the agent forgets it and a later
+/// `ensure_agent_key_loaded` call reloads it.
+const DEFAULT_KEY_LIFETIME_SECS: u32 = 8 * 60 * 60;
+
+/// What `ensure_ssh_agent_running` found or started.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentInfo {
+    pub auth_sock: String,
+    pub pid: Option<u32>,
+    pub newly_launched: bool,
+}
+
+/// Reports an already-running agent (via `SSH_AUTH_SOCK`) without touching
+/// anything, or launches a new one with `ssh-agent -s` and adopts its
+/// `SSH_AUTH_SOCK`/`SSH_AGENT_PID` into this process's environment so every
+/// other command in this module (and `establish_ssh_tunnel`'s agent auth)
+/// picks it up automatically.
+#[tauri::command]
+pub fn ensure_ssh_agent_running() -> Result<AgentInfo, String> {
+    if let Ok(auth_sock) = env::var("SSH_AUTH_SOCK") {
+        return Ok(AgentInfo {
+            auth_sock,
+            pid: env::var("SSH_AGENT_PID").ok().and_then(|p| p.parse().ok()),
+            newly_launched: false,
+        });
+    }
+
+    let output = new_command("ssh-agent")
+        .arg("-s")
+        .output()
+        .map_err(|e| format!("Failed to execute ssh-agent: {}. Make sure OpenSSH is installed.", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ssh-agent failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let auth_sock = parse_agent_var(&stdout, "SSH_AUTH_SOCK")
+        .ok_or_else(|| "ssh-agent -s output did not include SSH_AUTH_SOCK".to_string())?;
+    let pid = parse_agent_var(&stdout, "SSH_AGENT_PID").and_then(|p| p.parse().ok());
+
+    env::set_var("SSH_AUTH_SOCK", &auth_sock);
+    if let Some(pid) = pid {
+        env::set_var("SSH_AGENT_PID", pid.to_string());
+    }
+
+    Ok(AgentInfo {
+        auth_sock,
+        pid,
+        newly_launched: true,
+    })
+}
+
+/// Parses a `VAR=value; export VAR;` line out of `ssh-agent -s`'s Bourne
+/// shell output.
+fn parse_agent_var(output: &str, var: &str) -> Option<String> {
+    let prefix = format!("{}=", var);
+    output.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split(';').next())
+            .map(|s| s.to_string())
+    })
+}
+
+/// Writes a throwaway `SSH_ASKPASS` helper script that prints `passphrase`,
+/// so `ssh-add` can be driven non-interactively instead of prompting on a
+/// tty we don't have. Caller is responsible for deleting the returned path.
+fn write_askpass_script(passphrase: &str) -> Result<std::path::PathBuf, String> {
+    let script_path = std::env::temp_dir().join(format!("ariana-askpass-{}.sh", uuid::Uuid::new_v4()));
+    let escaped = passphrase.replace('\'', "'\\''");
+    let script = format!("#!/bin/sh\necho '{}'\n", escaped);
+
+    fs::write(&script_path, script)
+        .map_err(|e| format!("Failed to write askpass helper: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to set askpass helper permissions: {}", e))?;
+    }
+
+    Ok(script_path)
+}
+
+/// Like `ensure_agent_key_loaded`, but for passphrase-protected keys:
+/// drives `ssh-add` through a temporary `SSH_ASKPASS` helper instead of
+/// relying on a controlling tty, so it can be called from a GUI context.
+#[tauri::command]
+pub fn add_agent_key_with_passphrase(passphrase: String) -> Result<(), String> {
+    require_ssh_auth_sock()?;
+
+    let key_path = get_ssh_directory()?.join("ariana_id_ed25519");
+    if !key_path.exists() {
+        return Err("Ariana SSH key not found. Call get_or_create_ssh_key first.".to_string());
+    }
+
+    let askpass_script = write_askpass_script(&passphrase)?;
+
+    let result = new_command("ssh-add")
+        .arg(&key_path)
+        .env("SSH_ASKPASS", &askpass_script)
+        .env("SSH_ASKPASS_REQUIRE", "force")
+        .env("DISPLAY", env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string()))
+        .stdin(Stdio::null())
+        .output();
+
+    let _ = fs::remove_file(&askpass_script);
+
+    let output = result.map_err(|e| format!("Failed to execute ssh-add: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ssh-add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Loads the `ariana_id_ed25519` key into the running ssh-agent (reached
+/// via `SSH_AUTH_SOCK`) with a bounded lifetime, so SSH connections to
+/// agent hosts authenticate against the agent instead of re-reading the
+/// private key from disk on every invocation.
+#[tauri::command]
+pub fn ensure_agent_key_loaded(lifetime_secs: Option<u32>) -> Result<(), String> {
+    require_ssh_auth_sock()?;
+
+    let key_path = get_ssh_directory()?.join("ariana_id_ed25519");
+    if !key_path.exists() {
+        return Err("Ariana SSH key not found. Call get_or_create_ssh_key first.".to_string());
+    }
+
+    let lifetime = lifetime_secs.unwrap_or(DEFAULT_KEY_LIFETIME_SECS);
+
+    let output = new_command("ssh-add")
+        .arg("-t")
+        .arg(lifetime.to_string())
+        .arg(&key_path)
+        .output()
+        .map_err(|e| format!("Failed to execute ssh-add: {}. Make sure OpenSSH is installed.", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ssh-add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Lists the identities currently loaded in the running ssh-agent, as
+/// reported by `ssh-add -l` (one fingerprint/comment line per identity).
+#[tauri::command]
+pub fn list_agent_identities() -> Result<Vec<String>, String> {
+    require_ssh_auth_sock()?;
+
+    let output = new_command("ssh-add")
+        .arg("-l")
+        .output()
+        .map_err(|e| format!("Failed to execute ssh-add: {}", e))?;
+
+    // `ssh-add -l` exits 1 (not an error) when the agent has no identities.
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!(
+            "ssh-add -l failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let identities = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.contains("no identities"))
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok(identities)
+}
+
+/// Removes Ariana's key from the running ssh-agent, e.g. on logout.
+#[tauri::command]
+pub fn remove_agent_key() -> Result<(), String> {
+    require_ssh_auth_sock()?;
+
+    let key_path = get_ssh_directory()?.join("ariana_id_ed25519");
+
+    let output = new_command("ssh-add")
+        .arg("-d")
+        .arg(&key_path)
+        .output()
+        .map_err(|e| format!("Failed to execute ssh-add: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ssh-add -d failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+fn require_ssh_auth_sock() -> Result<(), String> {
+    env::var("SSH_AUTH_SOCK")
+        .map(|_| ())
+        .map_err(|_| "No running ssh-agent found (SSH_AUTH_SOCK is not set).".to_string())
+}
@@ -0,0 +1,174 @@
+use std::fs;
+
+use crate::ssh_utils::get_ssh_directory;
+
+/// The effective OpenSSH client settings for one target, layering every
+/// matching `Host`/`Match all` block in `~/.ssh/config` the way real `ssh`
+/// does: first match per-keyword wins, so a specific `Host` block near the
+/// top overrides a catch-all `Host *` fallback further down.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedSshHost {
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<String>,
+    pub proxy_jump: Option<String>,
+    pub proxy_command: Option<String>,
+}
+
+/// One `Host`/`Match` block: the patterns (or negated patterns) that select
+/// it, and the keyword/value pairs it sets.
+struct ConfigBlock {
+    patterns: Vec<String>,
+    negated_patterns: Vec<String>,
+    match_all: bool,
+    settings: Vec<(String, String)>,
+}
+
+impl ConfigBlock {
+    fn matches(&self, alias: &str) -> bool {
+        if self.match_all {
+            return true;
+        }
+        if self.negated_patterns.iter().any(|pattern| glob_match(pattern, alias)) {
+            return false;
+        }
+        self.patterns.iter().any(|pattern| glob_match(pattern, alias))
+    }
+}
+
+/// A parsed `~/.ssh/config`, ready to resolve per-host settings out of.
+///
+/// Only `Host` blocks and `Match all` blocks (a common idiom for trailing
+/// global defaults) are understood; other `Match` criteria (`exec`,
+/// `canonical`, etc.) are parsed far enough to find the block's extent but
+/// never match, since evaluating them would mean re-implementing large
+/// chunks of `ssh_config(5)` for a case that's rare in practice.
+pub struct SshClientConfig {
+    blocks: Vec<ConfigBlock>,
+}
+
+impl SshClientConfig {
+    /// Parses `~/.ssh/config`. A missing file resolves to an empty config
+    /// rather than an error, since most hosts have no entry at all.
+    pub fn load() -> Result<Self, String> {
+        let config_path = get_ssh_directory()?.join("config");
+        if !config_path.exists() {
+            return Ok(Self { blocks: Vec::new() });
+        }
+
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read SSH config: {}", e))?;
+
+        Ok(Self::parse(&content))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut blocks = Vec::new();
+        let mut current: Option<ConfigBlock> = None;
+
+        for raw_line in content.lines() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((keyword, value)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let keyword = keyword.trim().to_lowercase();
+            let value = value.trim().trim_matches('"');
+
+            if keyword == "host" {
+                blocks.extend(current.take());
+
+                let mut patterns = Vec::new();
+                let mut negated_patterns = Vec::new();
+                for token in value.split_whitespace() {
+                    match token.strip_prefix('!') {
+                        Some(negated) => negated_patterns.push(negated.to_string()),
+                        None => patterns.push(token.to_string()),
+                    }
+                }
+
+                current = Some(ConfigBlock { patterns, negated_patterns, match_all: false, settings: Vec::new() });
+            } else if keyword == "match" {
+                blocks.extend(current.take());
+
+                let match_all = value.split_whitespace().next().is_some_and(|w| w.eq_ignore_ascii_case("all"));
+                current = Some(ConfigBlock { patterns: Vec::new(), negated_patterns: Vec::new(), match_all, settings: Vec::new() });
+            } else if let Some(block) = current.as_mut() {
+                block.settings.push((keyword, value.to_string()));
+            }
+            // Keywords before the first `Host`/`Match` line apply to every
+            // host per ssh_config(5), same as a leading `Host *` - but no
+            // known_hosts-managed config (ours included) writes anything
+            // before its first Host block, so this is left unhandled.
+        }
+        blocks.extend(current.take());
+
+        Self { blocks }
+    }
+
+    /// Resolves the effective settings for `alias`, in the same
+    /// first-match-wins order `ssh` itself applies.
+    pub fn resolve(&self, alias: &str) -> ResolvedSshHost {
+        let mut resolved = ResolvedSshHost::default();
+
+        for block in self.blocks.iter().filter(|block| block.matches(alias)) {
+            for (keyword, value) in &block.settings {
+                match keyword.as_str() {
+                    "hostname" if resolved.host_name.is_none() => resolved.host_name = Some(value.clone()),
+                    "user" if resolved.user.is_none() => resolved.user = Some(value.clone()),
+                    "port" if resolved.port.is_none() => resolved.port = value.parse().ok(),
+                    "identityfile" if resolved.identity_file.is_none() => {
+                        resolved.identity_file = Some(expand_tilde(value))
+                    }
+                    "proxyjump" if resolved.proxy_jump.is_none() && !value.eq_ignore_ascii_case("none") => {
+                        resolved.proxy_jump = Some(value.clone())
+                    }
+                    "proxycommand" if resolved.proxy_command.is_none() && !value.eq_ignore_ascii_case("none") => {
+                        resolved.proxy_command = Some(value.clone())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        resolved
+    }
+}
+
+/// `IdentityFile` values are commonly written `~/.ssh/some_key`; `ssh` itself
+/// expands that, so we need to as well since we're not handing this path
+/// back to `ssh` through the config file it came from.
+fn expand_tilde(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest).to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+        None => path.to_string(),
+    }
+}
+
+/// Matches `ssh_config(5)` `Host` patterns: `*` for any run of characters,
+/// `?` for exactly one, case-sensitive (hostnames/aliases in `Host` lines
+/// are matched literally, unlike the case-insensitive plain-pattern
+/// known_hosts matching in `host_key_verification`).
+fn glob_match(pattern: &str, candidate: &str) -> bool {
+    fn matches(pattern: &[char], candidate: &[char]) -> bool {
+        match pattern.first() {
+            None => candidate.is_empty(),
+            Some('*') => {
+                matches(&pattern[1..], candidate)
+                    || (!candidate.is_empty() && matches(pattern, &candidate[1..]))
+            }
+            Some('?') => !candidate.is_empty() && matches(&pattern[1..], &candidate[1..]),
+            Some(c) => candidate.first() == Some(c) && matches(&pattern[1..], &candidate[1..]),
+        }
+    }
+
+    let pattern_chars: Vec<char> = pattern.chars().collect();
+    let candidate_chars: Vec<char> = candidate.chars().collect();
+    matches(&pattern_chars, &candidate_chars)
+}
@@ -15,6 +15,15 @@ impl SSHConfigManager {
         Ok(Self { config_path })
     }
 
+    /// The SSH config `Host` alias an agent is reachable under once
+    /// `upsert_agent_entry` has run for it. Deterministic from `agent_id`
+    /// alone, so callers that already know an entry exists (e.g. a
+    /// provisioning step that runs after `get_ide_ssh_url`) don't need to
+    /// re-supply the agent's name/IP/user just to rediscover its alias.
+    pub fn host_alias(agent_id: &str) -> String {
+        format!("ariana-agent-{}", agent_id)
+    }
+
     /// Ensures the SSH config file exists and creates it if it doesn't
     fn ensure_config_exists(&self) -> Result<(), String> {
         if !self.config_path.exists() {
@@ -43,10 +52,24 @@ impl SSHConfigManager {
         agent_name: &str,
         machine_ip: &str,
         ssh_user: &str,
+    ) -> Result<String, String> {
+        self.upsert_agent_entry_with_agent_forwarding(agent_id, agent_name, machine_ip, ssh_user, false)
+    }
+
+    /// Like `upsert_agent_entry`, additionally setting `ForwardAgent yes`
+    /// when `forward_agent` is true, so the agent connection can itself
+    /// reach further hosts using identities held in our ssh-agent.
+    pub fn upsert_agent_entry_with_agent_forwarding(
+        &self,
+        agent_id: &str,
+        agent_name: &str,
+        machine_ip: &str,
+        ssh_user: &str,
+        forward_agent: bool,
     ) -> Result<String, String> {
         self.ensure_config_exists()?;
 
-        let host_alias = format!("ariana-agent-{}", agent_id);
+        let host_alias = Self::host_alias(agent_id);
         let ssh_key_path = get_ssh_directory()?.join("ariana_id_ed25519");
 
         // Read existing config
@@ -57,23 +80,37 @@ impl SSHConfigManager {
         let marker_start = format!("# Ariana Agent: {} (ID: {})", agent_name, agent_id);
         let marker_end = format!("# End Ariana Agent: {}", agent_id);
 
+        let forward_agent_line = if forward_agent {
+            "\n  ForwardAgent yes"
+        } else {
+            ""
+        };
+
+        // `HostKeyAlias` pins known_hosts lookups to the agent's actual
+        // address rather than `host_alias`, so a recreated agent that's
+        // handed the same machine back doesn't need re-verifying, and so
+        // `host_key_verification::ensure_host_key_verified` (invoked by
+        // `get_common_ssh_options` via `ssh -G`) resolves the same target
+        // host key checking itself verifies against.
         let new_entry = format!(
             r#"
 # Ariana Agent: {} (ID: {})
 Host {}
   HostName {}
+  HostKeyAlias {}
   User {}
   IdentityFile {}
-  StrictHostKeyChecking no
-  UserKnownHostsFile /dev/null
+  AddKeysToAgent yes{}
 # End Ariana Agent: {}
 "#,
             agent_name,
             agent_id,
             host_alias,
             machine_ip,
+            machine_ip,
             ssh_user,
             ssh_key_path.to_string_lossy(),
+            forward_agent_line,
             agent_id
         );
 
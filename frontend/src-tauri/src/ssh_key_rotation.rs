@@ -0,0 +1,166 @@
+use std::fs;
+
+use serde::Serialize;
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, get_ssh_directory};
+
+const DEFAULT_ROTATION_COMMENT: &str = "ariana-ide";
+
+/// Per-host outcome of a `rotate_ariana_ssh_key` attempt, so the caller can
+/// surface partial failures instead of all-or-nothing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotationResult {
+    pub agent_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+fn host_alias(agent_id: &str) -> String {
+    format!("ariana-agent-{}", agent_id)
+}
+
+/// Runs `command` on `host`, authenticating with `ssh_key_path` explicitly
+/// rather than whatever `get_ssh_key_path()` currently resolves to on disk -
+/// callers rotating that very key need to keep authenticating with the old
+/// one until every host has been updated.
+fn run_remote(host: &str, ssh_key_path: &str, command: &str) -> Result<(), String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    let output = new_command(&ssh_cmd)
+        .arg("-i")
+        .arg(ssh_key_path)
+        .args(&common_opts)
+        .arg(host)
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run remote command on {}: {}", host, e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    Ok(())
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Extracts the base64 key-body field (the second whitespace-separated
+/// field) from an `authorized_keys`-style public key line, ignoring the
+/// trailing comment so rotation matches on key material, not naming.
+fn public_key_body(public_key: &str) -> Option<&str> {
+    public_key.trim().split_whitespace().nth(1)
+}
+
+/// Removes exactly the line in `~/.ssh/authorized_keys` whose key body
+/// matches `old_public_key`, then appends `new_public_key`. Both edits run
+/// in a single remote shell invocation so a dropped connection can't leave
+/// `authorized_keys` with the new key appended but the old one still in
+/// place, or vice versa.
+fn rotate_authorized_keys(
+    host: &str,
+    old_key_path: &str,
+    old_public_key: &str,
+    new_public_key: &str,
+) -> Result<(), String> {
+    let old_key_body = public_key_body(old_public_key)
+        .ok_or_else(|| "Old public key is malformed (missing base64 body)".to_string())?;
+
+    let command = format!(
+        "grep -vF {} ~/.ssh/authorized_keys > ~/.ssh/authorized_keys.ariana_tmp 2>/dev/null; \
+         mv ~/.ssh/authorized_keys.ariana_tmp ~/.ssh/authorized_keys; \
+         echo {} >> ~/.ssh/authorized_keys",
+        shell_quote(old_key_body),
+        shell_quote(new_public_key.trim()),
+    );
+
+    run_remote(host, old_key_path, &command)
+}
+
+/// Rotates the Ariana SSH key: for each given agent, connects with the
+/// *current* (soon-to-be-old) key and rotates that machine's
+/// `~/.ssh/authorized_keys` to trust a freshly generated key instead (matched
+/// on key body, not comment, so unrelated keys sharing a comment aren't
+/// touched). The new keypair is generated up front under `.pending` names
+/// and only installed over the local one after every host has been
+/// attempted; a host whose rotation failed (see its `RotationResult`) still
+/// trusts only the old key. Doing this in the other order - replacing the
+/// local keypair first - would leave every host still trusting the old
+/// public key while `get_ssh_key_path()` (and so every subsequent
+/// connection) only has the new private key to authenticate with, locking
+/// every agent out. Mirrors ssh-keyctl's "renew = revoke then init" flow,
+/// but with "revoke" driven by the key being replaced, not the replacement.
+#[tauri::command]
+pub fn rotate_ariana_ssh_key(agent_ids: Vec<String>) -> Result<Vec<RotationResult>, String> {
+    let ssh_dir = get_ssh_directory()?;
+    let private_key_path = ssh_dir.join("ariana_id_ed25519");
+    let public_key_path = ssh_dir.join("ariana_id_ed25519.pub");
+
+    let old_key_path = private_key_path.to_string_lossy().to_string();
+    let old_public_key = fs::read_to_string(&public_key_path)
+        .map_err(|e| format!("Failed to read current public key: {}", e))?;
+
+    let new_public_key = generate_pending_key_pair(&ssh_dir)?;
+
+    let results: Vec<RotationResult> = agent_ids
+        .into_iter()
+        .map(|agent_id| {
+            let host = host_alias(&agent_id);
+            match rotate_authorized_keys(&host, &old_key_path, &old_public_key, &new_public_key) {
+                Ok(()) => RotationResult {
+                    agent_id,
+                    success: true,
+                    error: None,
+                },
+                Err(e) => RotationResult {
+                    agent_id,
+                    success: false,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect();
+
+    // Only swap the local keypair in for the one every host now trusts once
+    // the rotation loop above is done with it - the old key stays on disk
+    // (and thus usable by `run_remote`) for the entire loop.
+    let pending_private_path = ssh_dir.join("ariana_id_ed25519.pending");
+    let pending_public_path = ssh_dir.join("ariana_id_ed25519.pub.pending");
+    fs::rename(&pending_private_path, &private_key_path)
+        .map_err(|e| format!("Failed to install new private key: {}", e))?;
+    fs::rename(&pending_public_path, &public_key_path)
+        .map_err(|e| format!("Failed to install new public key: {}", e))?;
+
+    Ok(results)
+}
+
+/// Generates a fresh key pair at `ariana_id_ed25519.pending`/`.pub.pending`
+/// inside `ssh_dir`, alongside (not over) the current keypair, and returns
+/// its public key. Kept as `.pending` rather than reusing
+/// `get_or_create_ssh_key` directly, since that function writes straight to
+/// `ariana_id_ed25519`/`.pub` and won't regenerate a key that's already
+/// there - exactly the file `run_remote` needs to keep reading as the *old*
+/// key until every host has been rotated onto the replacement.
+fn generate_pending_key_pair(ssh_dir: &std::path::Path) -> Result<String, String> {
+    let pending_private_path = ssh_dir.join("ariana_id_ed25519.pending");
+    let pending_public_path = ssh_dir.join("ariana_id_ed25519.pub.pending");
+
+    let _ = fs::remove_file(&pending_private_path);
+    let _ = fs::remove_file(&pending_public_path);
+
+    crate::ssh_keys::generate_ssh_key(
+        &pending_private_path,
+        &pending_public_path,
+        osshkeys::keys::KeyType::ED25519,
+        DEFAULT_ROTATION_COMMENT.to_string(),
+        None,
+    )?;
+
+    fs::read_to_string(&pending_public_path)
+        .map(|key| key.trim().to_string())
+        .map_err(|e| format!("Failed to read newly generated public key: {}", e))
+}
@@ -1,10 +1,17 @@
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
+use osshkeys::cipher::Cipher;
+use osshkeys::keys::{KeyPair, KeyType};
 use crate::ssh_utils::get_ssh_directory;
 
+const DEFAULT_COMMENT: &str = "ariana-ide";
+
 #[tauri::command]
-pub fn get_or_create_ssh_key() -> Result<String, String> {
+pub fn get_or_create_ssh_key(
+    key_type: Option<String>,
+    comment: Option<String>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
     // Get the SSH directory path from centralized utility
     let ssh_dir = get_ssh_directory()?;
 
@@ -35,8 +42,13 @@ pub fn get_or_create_ssh_key() -> Result<String, String> {
     let need_new_key = !(private_key_path.exists() && public_key_path.exists());
 
     if need_new_key {
-        // Generate new SSH key pair (will use default format from ssh-keygen)
-        generate_ssh_key(&private_key_path)?;
+        generate_ssh_key(
+            &private_key_path,
+            &public_key_path,
+            parse_key_type(key_type.as_deref())?,
+            comment.unwrap_or_else(|| DEFAULT_COMMENT.to_string()),
+            passphrase,
+        )?;
     }
 
     // Read and return the public key
@@ -46,49 +58,69 @@ pub fn get_or_create_ssh_key() -> Result<String, String> {
     Ok(public_key.trim().to_string())
 }
 
-fn generate_ssh_key(private_key_path: &PathBuf) -> Result<(), String> {
-    // Use centralized SSH executable finder (for ssh-keygen)
-    let ssh_keygen_cmd = if cfg!(target_os = "windows") {
-        // On Windows, try to find ssh-keygen in common locations
-        if PathBuf::from("C:\\Windows\\System32\\OpenSSH\\ssh-keygen.exe").exists() {
-            "C:\\Windows\\System32\\OpenSSH\\ssh-keygen.exe"
-        } else if PathBuf::from("C:\\Program Files\\Git\\usr\\bin\\ssh-keygen.exe").exists() {
-            "C:\\Program Files\\Git\\usr\\bin\\ssh-keygen.exe"
-        } else {
-            "ssh-keygen"
-        }
+fn parse_key_type(requested: Option<&str>) -> Result<KeyType, String> {
+    match requested.unwrap_or("ed25519") {
+        "ed25519" => Ok(KeyType::ED25519),
+        "rsa" => Ok(KeyType::RSA),
+        "ecdsa" => Ok(KeyType::ECDSA),
+        other => Err(format!("Unsupported SSH key type: {other}")),
+    }
+}
+
+/// Generates a key pair in-process via `osshkeys` instead of shelling out to
+/// `ssh-keygen`, which isn't guaranteed to be installed (especially on
+/// Windows, despite the path-probing `find_ssh_executable` does for other
+/// commands). Writes both halves with the same permissions `ssh-keygen`
+/// would have used, so `determine_key_type` and friends keep working
+/// unchanged against keys we produce ourselves.
+///
+/// When `passphrase` is given, the private key is encrypted with
+/// `Cipher::Aes256_Ctr` instead of being written out as plaintext, so
+/// `read_ssh_key_pair` has to be given the same passphrase back before it
+/// will hand out the decrypted key.
+pub(crate) fn generate_ssh_key(
+    private_key_path: &PathBuf,
+    public_key_path: &PathBuf,
+    key_type: KeyType,
+    comment: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let bits = match key_type {
+        KeyType::RSA => 3072,
+        KeyType::ECDSA => 256,
+        _ => 0,
+    };
+
+    let mut key_pair = KeyPair::generate(key_type, bits)
+        .map_err(|e| format!("Failed to generate SSH key pair: {}", e))?;
+    *key_pair.comment_mut() = comment;
+
+    let cipher = if passphrase.is_some() {
+        Cipher::Aes256_Ctr
     } else {
-        "ssh-keygen"
+        Cipher::Null
     };
+    let private_key = key_pair
+        .serialize_openssh(passphrase.as_deref(), cipher)
+        .map_err(|e| format!("Failed to serialize private key: {}", e))?;
+    let public_key = key_pair
+        .serialize_publickey()
+        .map_err(|e| format!("Failed to serialize public key: {}", e))?;
 
-    // Generate the key with no passphrase (use default format from ssh-keygen)
-    // Note: Modern ssh-keygen generates OpenSSH format by default for ed25519, which is fine
-    let output = Command::new(ssh_keygen_cmd)
-        .args(&[
-            "-t", "ed25519",
-            "-f", &private_key_path.to_string_lossy(),
-            "-N", "", // No passphrase
-            "-C", "ariana-ide", // Comment
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute ssh-keygen: {}. Make sure OpenSSH is installed.", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ssh-keygen failed: {}", stderr));
-    }
+    fs::write(private_key_path, private_key)
+        .map_err(|e| format!("Failed to write private key: {}", e))?;
+    fs::write(public_key_path, public_key)
+        .map_err(|e| format!("Failed to write public key: {}", e))?;
 
-    // Set proper permissions on the private key (Unix only)
+    // Set proper permissions on Unix systems
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let metadata = fs::metadata(&private_key_path)
-            .map_err(|e| format!("Failed to get private key metadata: {}", e))?;
-        let mut permissions = metadata.permissions();
-        permissions.set_mode(0o600);
-        fs::set_permissions(&private_key_path, permissions)
+        fs::set_permissions(private_key_path, fs::Permissions::from_mode(0o600))
             .map_err(|e| format!("Failed to set private key permissions: {}", e))?;
+        fs::set_permissions(public_key_path, fs::Permissions::from_mode(0o644))
+            .map_err(|e| format!("Failed to set public key permissions: {}", e))?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::State;
+use wezterm_ssh::{Config, Session, SessionEvent};
+
+use crate::host_key_verification::{ariana_known_hosts_path, ensure_host_key_verified};
+use crate::ssh_tunnel::wait_for_authentication;
+
+/// Identifies a reusable SSH connection - the pool hands out the same
+/// session to any caller asking for the same host/port/user/identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SshConnectionKey {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub identity: String,
+}
+
+struct PooledSession {
+    session: Session,
+    alive: Arc<AtomicBool>,
+    established_at: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshPoolStats {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub alive: bool,
+    pub age_seconds: u64,
+}
+
+/// A checked-out connection handle. `Session` is itself a cheap, internally
+/// shared handle (the tunnel accept loop already clones it per inbound
+/// connection), so this is just a typed wrapper rather than a true RAII
+/// check-in/check-out - the underlying session stays pooled for the next
+/// caller regardless of how many guards currently exist.
+pub struct SshSessionGuard {
+    pub session: Session,
+}
+
+/// Keyed pool of reusable SSH sessions (one per host/port/user/identity) so
+/// repeated sync/IDE-URL/tunnel operations against the same agent host
+/// don't each pay a fresh handshake. A background watcher per session flips
+/// it dead the moment its event stream reports an error or closes, and
+/// `checkout` transparently reconnects instead of handing out a stale
+/// session.
+pub struct SshConnectionPool {
+    ssh_dir_override: Option<PathBuf>,
+    sessions: Mutex<HashMap<SshConnectionKey, PooledSession>>,
+    // Mirrors `TunnelManager`'s agent-keyed bookkeeping so
+    // `evict_agent` can pair with `close_all_tunnels_for_agent`.
+    agent_keys: Mutex<HashMap<String, Vec<SshConnectionKey>>>,
+}
+
+impl SshConnectionPool {
+    /// `ssh_dir_override` resolves private-key material relative to a
+    /// configurable location instead of the fixed `~/.ssh` - pass `None` to
+    /// keep the default.
+    pub fn new(ssh_dir_override: Option<PathBuf>) -> Self {
+        Self {
+            ssh_dir_override,
+            sessions: Mutex::new(HashMap::new()),
+            agent_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn identity_path(&self, identity: &str) -> Result<String, String> {
+        let ssh_dir = match &self.ssh_dir_override {
+            Some(dir) => dir.clone(),
+            None => dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?.join(".ssh"),
+        };
+
+        Ok(ssh_dir.join(identity).to_string_lossy().to_string())
+    }
+
+    /// Hands out a live session for `key`, reconnecting transparently if the
+    /// previously pooled one has died (or none exists yet), and records the
+    /// association with `agent_id` so `evict_agent` can tear it down later.
+    pub async fn checkout(&self, agent_id: &str, key: SshConnectionKey) -> Result<SshSessionGuard, String> {
+        {
+            let sessions = self.sessions.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            if let Some(pooled) = sessions.get(&key) {
+                if pooled.alive.load(Ordering::Relaxed) {
+                    return Ok(SshSessionGuard { session: pooled.session.clone() });
+                }
+            }
+        }
+
+        let pooled = self.connect(&key).await?;
+        let guard = SshSessionGuard { session: pooled.session.clone() };
+
+        {
+            let mut sessions = self.sessions.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            sessions.insert(key.clone(), pooled);
+        }
+        {
+            let mut agent_keys = self.agent_keys.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            let keys = agent_keys.entry(agent_id.to_string()).or_default();
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        Ok(guard)
+    }
+
+    async fn connect(&self, key: &SshConnectionKey) -> Result<PooledSession, String> {
+        let identity_path = self.identity_path(&key.identity)?;
+
+        // Same host-key gate `get_common_ssh_options` applies to subprocess
+        // `ssh` calls - this pool opens its own wezterm_ssh sessions, so it
+        // needs the same verify-then-pin check rather than inheriting it.
+        ensure_host_key_verified(&key.host)?;
+        let known_hosts_path = ariana_known_hosts_path()?;
+
+        let mut config = Config::new();
+        config.add_default_config_files();
+        let mut options = config.for_host(&key.host);
+        options.insert("user".to_string(), key.user.clone());
+        options.insert("port".to_string(), key.port.to_string());
+        options.insert("stricthostkeychecking".to_string(), "yes".to_string());
+        options.insert("userknownhostsfile".to_string(), known_hosts_path.to_string_lossy().to_string());
+        options.insert("identityfile".to_string(), identity_path);
+
+        let (session, mut events) =
+            Session::connect(options).map_err(|e| format!("Failed to open SSH session to {}: {}", key.host, e))?;
+
+        wait_for_authentication(&mut events).await?;
+
+        let alive = Arc::new(AtomicBool::new(true));
+        spawn_liveness_watcher(events, alive.clone());
+
+        Ok(PooledSession { session, alive, established_at: Instant::now() })
+    }
+
+    /// Evicts every pooled session associated with `agent_id`, pairing with
+    /// `close_all_tunnels_for_agent`. Returns how many were evicted.
+    pub fn evict_agent(&self, agent_id: &str) -> Result<usize, String> {
+        let keys = {
+            let mut agent_keys = self.agent_keys.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            agent_keys.remove(agent_id).unwrap_or_default()
+        };
+
+        let mut sessions = self.sessions.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let evicted = keys.iter().filter(|key| sessions.remove(key).is_some()).count();
+        Ok(evicted)
+    }
+
+    pub fn stats(&self) -> Result<Vec<SshPoolStats>, String> {
+        let sessions = self.sessions.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        Ok(sessions
+            .iter()
+            .map(|(key, pooled)| SshPoolStats {
+                host: key.host.clone(),
+                port: key.port,
+                user: key.user.clone(),
+                alive: pooled.alive.load(Ordering::Relaxed),
+                age_seconds: pooled.established_at.elapsed().as_secs(),
+            })
+            .collect())
+    }
+}
+
+/// Watches a pooled session's event stream for as long as it runs, flipping
+/// `alive` false the moment it reports an error or the stream closes.
+fn spawn_liveness_watcher(mut events: tokio::sync::mpsc::UnboundedReceiver<SessionEvent>, alive: Arc<AtomicBool>) {
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let SessionEvent::Error(_) = event {
+                break;
+            }
+        }
+        alive.store(false, Ordering::Relaxed);
+    });
+}
+
+/// Opens (or reuses) a pooled connection for `agent_id`'s host, just to warm
+/// it up ahead of a sync/IDE-URL/tunnel operation that will need it shortly.
+#[tauri::command]
+pub async fn warm_ssh_connection(
+    agent_id: String,
+    host: String,
+    port: u16,
+    user: String,
+    identity: String,
+    pool: State<'_, Arc<SshConnectionPool>>,
+) -> Result<(), String> {
+    pool.checkout(&agent_id, SshConnectionKey { host, port, user, identity }).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_ssh_pool_stats(pool: State<'_, Arc<SshConnectionPool>>) -> Result<Vec<SshPoolStats>, String> {
+    pool.stats()
+}
+
+/// Evicts all pooled sessions for `agent_id`, pairing with
+/// `close_all_tunnels_for_agent`.
+#[tauri::command]
+pub fn evict_ssh_connections_for_agent(agent_id: String, pool: State<'_, Arc<SshConnectionPool>>) -> Result<usize, String> {
+    pool.evict_agent(&agent_id)
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use crate::command_utils::new_command;
+
+/// Which SSH client binary Ariana is driving. Argument syntax differs
+/// enough between families - port, batch mode, and how (or whether) a
+/// known_hosts file can be specified - that command-building code needs to
+/// know which one it's talking to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshProgramKind {
+    OpenSsh,
+    Plink,
+    Putty,
+    TortoisePlink,
+}
+
+impl SshProgramKind {
+    fn executable_name(&self) -> &'static str {
+        match self {
+            SshProgramKind::OpenSsh => "ssh",
+            SshProgramKind::Plink => "plink.exe",
+            SshProgramKind::Putty => "putty.exe",
+            SshProgramKind::TortoisePlink => "tortoiseplink.exe",
+        }
+    }
+
+    /// Port flag: `-p` for OpenSSH, `-P` for the whole PuTTY family (they
+    /// use `-p` for something else on some of these tools, so all three
+    /// standardize on `-P`).
+    pub fn port_flag(&self) -> &'static str {
+        match self {
+            SshProgramKind::OpenSsh => "-p",
+            SshProgramKind::Plink | SshProgramKind::Putty | SshProgramKind::TortoisePlink => "-P",
+        }
+    }
+
+    /// Private key file flag - `-i` is shared across every kind here, but
+    /// this stays a method rather than a hard-coded literal at call sites
+    /// in case a future kind needs to diverge.
+    pub fn identity_flag(&self) -> &'static str {
+        "-i"
+    }
+
+    /// Non-interactive/unattended mode: OpenSSH takes it as a config
+    /// option, the PuTTY family as a dedicated flag that also suppresses
+    /// interactive host-key-change prompts.
+    pub fn batch_mode_args(&self) -> Vec<String> {
+        match self {
+            SshProgramKind::OpenSsh => vec!["-o".to_string(), "BatchMode=yes".to_string()],
+            SshProgramKind::Plink | SshProgramKind::Putty | SshProgramKind::TortoisePlink => {
+                vec!["-batch".to_string()]
+            }
+        }
+    }
+
+    /// Whether this kind understands OpenSSH's `-o key=value` options
+    /// (`StrictHostKeyChecking`, `UserKnownHostsFile`, `ProxyCommand`, the
+    /// `-J` jump-host flag, ...). Only true OpenSSH does - the PuTTY family
+    /// has no equivalent command-line surface for any of this, since it
+    /// keeps its own host-key cache in the Windows registry instead of a
+    /// known_hosts file.
+    pub fn supports_openssh_options(&self) -> bool {
+        matches!(self, SshProgramKind::OpenSsh)
+    }
+}
+
+/// Fixed install locations checked before falling back to PATH, in
+/// preference order: OpenSSH first (its argument syntax is what the rest
+/// of this module assumes by default), then the PuTTY family.
+const WINDOWS_CANDIDATE_PATHS: &[(SshProgramKind, &str)] = &[
+    (SshProgramKind::OpenSsh, "C:\\Windows\\System32\\OpenSSH\\ssh.exe"),
+    (SshProgramKind::OpenSsh, "C:\\Program Files\\Git\\usr\\bin\\ssh.exe"),
+    (SshProgramKind::Plink, "C:\\Program Files\\PuTTY\\plink.exe"),
+    (SshProgramKind::Plink, "C:\\Program Files (x86)\\PuTTY\\plink.exe"),
+    (
+        SshProgramKind::TortoisePlink,
+        "C:\\Program Files\\TortoiseGit\\bin\\TortoisePlink.exe",
+    ),
+    (
+        SshProgramKind::TortoisePlink,
+        "C:\\Program Files\\TortoiseSVN\\bin\\TortoisePlink.exe",
+    ),
+    (SshProgramKind::Putty, "C:\\Program Files\\PuTTY\\putty.exe"),
+    (SshProgramKind::Putty, "C:\\Program Files (x86)\\PuTTY\\putty.exe"),
+];
+
+fn is_on_path(executable: &str) -> bool {
+    new_command("where")
+        .arg(executable)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Find an SSH-capable executable on a Windows system: OpenSSH if present
+/// (matching historical behavior), otherwise whichever PuTTY-family tool
+/// can be found, checked first at common install paths and then on PATH.
+pub fn find_windows_ssh() -> Result<(SshProgramKind, String), String> {
+    for (kind, path) in WINDOWS_CANDIDATE_PATHS {
+        if PathBuf::from(path).exists() {
+            return Ok((*kind, path.to_string()));
+        }
+    }
+
+    for kind in [
+        SshProgramKind::OpenSsh,
+        SshProgramKind::Plink,
+        SshProgramKind::TortoisePlink,
+        SshProgramKind::Putty,
+    ] {
+        if is_on_path(kind.executable_name()) {
+            return Ok((kind, kind.executable_name().to_string()));
+        }
+    }
+
+    // Fallback: hope it's in PATH under its default name.
+    Ok((SshProgramKind::OpenSsh, "ssh".to_string()))
+}
@@ -1,13 +1,81 @@
 use std::collections::HashMap;
-use std::process::{Child, Stdio};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
 use tauri::State;
-use crate::ssh_utils::{get_ssh_key_path, find_ssh_executable, get_common_ssh_options};
-use crate::command_utils::new_command;
+use wezterm_ssh::{Config, Session, SessionEvent};
+
+use crate::host_key_verification::{ariana_known_hosts_path, ensure_host_key_verified};
+use crate::ssh_utils::resolve_ssh_key_path;
+
+/// How long to wait for the SSH session to report itself authenticated
+/// before giving up on establishing a tunnel.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How often the watchdog checks a tunnel that's currently alive.
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Initial and maximum delay between reconnect attempts once a tunnel is
+/// found dead; doubles after each failed attempt.
+const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TunnelState {
+    Alive,
+    Reconnecting,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TunnelStatusInfo {
+    pub remote_port: u16,
+    pub local_port: u16,
+    pub state: TunnelState,
+    pub last_error: Option<String>,
+}
+
+struct TunnelStatus {
+    state: TunnelState,
+    last_error: Option<String>,
+}
 
-// Track active SSH tunnels: (agent_id, remote_port) -> Child process
+/// The parameters needed to (re)establish a tunnel, kept around so the
+/// watchdog can respawn it from scratch after the connection dies.
+#[derive(Clone)]
+struct TunnelParams {
+    machine_ip: String,
+    actual_local_port: u16,
+    ssh_user: Option<String>,
+    use_agent: bool,
+}
+
+/// Shared handles the accept loop and the watchdog both touch: `cancel`
+/// stops everything for good (replaces the old `child.kill()`), `alive`
+/// flips to false whenever the accept loop exits on its own so the
+/// watchdog knows to reconnect, and `status` is what `get_tunnel_status`
+/// reads.
+#[derive(Clone)]
+struct TunnelRuntime {
+    cancel: Arc<AtomicBool>,
+    alive: Arc<AtomicBool>,
+    status: Arc<Mutex<TunnelStatus>>,
+}
+
+struct ActiveTunnel {
+    runtime: TunnelRuntime,
+    params: TunnelParams,
+}
+
+/// Track active SSH tunnels: (agent_id, remote_port) -> ActiveTunnel
 pub struct TunnelManager {
-    tunnels: Mutex<HashMap<(String, u16), Child>>,
+    tunnels: Mutex<HashMap<(String, u16), ActiveTunnel>>,
 }
 
 impl TunnelManager {
@@ -18,64 +86,311 @@ impl TunnelManager {
     }
 }
 
+fn set_status(status: &Arc<Mutex<TunnelStatus>>, state: TunnelState, last_error: Option<String>) {
+    let mut status = status.lock().unwrap();
+    status.state = state;
+    status.last_error = last_error;
+}
+
 #[tauri::command]
-pub fn establish_ssh_tunnel(
+pub async fn establish_ssh_tunnel(
     agent_id: String,
     machine_ip: String,
     remote_port: u16,
     local_port: Option<u16>,
     ssh_user: Option<String>,
+    use_agent: Option<bool>,
     tunnel_manager: State<'_, Arc<TunnelManager>>,
 ) -> Result<u16, String> {
     let actual_local_port = local_port.unwrap_or(remote_port);
     let key = (agent_id.clone(), remote_port);
 
-    // Check if tunnel already exists
-    let mut tunnels = tunnel_manager.tunnels.lock()
+    {
+        let tunnels = tunnel_manager
+            .tunnels
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        if tunnels.contains_key(&key) {
+            return Ok(actual_local_port); // Already established
+        }
+    }
+
+    let params = TunnelParams {
+        machine_ip,
+        actual_local_port,
+        ssh_user,
+        use_agent: use_agent.unwrap_or(false),
+    };
+
+    let runtime = TunnelRuntime {
+        cancel: Arc::new(AtomicBool::new(false)),
+        alive: Arc::new(AtomicBool::new(false)),
+        status: Arc::new(Mutex::new(TunnelStatus {
+            state: TunnelState::Reconnecting,
+            last_error: None,
+        })),
+    };
+
+    // The first connection attempt happens inline so a bad host/port/key
+    // fails `establish_ssh_tunnel` immediately instead of silently retrying
+    // in the background; every attempt after this one is the watchdog's job.
+    connect_and_serve(&params, remote_port, agent_id.clone(), runtime.clone()).await?;
+    runtime.alive.store(true, Ordering::Relaxed);
+    set_status(&runtime.status, TunnelState::Alive, None);
+
+    tauri::async_runtime::spawn(supervise_tunnel(
+        params.clone(),
+        remote_port,
+        agent_id.clone(),
+        runtime.clone(),
+    ));
+
+    let mut tunnels = tunnel_manager
+        .tunnels
+        .lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    tunnels.insert(key, ActiveTunnel { runtime, params });
+
+    log::info!(
+        target: "tunnel",
+        "Established tunnel: agent={}, port {} -> localhost:{}",
+        agent_id, remote_port, actual_local_port
+    );
+
+    Ok(actual_local_port)
+}
 
-    if tunnels.contains_key(&key) {
-        return Ok(actual_local_port); // Already established
+/// Watches a tunnel for as long as it hasn't been cancelled: while it's
+/// alive it just polls, and as soon as the accept loop reports it dead it
+/// reconnects with exponential backoff, mirroring the retry/zombie-cleanup
+/// behavior long-lived remote sessions need.
+async fn supervise_tunnel(
+    params: TunnelParams,
+    remote_port: u16,
+    agent_id: String,
+    runtime: TunnelRuntime,
+) {
+    let mut backoff = RECONNECT_INITIAL_BACKOFF;
+
+    loop {
+        if runtime.cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if !runtime.alive.load(Ordering::Relaxed) {
+            set_status(&runtime.status, TunnelState::Reconnecting, None);
+            match connect_and_serve(&params, remote_port, agent_id.clone(), runtime.clone()).await {
+                Ok(()) => {
+                    runtime.alive.store(true, Ordering::Relaxed);
+                    set_status(&runtime.status, TunnelState::Alive, None);
+                    backoff = RECONNECT_INITIAL_BACKOFF;
+                }
+                Err(e) => {
+                    log::warn!(
+                        target: "tunnel",
+                        "Reconnect failed for agent={} port={}: {}",
+                        agent_id, remote_port, e
+                    );
+                    set_status(&runtime.status, TunnelState::Failed, Some(e));
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(RECONNECT_MAX_BACKOFF);
+                    continue;
+                }
+            }
+        }
+
+        tokio::time::sleep(WATCHDOG_POLL_INTERVAL).await;
     }
+}
 
-    // Get SSH key path and executable from centralized utilities
-    let ssh_key_path = get_ssh_key_path()?;
-    let ssh_cmd = find_ssh_executable()?;
-    let common_opts = get_common_ssh_options();
-
-    // Use provided SSH user or default to 'ariana' for backward compatibility
-    let user = ssh_user.unwrap_or_else(|| "ariana".to_string());
-
-    // Build arguments (store formatted strings to extend lifetime)
-    let port_forward = format!("{}:localhost:{}", actual_local_port, remote_port);
-    let ssh_target = format!("{}@{}", user, machine_ip);
-
-    let mut args = vec![
-        "-i", &ssh_key_path,
-        "-L", &port_forward,
-        "-N", // No remote command
-    ];
-    args.extend(common_opts);
-    args.push("-o");
-    args.push("ExitOnForwardFailure=yes");
-    args.push(&ssh_target);
-
-    // Spawn SSH tunnel process
-    let child = new_command(&ssh_cmd)
-        .args(&args)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn SSH tunnel: {}", e))?;
-
-    // Store the child process
-    tunnels.insert(key.clone(), child);
-
-    println!("[TunnelManager] Established tunnel: agent={}, {}:{} -> localhost:{}",
-        agent_id, machine_ip, remote_port, actual_local_port);
+/// Waits for the session to report `Authenticated` (or a fatal error),
+/// bounded by `AUTH_TIMEOUT` so a hung handshake doesn't block the caller
+/// forever. Shared with `ssh_pool`, which needs the identical handshake
+/// wait when establishing a pooled connection.
+pub(crate) async fn wait_for_authentication(
+    events: &mut tokio::sync::mpsc::UnboundedReceiver<SessionEvent>,
+) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + AUTH_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Err("Timed out waiting for SSH authentication".to_string());
+        }
 
-    Ok(actual_local_port)
+        match tokio::time::timeout(remaining, events.recv()).await {
+            Ok(Some(SessionEvent::Authenticated)) => return Ok(()),
+            Ok(Some(SessionEvent::Error(err))) => return Err(format!("SSH session error: {}", err)),
+            Ok(Some(SessionEvent::Banner(Some(banner)))) => {
+                log::info!(target: "tunnel", "SSH banner: {}", banner);
+            }
+            Ok(Some(_)) => continue,
+            Ok(None) => return Err("SSH session closed before authenticating".to_string()),
+            Err(_) => return Err("Timed out waiting for SSH authentication".to_string()),
+        }
+    }
+}
+
+/// Opens a fresh SSH session and local listener for `params`, then spawns
+/// the accept loop in the background. Returns once the tunnel is actually
+/// serving connections; the accept loop itself keeps running until
+/// `runtime.cancel` is set or it hits a fatal error (at which point it
+/// flips `runtime.alive` back to false for the watchdog to notice).
+async fn connect_and_serve(
+    params: &TunnelParams,
+    remote_port: u16,
+    agent_id: String,
+    runtime: TunnelRuntime,
+) -> Result<(), String> {
+    let user = params.ssh_user.clone().unwrap_or_else(|| "ariana".to_string());
+
+    // Verify (and, on first contact, TOFU-pin) the key this host presents
+    // before handing it anything - this wezterm_ssh session bypasses the
+    // `ssh`-subprocess path `get_common_ssh_options` protects, so it needs
+    // its own call to the same check.
+    ensure_host_key_verified(&params.machine_ip)?;
+    let known_hosts_path = ariana_known_hosts_path()?;
+
+    let mut config = Config::new();
+    config.add_default_config_files();
+    let mut options = config.for_host(&params.machine_ip);
+    options.insert("user".to_string(), user);
+    options.insert("stricthostkeychecking".to_string(), "yes".to_string());
+    options.insert("userknownhostsfile".to_string(), known_hosts_path.to_string_lossy().to_string());
+
+    if params.use_agent {
+        let auth_sock = std::env::var("SSH_AUTH_SOCK")
+            .map_err(|_| "use_agent requested but no ssh-agent is running (SSH_AUTH_SOCK is not set)".to_string())?;
+        options.insert("identityagent".to_string(), auth_sock);
+    } else {
+        let ssh_key_path = resolve_ssh_key_path()?;
+        options.insert("identityfile".to_string(), ssh_key_path);
+    }
+
+    let (session, mut events) = Session::connect(options)
+        .map_err(|e| format!("Failed to open SSH session to {}: {}", params.machine_ip, e))?;
+
+    wait_for_authentication(&mut events).await?;
+
+    let listener = TcpListener::bind(("127.0.0.1", params.actual_local_port))
+        .map_err(|e| format!("Failed to bind local port {}: {}", params.actual_local_port, e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure local listener: {}", e))?;
+
+    spawn_accept_loop(listener, session, remote_port, runtime, agent_id);
+
+    Ok(())
+}
+
+/// Runs the local-port-forward accept loop on a background thread: each
+/// inbound TCP connection gets its own `direct-tcpip` channel to
+/// `localhost:remote_port`, pumped in both directions on tokio tasks. Marks
+/// `runtime.alive` false before returning, unless it's exiting because
+/// `runtime.cancel` was set.
+fn spawn_accept_loop(
+    listener: TcpListener,
+    session: Session,
+    remote_port: u16,
+    runtime: TunnelRuntime,
+    agent_id: String,
+) {
+    std::thread::spawn(move || {
+        loop {
+            if runtime.cancel.load(Ordering::Relaxed) {
+                return;
+            }
+
+            match listener.accept() {
+                Ok((stream, _addr)) => {
+                    let session = session.clone();
+                    let agent_id = agent_id.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = forward_connection(session, stream, remote_port).await {
+                            log::warn!(
+                                target: "tunnel",
+                                "Forward failed for agent={} port={}: {}",
+                                agent_id, remote_port, e
+                            );
+                        }
+                    });
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(e) => {
+                    log::error!(
+                        target: "tunnel",
+                        "Accept loop for agent={} port={} died: {}",
+                        agent_id, remote_port, e
+                    );
+                    break;
+                }
+            }
+        }
+
+        if !runtime.cancel.load(Ordering::Relaxed) {
+            runtime.alive.store(false, Ordering::Relaxed);
+            set_status(
+                &runtime.status,
+                TunnelState::Failed,
+                Some("local listener stopped unexpectedly".to_string()),
+            );
+        }
+    });
+}
+
+/// Opens a `direct-tcpip` channel to `localhost:remote_port` over `session`
+/// and pumps bytes between it and `stream` in both directions until either
+/// side closes.
+async fn forward_connection(
+    session: Session,
+    stream: std::net::TcpStream,
+    remote_port: u16,
+) -> Result<(), String> {
+    let channel = session
+        .connect_direct_tcpip(remote_port, "localhost", None)
+        .await
+        .map_err(|e| format!("Failed to open direct-tcpip channel: {}", e))?;
+
+    let tcp_reader = stream
+        .try_clone()
+        .map_err(|e| format!("Failed to clone local connection: {}", e))?;
+    let tcp_writer = stream;
+    let channel = Arc::new(Mutex::new(channel));
+
+    let upload_channel = channel.clone();
+    let upload = tauri::async_runtime::spawn_blocking(move || {
+        let mut tcp_reader = tcp_reader;
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let n = match tcp_reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            if upload_channel.lock().unwrap().write_all(&buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let download = tauri::async_runtime::spawn_blocking(move || {
+        let mut tcp_writer = tcp_writer;
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let n = match channel.lock().unwrap().read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            if tcp_writer.write_all(&buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let _ = upload.await;
+    let _ = download.await;
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -93,11 +408,39 @@ pub fn close_all_tunnels_for_agent(
         .collect();
 
     for key in keys_to_remove {
-        if let Some(mut child) = tunnels.remove(&key) {
-            let _ = child.kill();
-            println!("[TunnelManager] Closed tunnel: agent={}, port={}", key.0, key.1);
+        if let Some(tunnel) = tunnels.remove(&key) {
+            tunnel.runtime.cancel.store(true, Ordering::Relaxed);
+            log::info!(target: "tunnel", "Closed tunnel: agent={}, port={}", key.0, key.1);
         }
     }
 
     Ok(())
 }
+
+/// Reports each of an agent's tunnels' current state (alive/reconnecting/
+/// failed) and, if applicable, the last error the watchdog hit trying to
+/// bring it back up.
+#[tauri::command]
+pub fn get_tunnel_status(
+    agent_id: String,
+    tunnel_manager: State<'_, Arc<TunnelManager>>,
+) -> Result<Vec<TunnelStatusInfo>, String> {
+    let tunnels = tunnel_manager
+        .tunnels
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    Ok(tunnels
+        .iter()
+        .filter(|((aid, _), _)| aid == &agent_id)
+        .map(|((_, remote_port), tunnel)| {
+            let status = tunnel.runtime.status.lock().unwrap();
+            TunnelStatusInfo {
+                remote_port: *remote_port,
+                local_port: tunnel.params.actual_local_port,
+                state: status.state,
+                last_error: status.last_error.clone(),
+            }
+        })
+        .collect())
+}
@@ -1,5 +1,10 @@
+use std::fs;
 use std::path::PathBuf;
 
+use crate::host_key_verification::{ariana_known_hosts_path, ensure_host_key_verified};
+use crate::ssh_client_config::SshClientConfig;
+use crate::ssh_program::SshProgramKind;
+
 /// Get the path to the Ariana SSH private key
 pub fn get_ssh_key_path() -> Result<String, String> {
     let home_dir = dirs::home_dir()
@@ -14,6 +19,90 @@ pub fn get_ssh_key_path() -> Result<String, String> {
     Ok(key_path.to_string_lossy().to_string())
 }
 
+/// Resolves the private key connection code should use: the explicit
+/// Ariana-managed key if present, otherwise the best candidate
+/// `discover_ssh_key_path` can find lying around in `~/.ssh` already. Only
+/// errors when neither exists.
+pub fn resolve_ssh_key_path() -> Result<String, String> {
+    get_ssh_key_path().or_else(|_| discover_ssh_key_path())
+}
+
+/// Algorithm preference when more than one usable private key is found:
+/// lower is preferred. Matches the order modern `ssh`/most servers
+/// negotiate in anyway.
+fn key_type_rank(public_key_prefix: &str) -> u8 {
+    if public_key_prefix.starts_with("ssh-ed25519") {
+        0
+    } else if public_key_prefix.starts_with("ecdsa-sha2-") {
+        1
+    } else if public_key_prefix.starts_with("ssh-rsa") {
+        2
+    } else {
+        3
+    }
+}
+
+/// Scans `get_ssh_directory()` for usable private keys - any file with a
+/// matching `.pub` sibling, other than `known_hosts`/`authorized_keys`/
+/// `config`/dotfiles (mirrors `list_ssh_keys::list_available_ssh_keys`'s
+/// custom-key scan) - and returns the best one: the Ariana-managed key if
+/// present, else ranked ed25519 > ecdsa > rsa > anything else. Only errors
+/// when the directory has no private keys at all.
+pub fn discover_ssh_key_path() -> Result<String, String> {
+    let ssh_dir = get_ssh_directory()?;
+
+    if !ssh_dir.exists() {
+        return Err("No SSH keys found: ~/.ssh does not exist".to_string());
+    }
+
+    let entries = fs::read_dir(&ssh_dir).map_err(|e| format!("Failed to read SSH directory: {}", e))?;
+
+    let mut best: Option<(u8, PathBuf)> = None;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if !path.is_file() || path.extension().is_some_and(|ext| ext == "pub") {
+            continue;
+        }
+
+        let Some(file_name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+
+        if file_name == "known_hosts"
+            || file_name == "authorized_keys"
+            || file_name == "config"
+            || file_name.starts_with('.')
+        {
+            continue;
+        }
+
+        let pub_path = ssh_dir.join(format!("{}.pub", file_name));
+        if !pub_path.exists() {
+            continue;
+        }
+
+        let rank = if file_name == "ariana_id_ed25519" {
+            0
+        } else {
+            let public_key = fs::read_to_string(&pub_path).unwrap_or_default();
+            1 + key_type_rank(public_key.trim())
+        };
+
+        let is_better = match &best {
+            Some((best_rank, _)) => rank < *best_rank,
+            None => true,
+        };
+        if is_better {
+            best = Some((rank, path));
+        }
+    }
+
+    best.map(|(_, path)| path.to_string_lossy().to_string())
+        .ok_or_else(|| "No SSH private keys found in ~/.ssh".to_string())
+}
+
 /// Get the SSH directory path
 pub fn get_ssh_directory() -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir()
@@ -22,36 +111,92 @@ pub fn get_ssh_directory() -> Result<PathBuf, String> {
     Ok(home_dir.join(".ssh"))
 }
 
-/// Find SSH executable on the system
-pub fn find_ssh_executable() -> Result<String, String> {
+/// Find an SSH-capable executable on the system, alongside which kind it
+/// is. Non-Windows platforms are assumed to have real OpenSSH (true of
+/// every supported Unix target); Windows is the one where PuTTY/plink are
+/// common enough to need detecting (see `ssh_program::find_windows_ssh`).
+pub fn find_ssh_executable() -> Result<(SshProgramKind, String), String> {
     if cfg!(target_os = "windows") {
-        find_windows_ssh()
+        crate::ssh_program::find_windows_ssh()
     } else {
-        Ok("ssh".to_string())
+        Ok((SshProgramKind::OpenSsh, "ssh".to_string()))
     }
 }
 
-/// Find SSH executable on Windows in common locations
-fn find_windows_ssh() -> Result<String, String> {
-    let possible_paths = vec![
-        "C:\\Windows\\System32\\OpenSSH\\ssh.exe",
-        "C:\\Program Files\\Git\\usr\\bin\\ssh.exe",
-    ];
+/// Common SSH options for non-interactive connections. Verifies `host`'s
+/// presented key against known_hosts (see `host_key_verification`) before
+/// returning, then points `ssh` at the Ariana-managed known_hosts file with
+/// strict checking enabled - this used to unconditionally disable host key
+/// verification, which left every Ariana SSH connection open to a MITM
+/// silently swapping out the remote end.
+///
+/// Also carries forward whatever `Port`/`ProxyJump`/`ProxyCommand` the user
+/// already has configured for `host` in `~/.ssh/config` (see
+/// `ssh_client_config`), so a host that's only reachable through a jump
+/// host, or on a nonstandard port, keeps working instead of silently
+/// dropping those settings.
+///
+/// `kind` matters because only real OpenSSH understands any of this as
+/// `-o key=value`/`-J` - the PuTTY family (see `ssh_program`) has no
+/// equivalent command-line surface, so for those kinds this only adds
+/// batch mode and the port flag. `ensure_host_key_verified` itself is also
+/// skipped for them: it shells out to `ssh -G`/`ssh-keyscan`, both
+/// OpenSSH-only binaries that don't exist on a PuTTY/Plink-only box, so
+/// calling it unconditionally would fail every SSH call site on exactly
+/// the machines `SshProgramKind` exists to support. PuTTY/Plink keep their
+/// own separate host-key cache in the Windows registry, which this
+/// doesn't touch either way.
+pub fn get_common_ssh_options(host: &str, kind: SshProgramKind) -> Result<Vec<String>, String> {
+    if kind.supports_openssh_options() {
+        ensure_host_key_verified(host)?;
+    }
+
+    let mut options = kind.batch_mode_args();
+
+    if kind.supports_openssh_options() {
+        let known_hosts_path = ariana_known_hosts_path()?;
+        options.push("-o".to_string());
+        options.push("StrictHostKeyChecking=yes".to_string());
+        options.push("-o".to_string());
+        options.push(format!("UserKnownHostsFile={}", known_hosts_path.to_string_lossy()));
+    }
 
-    for path in possible_paths {
-        if PathBuf::from(path).exists() {
-            return Ok(path.to_string());
+    let resolved = SshClientConfig::load()?.resolve(host);
+
+    if let Some(port) = resolved.port {
+        options.push(kind.port_flag().to_string());
+        options.push(port.to_string());
+    }
+
+    if kind.supports_openssh_options() {
+        match (resolved.proxy_jump, resolved.proxy_command) {
+            (Some(proxy_jump), _) => {
+                options.push("-J".to_string());
+                options.push(proxy_jump);
+            }
+            (None, Some(proxy_command)) => {
+                options.push("-o".to_string());
+                options.push(format!("ProxyCommand={}", proxy_command));
+            }
+            (None, None) => {}
         }
     }
 
-    // Fallback: hope it's in PATH
-    Ok("ssh".to_string())
+    Ok(options)
 }
 
-/// Common SSH options for non-interactive connections
-pub fn get_common_ssh_options() -> Vec<&'static str> {
-    vec![
-        "-o", "StrictHostKeyChecking=no",
-        "-o", "UserKnownHostsFile=/dev/null",
-    ]
+/// The identity-file flag pair to pass for `host`: the `IdentityFile` the
+/// user already has configured for it in `~/.ssh/config`, if any,
+/// otherwise `fallback_identity` (typically `resolve_ssh_key_path()`'s
+/// result, or whichever key the caller already resolved another way).
+/// Letting a configured `IdentityFile` win means a host people reach
+/// through a different key than Ariana's managed one keeps doing so,
+/// instead of the hard-coded key silently taking over.
+pub fn ssh_identity_args(host: &str, fallback_identity: &str, kind: SshProgramKind) -> Result<Vec<String>, String> {
+    let identity = SshClientConfig::load()?
+        .resolve(host)
+        .identity_file
+        .unwrap_or_else(|| fallback_identity.to_string());
+
+    Ok(vec![kind.identity_flag().to_string(), identity])
 }
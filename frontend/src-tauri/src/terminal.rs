@@ -1,24 +1,53 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
 use std::io::{Read, Write};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
 use anyhow::{anyhow, Result};
 use portable_pty::{Child, CommandBuilder, PtyPair, PtySize};
+use serde::Serialize;
 use tauri::AppHandle;
 use tauri::Emitter;
 use uuid::Uuid;
 
+/// Cap on buffered scrollback per connection, so a long-running shell that's
+/// spammed with output for hours doesn't grow this unboundedly.
+const SCROLLBACK_CAP_BYTES: usize = 1024 * 1024;
 
 pub struct TerminalConnection {
 	pub id: String,
 	pub pty_pair: PtyPair,
 	pub child: Box<dyn Child + Send + Sync>,
 	pub app_handle: AppHandle,
+	/// Ring buffer of the last `SCROLLBACK_CAP_BYTES` of PTY output, appended
+	/// to by the IO thread alongside emitting it live. Lets `reattach` replay
+	/// history to a frontend that just (re)connected instead of that output
+	/// being lost forever once emitted with no listener around to see it.
+	pub scrollback: Arc<Mutex<VecDeque<u8>>>,
+	/// Opt-in transcript file the IO thread also tees output to, alongside
+	/// `scrollback` and the live frontend emit; `None` when recording isn't
+	/// active. Set/cleared by `start_recording`/`stop_recording`.
+	pub recording: Arc<Mutex<Option<File>>>,
+}
+
+/// A past (or currently recording) session transcript on disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptInfo {
+	pub path: String,
+	pub connection_id: String,
+	pub size_bytes: u64,
 }
 
 impl TerminalConnection {
-	pub fn new(id: String, working_directory: &str, app_handle: AppHandle) -> Result<Self> {
+	pub fn new(
+		id: String,
+		working_directory: &str,
+		target_user: Option<&str>,
+		env_overrides: &HashMap<String, String>,
+		app_handle: AppHandle,
+	) -> Result<Self> {
 		let pty_system = portable_pty::native_pty_system();
 
 		let pty_pair = pty_system.openpty(PtySize {
@@ -28,7 +57,7 @@ impl TerminalConnection {
 			pixel_height: 0,
 		})?;
 
-		let cmd = build_command(working_directory, true)?;
+		let cmd = build_command(working_directory, true, target_user, env_overrides)?;
 		let child = pty_pair.slave.spawn_command(cmd)?;
 
 		Ok(Self {
@@ -36,9 +65,42 @@ impl TerminalConnection {
 			pty_pair,
 			child,
 			app_handle,
+			scrollback: Arc::new(Mutex::new(VecDeque::new())),
+			recording: Arc::new(Mutex::new(None)),
 		})
 	}
 
+	/// Starts teeing this connection's PTY output to a timestamped file under
+	/// `directory`, seeded with whatever's already buffered in `scrollback`
+	/// so a recording started mid-session still has the preceding output.
+	/// Returns the transcript's full path.
+	pub fn start_recording(&self, directory: &str) -> Result<String> {
+		std::fs::create_dir_all(directory)?;
+
+		let timestamp = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+		let file_path = std::path::Path::new(directory).join(format!("terminal-{}-{}.log", self.id, timestamp));
+
+		let mut file = File::create(&file_path)?;
+		let buffered = {
+			let scrollback = self.scrollback.lock().unwrap();
+			scrollback.iter().copied().collect::<Vec<u8>>()
+		};
+		file.write_all(&buffered)?;
+
+		*self.recording.lock().unwrap() = Some(file);
+
+		Ok(file_path.to_string_lossy().to_string())
+	}
+
+	/// Stops teeing output to the transcript file started by `start_recording`,
+	/// if one is active. A no-op if recording was never started.
+	pub fn stop_recording(&self) {
+		*self.recording.lock().unwrap() = None;
+	}
+
 	pub fn is_alive(&mut self) -> bool {
 		match self.child.try_wait() {
 			Ok(Some(_)) => false, // Process has exited
@@ -51,6 +113,8 @@ impl TerminalConnection {
 		let mut reader = self.pty_pair.master.try_clone_reader()?;
 		let app_handle = self.app_handle.clone();
 		let connection_id = self.id.clone();
+		let scrollback = self.scrollback.clone();
+		let recording = self.recording.clone();
 
 		// Spawn thread to read from PTY and send to frontend
 		thread::spawn(move || {
@@ -59,16 +123,25 @@ impl TerminalConnection {
 				match reader.read(&mut buffer) {
 					Ok(0) => break, // EOF
 					Ok(n) => {
+						append_scrollback(&scrollback, &buffer[..n]);
+
+						if let Some(file) = recording.lock().unwrap().as_mut() {
+							let _ = file.write_all(&buffer[..n]);
+						}
+
 						let data = String::from_utf8_lossy(&buffer[..n]).to_string();
+						// No listener attached (tab closed, app reloaded) isn't
+						// fatal to the session - the shell keeps running and
+						// its output keeps landing in `scrollback` either way,
+						// ready for `reattach` once a listener shows back up.
 						if let Err(e) = app_handle
 							.emit(&format!("terminal-data-{}", connection_id), &data)
 						{
-							eprintln!("Failed to emit terminal data: {}", e);
-							break;
+							log::warn!(target: "terminal", "Failed to emit terminal data (no listener attached?): {}", e);
 						}
 					}
 					Err(e) => {
-						eprintln!("Error reading from PTY: {}", e);
+						log::error!(target: "terminal", "Error reading from PTY: {}", e);
 						break;
 					}
 				}
@@ -82,6 +155,21 @@ impl TerminalConnection {
 		Ok(())
 	}
 
+	/// Replays buffered scrollback to the frontend in one emit, for a
+	/// frontend that just (re)attached to this connection after missing some
+	/// or all of its live output.
+	pub fn reattach(&self) -> Result<()> {
+		let data = {
+			let scrollback = self.scrollback.lock().unwrap();
+			String::from_utf8_lossy(&scrollback.iter().copied().collect::<Vec<u8>>()).to_string()
+		};
+
+		self.app_handle
+			.emit(&format!("terminal-data-{}", self.id), &data)?;
+
+		Ok(())
+	}
+
 	pub fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
 		self.pty_pair.master.resize(PtySize {
 			rows,
@@ -93,6 +181,17 @@ impl TerminalConnection {
 	}
 }
 
+/// Appends `data` to `scrollback`, trimming from the front so the buffer
+/// never grows past `SCROLLBACK_CAP_BYTES`.
+fn append_scrollback(scrollback: &Arc<Mutex<VecDeque<u8>>>, data: &[u8]) {
+	let mut scrollback = scrollback.lock().unwrap();
+	scrollback.extend(data.iter().copied());
+	let overflow = scrollback.len().saturating_sub(SCROLLBACK_CAP_BYTES);
+	if overflow > 0 {
+		scrollback.drain(..overflow);
+	}
+}
+
 pub struct TerminalManager {
 	connections: Arc<Mutex<HashMap<String, TerminalConnection>>>,
 	writers: Arc<Mutex<HashMap<String, Box<dyn Write + Send>>>>,
@@ -111,6 +210,8 @@ impl TerminalManager {
 	pub fn create_connection(
 		&self,
 		working_directory: &str,
+		target_user: Option<&str>,
+		env_overrides: &HashMap<String, String>,
 		app_handle: AppHandle,
 	) -> Result<String> {
 		// Check connection limit first
@@ -122,8 +223,13 @@ impl TerminalManager {
 		}
 
 		let connection_id = Uuid::new_v4().to_string();
-		let connection =
-			TerminalConnection::new(connection_id.clone(), working_directory, app_handle)?;
+		let connection = TerminalConnection::new(
+			connection_id.clone(),
+			working_directory,
+			target_user,
+			env_overrides,
+			app_handle,
+		)?;
 
 		// Get the writer before starting the IO loop
 		let writer = connection.pty_pair.master.take_writer()?;
@@ -140,7 +246,7 @@ impl TerminalManager {
 	}
 
 	pub fn send_data(&self, connection_id: &str, data: &str) -> Result<()> {
-		println!("Backend sending data: {:?}", data);
+		log::debug!(target: "terminal", "Backend sending data: {:?}", data);
 		let mut writers = self.writers.lock().unwrap();
 		if let Some(writer) = writers.get_mut(connection_id) {
 			writer.write_all(data.as_bytes())?;
@@ -166,8 +272,42 @@ impl TerminalManager {
 		Ok(())
 	}
 
+	/// Replays a connection's buffered scrollback to the frontend, for a
+	/// terminal tab that was closed and reopened (or an app reload) to pick
+	/// its still-running shell back up with history intact.
+	pub fn reattach(&self, connection_id: &str) -> Result<()> {
+		let connections = self.connections.lock().unwrap();
+		if let Some(connection) = connections.get(connection_id) {
+			connection.reattach()?;
+		} else {
+			return Err(anyhow!("Connection not found: {}", connection_id));
+		}
+		Ok(())
+	}
+
+	/// Starts recording `connection_id`'s session to a timestamped transcript
+	/// file under `directory`. Returns the transcript's full path.
+	pub fn start_recording(&self, connection_id: &str, directory: &str) -> Result<String> {
+		let connections = self.connections.lock().unwrap();
+		let connection = connections
+			.get(connection_id)
+			.ok_or_else(|| anyhow!("Connection not found: {}", connection_id))?;
+		connection.start_recording(directory)
+	}
+
+	/// Stops a recording started by `start_recording`, leaving the transcript
+	/// file on disk with whatever was captured so far.
+	pub fn stop_recording(&self, connection_id: &str) -> Result<()> {
+		let connections = self.connections.lock().unwrap();
+		let connection = connections
+			.get(connection_id)
+			.ok_or_else(|| anyhow!("Connection not found: {}", connection_id))?;
+		connection.stop_recording();
+		Ok(())
+	}
+
 	pub fn close_connection(&self, connection_id: &str) -> Result<()> {
-		println!("Closing connection: {}", connection_id);
+		log::info!(target: "terminal", "Closing connection: {}", connection_id);
 		let mut connections = self.connections.lock().unwrap();
 		let mut writers = self.writers.lock().unwrap();
 
@@ -180,7 +320,7 @@ impl TerminalManager {
 		if let Some(mut connection) = connections.remove(connection_id) {
 			// Forcefully kill the child process
 			if let Err(e) = connection.child.kill() {
-				eprintln!("Failed to kill child process: {}", e);
+				log::warn!(target: "terminal", "Failed to kill child process: {}", e);
 			}
 
 			// Wait for the child to actually terminate
@@ -207,7 +347,7 @@ impl TerminalManager {
 
 		// Remove dead connections
 		for id in dead_connections {
-			println!("Cleaning up dead terminal connection: {}", id);
+			log::info!(target: "terminal", "Cleaning up dead terminal connection: {}", id);
 
 			// Cleanup writer
 			if let Some(mut writer) = writers.remove(&id) {
@@ -226,31 +366,114 @@ impl TerminalManager {
 }
 
 
-pub fn build_command(working_directory: &str, xterm: bool) -> Result<CommandBuilder> {
+/// Lists transcript files under `directory` (as written by
+/// `TerminalConnection::start_recording`), newest first.
+pub fn list_transcripts(directory: &str) -> Result<Vec<TranscriptInfo>> {
+	let mut transcripts = Vec::new();
+
+	let read_dir = match std::fs::read_dir(directory) {
+		Ok(read_dir) => read_dir,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e.into()),
+	};
+
+	for entry in read_dir {
+		let entry = entry?;
+		let path = entry.path();
+
+		let Some(file_name) = path.file_stem().and_then(|s| s.to_str()) else {
+			continue;
+		};
+		if path.extension().and_then(|e| e.to_str()) != Some("log") {
+			continue;
+		}
+		// Matches the "terminal-{connection_id}-{timestamp}" stem
+		// `start_recording` writes; connection_id itself is a UUID, so split
+		// off the trailing timestamp segment rather than the leading prefix.
+		let Some(rest) = file_name.strip_prefix("terminal-") else {
+			continue;
+		};
+		let Some((connection_id, timestamp)) = rest.rsplit_once('-') else {
+			continue;
+		};
+
+		transcripts.push((
+			timestamp.parse::<u64>().unwrap_or(0),
+			TranscriptInfo {
+				path: path.to_string_lossy().to_string(),
+				connection_id: connection_id.to_string(),
+				size_bytes: entry.metadata()?.len(),
+			},
+		));
+	}
+
+	transcripts.sort_by(|a, b| b.0.cmp(&a.0));
+	Ok(transcripts.into_iter().map(|(_, info)| info).collect())
+}
+
+/// Reads a transcript file in full. Transcripts are raw PTY bytes (including
+/// ANSI escape sequences), so this is lossy-decoded the same way live output
+/// already is before being emitted to the frontend.
+pub fn read_transcript(path: &str) -> Result<String> {
+	let bytes = std::fs::read(path)?;
+	Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+pub fn build_command(
+	working_directory: &str,
+	xterm: bool,
+	target_user: Option<&str>,
+	env_overrides: &HashMap<String, String>,
+) -> Result<CommandBuilder> {
 	let mut cmd = {
 		#[cfg(any(target_os = "macos", target_os = "linux"))]
 		{
-			// Try to get default shell from environment
-			let shell_path = std::env::var("SHELL").unwrap_or_else(|_| {
-				// Fallback priority: zsh (macOS default) -> bash -> sh
-				if std::path::Path::new("/bin/zsh").exists() {
-					"/bin/zsh".to_string()
-				} else if std::path::Path::new("/bin/bash").exists() {
-					"/bin/bash".to_string()
-				} else {
-					"/bin/sh".to_string()
-				}
-			});
+			// Prefer the shell recorded in the password database for the
+			// intended user - `$SHELL` reflects the spawning process's
+			// environment, which is wrong once we're spawning on behalf of a
+			// different (e.g. agent) user whose `$SHELL` was never set here.
+			let identity = resolve_unix_identity(target_user);
+
+			let shell_path = identity
+				.as_ref()
+				.map(|identity| identity.shell.clone())
+				.filter(|shell| !shell.is_empty())
+				.or_else(|| std::env::var("SHELL").ok())
+				.unwrap_or_else(|| {
+					// Fallback priority: zsh (macOS default) -> bash -> sh
+					if std::path::Path::new("/bin/zsh").exists() {
+						"/bin/zsh".to_string()
+					} else if std::path::Path::new("/bin/bash").exists() {
+						"/bin/bash".to_string()
+					} else {
+						"/bin/sh".to_string()
+					}
+				});
 
 			let mut cmd = CommandBuilder::new(shell_path);
 			cmd.arg("-l"); // Login shell
 
 			cmd.cwd(working_directory);
 
+			// Only actually switch identity when a target user was
+			// requested; otherwise `identity` is just our own passwd entry,
+			// looked up purely to read `pw_shell`.
+			if target_user.is_some() {
+				if let Some(identity) = &identity {
+					cmd.uid(identity.uid);
+					cmd.gid(identity.gid);
+					if !identity.groups.is_empty() {
+						cmd.groups(&identity.groups);
+					}
+				}
+			}
+
 			cmd
 		}
 		#[cfg(target_os = "windows")]
 		{
+			let _ = target_user;
+
 			// Use git bash if available
 			let git_bash_paths = [
 				"C:\\Program Files\\Git\\bin\\bash.exe",
@@ -290,5 +513,114 @@ pub fn build_command(working_directory: &str, xterm: bool) -> Result<CommandBuil
 		cmd.env("TERM_PROGRAM_VERSION", "3.0.0");
 	}
 
+	// Derived platform/arch/OS-family vars plus any caller-supplied
+	// overrides, so scripts launched in the terminal can branch on the
+	// runtime environment the same way a spawned build hook can branch on
+	// Tauri's `TAURI_*`/target-triple env.
+	for (key, value) in crate::command_utils::derived_runtime_env() {
+		cmd.env(key, value);
+	}
+	for (key, value) in env_overrides {
+		cmd.env(key, value);
+	}
+
 	Ok(cmd)
+}
+
+/// Shell and POSIX identity (uid/gid/supplementary groups) resolved from the
+/// system password database for `target_user`, or for the current user when
+/// `target_user` is `None`.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+struct UnixIdentity {
+	shell: String,
+	uid: u32,
+	gid: u32,
+	groups: Vec<u32>,
+}
+
+/// Looks up `target_user` (or the current user) via `getpwnam_r`/`getpwuid_r`
+/// so we can run a shell with the identity the password database actually
+/// assigns it, instead of whatever `$SHELL`/uid happens to be set in the
+/// process that's spawning the terminal. Returns `None` on any lookup
+/// failure so callers can fall back to `$SHELL`.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn resolve_unix_identity(target_user: Option<&str>) -> Option<UnixIdentity> {
+	use std::ffi::{CStr, CString};
+	use std::mem::MaybeUninit;
+
+	const PWBUF_LEN: usize = 16 * 1024;
+	let mut buf = vec![0i8; PWBUF_LEN];
+	let mut pwd = MaybeUninit::<libc::passwd>::zeroed();
+	let mut result: *mut libc::passwd = std::ptr::null_mut();
+
+	let rc = match target_user {
+		Some(username) => {
+			let c_username = CString::new(username).ok()?;
+			unsafe {
+				libc::getpwnam_r(
+					c_username.as_ptr(),
+					pwd.as_mut_ptr(),
+					buf.as_mut_ptr(),
+					buf.len(),
+					&mut result,
+				)
+			}
+		}
+		None => unsafe {
+			libc::getpwuid_r(
+				libc::getuid(),
+				pwd.as_mut_ptr(),
+				buf.as_mut_ptr(),
+				buf.len(),
+				&mut result,
+			)
+		},
+	};
+
+	if rc != 0 || result.is_null() {
+		return None;
+	}
+
+	let pwd = unsafe { pwd.assume_init() };
+	let shell = unsafe { CStr::from_ptr(pwd.pw_shell) }
+		.to_string_lossy()
+		.into_owned();
+	let uid = pwd.pw_uid;
+	let gid = pwd.pw_gid;
+	let name = unsafe { CStr::from_ptr(pwd.pw_name) };
+
+	// `getgrouplist` wants the caller to probe the group count first with a
+	// best-guess buffer, then retry with one sized to fit.
+	let mut ngroups: libc::c_int = 16;
+	let mut groups = vec![0 as libc::gid_t; ngroups as usize];
+	let rc = unsafe {
+		libc::getgrouplist(
+			name.as_ptr(),
+			gid as libc::gid_t,
+			groups.as_mut_ptr(),
+			&mut ngroups,
+		)
+	};
+	if rc < 0 {
+		groups = vec![0 as libc::gid_t; ngroups as usize];
+		let rc = unsafe {
+			libc::getgrouplist(
+				name.as_ptr(),
+				gid as libc::gid_t,
+				groups.as_mut_ptr(),
+				&mut ngroups,
+			)
+		};
+		if rc < 0 {
+			groups.clear();
+		}
+	}
+	groups.truncate(ngroups.max(0) as usize);
+
+	Some(UnixIdentity {
+		shell,
+		uid,
+		gid,
+		groups: groups.into_iter().map(|g| g as u32).collect(),
+	})
 }
\ No newline at end of file
@@ -1,26 +1,35 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use tauri::State;
-use crate::terminal::TerminalManager;
+use crate::terminal::{self, TerminalManager, TranscriptInfo};
+use crate::to_clean_absolute_path;
 
 #[tauri::command]
 pub async fn create_terminal_connection(
+	working_directory: Option<String>,
+	env: Option<HashMap<String, String>>,
+	target_user: Option<String>,
 	terminal_manager: State<'_, Arc<TerminalManager>>,
 	app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
-    let working_directory = {
-        #[cfg(target_os = "windows")]
-        {
-            "C:\\Users\\"
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            "/home/"
-        }
-    };
+	let working_directory = match working_directory {
+		Some(path) => to_clean_absolute_path(&path),
+		None => {
+			#[cfg(target_os = "windows")]
+			{
+				"C:\\Users\\".to_string()
+			}
+			#[cfg(not(target_os = "windows"))]
+			{
+				"/home/".to_string()
+			}
+		}
+	};
+	let env = env.unwrap_or_default();
 
 	terminal_manager
-		.create_connection(&working_directory, app_handle)
+		.create_connection(&working_directory, target_user.as_deref(), &env, app_handle)
 		.map_err(|e| e.to_string())
 }
 
@@ -57,6 +66,16 @@ pub async fn close_terminal_connection(
 		.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn reattach_terminal_connection(
+	connection_id: String,
+	terminal_manager: State<'_, Arc<TerminalManager>>,
+) -> Result<(), String> {
+	terminal_manager
+		.reattach(&connection_id)
+		.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn cleanup_dead_connections(
 	terminal_manager: State<'_, Arc<TerminalManager>>,
@@ -64,4 +83,38 @@ pub async fn cleanup_dead_connections(
 	terminal_manager
 		.cleanup_dead_connections()
 		.map_err(|e| e.to_string())
+}
+
+/// Starts teeing `connection_id`'s session to a timestamped transcript file
+/// under `directory`, so a user can later attach it to a bug report. Returns
+/// the transcript's full path.
+#[tauri::command]
+pub async fn start_terminal_recording(
+	connection_id: String,
+	directory: String,
+	terminal_manager: State<'_, Arc<TerminalManager>>,
+) -> Result<String, String> {
+	terminal_manager
+		.start_recording(&connection_id, &directory)
+		.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn stop_terminal_recording(
+	connection_id: String,
+	terminal_manager: State<'_, Arc<TerminalManager>>,
+) -> Result<(), String> {
+	terminal_manager
+		.stop_recording(&connection_id)
+		.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_terminal_transcripts(directory: String) -> Result<Vec<TranscriptInfo>, String> {
+	terminal::list_transcripts(&directory).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn read_terminal_transcript(path: String) -> Result<String, String> {
+	terminal::read_transcript(&path).map_err(|e| e.to_string())
 }
\ No newline at end of file
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use crate::command_utils::new_command;
+use crate::ssh_utils::{find_ssh_executable, get_common_ssh_options, resolve_ssh_key_path, ssh_identity_args};
+
+/// Tracks which (SSH host alias, `TERM`) pairs already have a provisioned
+/// terminfo entry on the remote, so opening a second terminal on the same
+/// agent doesn't redo the `infocmp`/`tic` round trip every time.
+pub struct TerminfoCache {
+    provisioned: Mutex<HashSet<(String, String)>>,
+}
+
+impl TerminfoCache {
+    pub fn new() -> Self {
+        Self {
+            provisioned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Ensures `term` (e.g. `xterm-256color`) has a terminfo entry under
+    /// `~/.terminfo` on `host` - an SSH config alias, such as the one
+    /// returned by `SSHConfigManager::upsert_agent_entry`. If the remote is
+    /// missing the entry, compiles it from the local terminfo database and
+    /// transfers it over. No-op once `(host, term)` has been provisioned
+    /// once this session.
+    pub fn ensure_terminfo(&self, host: &str, term: &str) -> Result<(), String> {
+        let cache_key = (host.to_string(), term.to_string());
+
+        if self.is_cached(&cache_key)? {
+            return Ok(());
+        }
+
+        if remote_has_terminfo(host, term)? {
+            self.mark_provisioned(cache_key)?;
+            return Ok(());
+        }
+
+        provision_remote_terminfo(host, term)?;
+        self.mark_provisioned(cache_key)?;
+
+        Ok(())
+    }
+
+    fn is_cached(&self, cache_key: &(String, String)) -> Result<bool, String> {
+        let provisioned = self
+            .provisioned
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        Ok(provisioned.contains(cache_key))
+    }
+
+    fn mark_provisioned(&self, cache_key: (String, String)) -> Result<(), String> {
+        self.provisioned
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?
+            .insert(cache_key);
+        Ok(())
+    }
+}
+
+/// ncurses shards the terminfo database by the entry name's first
+/// character, e.g. `xterm-256color` lives at `x/xterm-256color`.
+fn first_letter_dir(term: &str) -> &str {
+    &term[..1.min(term.len())]
+}
+
+fn remote_has_terminfo(host: &str, term: &str) -> Result<bool, String> {
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    let remote_path = format!("~/.terminfo/{}/{}", first_letter_dir(term), term);
+    let test_cmd = format!("test -f {}", remote_path);
+
+    let status = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(test_cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to check remote terminfo entry for {}: {}", host, e))?;
+
+    Ok(status.success())
+}
+
+fn provision_remote_terminfo(host: &str, term: &str) -> Result<(), String> {
+    // Capture the local terminfo source with `infocmp` and compile it on
+    // the remote with `tic` reading that source from stdin, rather than
+    // `scp`-ing a compiled binary entry over directly - the compiled format
+    // isn't guaranteed portable across architectures/ncurses versions, but
+    // the source form `infocmp -x` produces is.
+    let infocmp_output = new_command("infocmp")
+        .arg("-x")
+        .arg(term)
+        .output()
+        .map_err(|e| format!("Failed to run infocmp for TERM={}: {}", term, e))?;
+
+    if !infocmp_output.status.success() {
+        return Err(format!(
+            "infocmp failed for TERM={}: {}",
+            term,
+            String::from_utf8_lossy(&infocmp_output.stderr)
+        ));
+    }
+
+    let (ssh_kind, ssh_cmd) = find_ssh_executable()?;
+    let ssh_key_path = resolve_ssh_key_path()?;
+    let identity_args = ssh_identity_args(host, &ssh_key_path, ssh_kind)?;
+    let common_opts = get_common_ssh_options(host, ssh_kind)?;
+
+    let remote_cmd = "mkdir -p ~/.terminfo && TERMINFO=$HOME/.terminfo tic -x -o ~/.terminfo -";
+
+    let mut child = new_command(&ssh_cmd)
+        .args(&identity_args)
+        .args(&common_opts)
+        .arg(host)
+        .arg(remote_cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn remote tic for TERM={}: {}", term, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open stdin to remote tic".to_string())?
+        .write_all(&infocmp_output.stdout)
+        .map_err(|e| format!("Failed to write terminfo source to remote tic: {}", e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for remote tic: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Remote tic failed for TERM={}: {}",
+            term,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn ensure_remote_terminfo(
+    host: String,
+    term: String,
+    terminfo_cache: tauri::State<'_, std::sync::Arc<TerminfoCache>>,
+) -> Result<(), String> {
+    terminfo_cache.ensure_terminfo(&host, &term)
+}
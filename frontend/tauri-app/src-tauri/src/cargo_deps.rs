@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+use crate::ignore::IgnoreMatcher;
+
+/// Subdirectory under a copy's destination root that relocated out-of-tree
+/// Cargo path dependencies are copied into.
+pub const EXTERNAL_DEPS_DIR: &str = "_external_deps";
+
+/// One `path = "..."` dependency discovered in a `Cargo.toml` under the
+/// copy's `source` that resolves to somewhere outside `source` - the main
+/// copy never reaches it, so the copied workspace would fail to build
+/// without it.
+#[derive(Debug, Clone)]
+pub struct ExternalPathDependency {
+    /// The manifest that referenced it, absolute and under `source`.
+    pub manifest_path: PathBuf,
+    /// The dependency's canonicalized absolute location, outside `source`.
+    pub external_path: PathBuf,
+    /// Stable location under the destination root it's relocated to, e.g.
+    /// `_external_deps/shared-3f21`.
+    pub relocated_relative: PathBuf,
+}
+
+/// Walks `source` for `Cargo.toml` files (skipping anything `matcher`
+/// ignores, same exclusions the main copy applies), parses each one's
+/// `[dependencies]`/`[dev-dependencies]`/`[build-dependencies]` and
+/// `[workspace.dependencies]` tables for `path = "..."` entries, and
+/// returns the ones that fall outside `source`. Dependencies shared by
+/// several manifests resolve to the same `relocated_relative`, so callers
+/// only need to copy each one once.
+pub fn find_external_path_dependencies(source: &Path, matcher: &IgnoreMatcher) -> Vec<ExternalPathDependency> {
+    let Ok(source_canonical) = source.canonicalize() else {
+        return Vec::new();
+    };
+
+    let mut manifests = Vec::new();
+    collect_manifests(source, matcher, &mut manifests);
+
+    let mut relocated_for: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut deps = Vec::new();
+
+    for manifest_path in manifests {
+        let Ok(contents) = fs::read_to_string(&manifest_path) else {
+            continue;
+        };
+        let Ok(document) = contents.parse::<DocumentMut>() else {
+            continue;
+        };
+        let Some(manifest_dir) = manifest_path.parent() else {
+            continue;
+        };
+
+        for raw_path in path_dependency_entries(&document) {
+            let candidate = manifest_dir.join(&raw_path);
+            let Ok(canonical) = candidate.canonicalize() else {
+                continue;
+            };
+            if canonical.starts_with(&source_canonical) {
+                continue; // already inside the copied tree
+            }
+
+            let relocated_relative = relocated_for
+                .entry(canonical.clone())
+                .or_insert_with(|| {
+                    let name = canonical
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "dep".to_string());
+                    Path::new(EXTERNAL_DEPS_DIR).join(format!("{}-{:04x}", name, fingerprint(&canonical)))
+                })
+                .clone();
+
+            deps.push(ExternalPathDependency {
+                manifest_path: manifest_path.clone(),
+                external_path: canonical,
+                relocated_relative,
+            });
+        }
+    }
+
+    deps
+}
+
+/// Rewrites every `path = "..."` entry in the copied manifest at
+/// `copied_manifest` that referenced `original_target` to point at
+/// `new_target` instead, using a path relative to the manifest's own
+/// directory - the only form Cargo accepts for `path` dependencies. A no-op
+/// if the main copy hasn't actually reached this manifest yet.
+pub fn rewrite_manifest_path(copied_manifest: &Path, original_target: &Path, new_target: &Path) -> Result<(), String> {
+    let contents = match fs::read_to_string(copied_manifest) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(()),
+    };
+
+    let mut document = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse manifest '{}': {}", copied_manifest.display(), e))?;
+
+    let Some(manifest_dir) = copied_manifest.parent() else {
+        return Ok(());
+    };
+
+    let replacement = relative_path(manifest_dir, new_target).to_string_lossy().replace('\\', "/");
+
+    let mut changed = false;
+    for table_name in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        if rewrite_paths_in_item(document.get_mut(table_name), manifest_dir, original_target, &replacement) {
+            changed = true;
+        }
+    }
+    if let Some(workspace) = document.get_mut("workspace") {
+        if rewrite_paths_in_item(workspace.get_mut("dependencies"), manifest_dir, original_target, &replacement) {
+            changed = true;
+        }
+    }
+
+    if changed {
+        fs::write(copied_manifest, document.to_string())
+            .map_err(|e| format!("Failed to write manifest '{}': {}", copied_manifest.display(), e))?;
+    }
+
+    Ok(())
+}
+
+fn path_dependency_entries(document: &DocumentMut) -> Vec<String> {
+    let mut paths = Vec::new();
+    for table_name in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        collect_paths_from_item(document.get(table_name), &mut paths);
+    }
+    if let Some(workspace) = document.get("workspace") {
+        collect_paths_from_item(workspace.get("dependencies"), &mut paths);
+    }
+    paths
+}
+
+fn collect_paths_from_item(item: Option<&Item>, paths: &mut Vec<String>) {
+    let Some(table) = item.and_then(|t| t.as_table_like()) else {
+        return;
+    };
+    for (_, value) in table.iter() {
+        if let Some(path) = value.as_table_like().and_then(|t| t.get("path")).and_then(|p| p.as_str()) {
+            paths.push(path.to_string());
+        }
+    }
+}
+
+fn rewrite_paths_in_item(item: Option<&mut Item>, manifest_dir: &Path, original_target: &Path, replacement: &str) -> bool {
+    let Some(table) = item.and_then(|t| t.as_table_like_mut()) else {
+        return false;
+    };
+
+    let mut changed = false;
+    for (_, value) in table.iter_mut() {
+        let Some(dep) = value.as_table_like_mut() else {
+            continue;
+        };
+        let Some(existing) = dep.get("path").and_then(|p| p.as_str()).map(|s| s.to_string()) else {
+            continue;
+        };
+        let candidate = manifest_dir.join(&existing);
+        let Ok(canonical) = candidate.canonicalize() else {
+            continue;
+        };
+        if canonical == *original_target {
+            dep.insert("path", toml_edit::value(replacement));
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn collect_manifests(dir: &Path, matcher: &IgnoreMatcher, manifests: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if matcher.is_ignored(&path, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            collect_manifests(&path, matcher, manifests);
+        } else if path.file_name().map(|n| n == "Cargo.toml").unwrap_or(false) {
+            manifests.push(path);
+        }
+    }
+}
+
+/// `to`, expressed relative to `from_dir` - the only form Cargo accepts for
+/// a manifest's `path` dependency field.
+fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let from_components: Vec<_> = from_dir.components().collect();
+    let to_components: Vec<_> = to.components().collect();
+
+    let common = from_components
+        .iter()
+        .zip(to_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..from_components.len() {
+        result.push("..");
+    }
+    for component in &to_components[common..] {
+        result.push(component.as_os_str());
+    }
+
+    result
+}
+
+/// Cheap FNV-1a fingerprint used to disambiguate relocated dependency
+/// directories that happen to share a final path component (e.g. two
+/// unrelated `../shared` crates).
+fn fingerprint(path: &Path) -> u32 {
+    let mut hash: u32 = 2166136261;
+    for byte in path.to_string_lossy().bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(16777619);
+    }
+    hash & 0xffff
+}
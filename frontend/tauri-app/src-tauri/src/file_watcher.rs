@@ -0,0 +1,253 @@
+use crate::os::OsSession;
+use notify::{Event, EventKind, ModifyKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// How long to wait after the last event touching a path before flushing it
+/// as a single `FsEvent` - coalesces editor save-then-rewrite bursts, same
+/// window `watch.rs`'s sync watcher already debounces on.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(75);
+
+/// One filesystem change surfaced by `start_project_watch`. `kind` is
+/// `"resync"` (instead of a path-specific change) when the watch backend
+/// dropped events and the caller should re-read `path` from scratch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsEvent {
+    pub watch_id: String,
+    pub path: String,
+    pub kind: String, // "create" | "modify" | "delete" | "rename" | "resync"
+}
+
+enum WatchHandle {
+    Native(Arc<AtomicBool>),
+    Remote(Child),
+}
+
+/// Tracks running project watches, keyed by a caller-visible watch id, so
+/// `stop_project_watch` can tear one down regardless of which backend
+/// (`notify` locally, `inotifywait` over WSL) is behind it.
+#[derive(Default)]
+pub struct FileWatchers {
+    handles: Mutex<HashMap<String, WatchHandle>>,
+}
+
+impl FileWatchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Starts watching `path` for create/modify/delete/rename events, emitting a
+/// `project-file-change` event per change (or a batch resync if the backend
+/// drops events). Returns a watch id to pass to `stop_project_watch`.
+pub fn start_project_watch(
+    path: String,
+    os_session: OsSession,
+    app_handle: AppHandle,
+    watchers: &FileWatchers,
+) -> Result<String, String> {
+    let watch_id = uuid::Uuid::new_v4().to_string();
+
+    let handle = match &os_session {
+        OsSession::Local(_) => {
+            let stop_flag = Arc::new(AtomicBool::new(false));
+            spawn_native_watch(watch_id.clone(), path, app_handle, stop_flag.clone());
+            WatchHandle::Native(stop_flag)
+        }
+        OsSession::Wsl(wsl_session) => {
+            let child = spawn_wsl_watch(&watch_id, &path, &wsl_session.distribution, app_handle)?;
+            WatchHandle::Remote(child)
+        }
+    };
+
+    watchers
+        .handles
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .insert(watch_id.clone(), handle);
+
+    Ok(watch_id)
+}
+
+/// Stops a watch started by `start_project_watch`.
+pub fn stop_project_watch(watch_id: &str, watchers: &FileWatchers) -> Result<(), String> {
+    let mut handles = watchers
+        .handles
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if let Some(handle) = handles.remove(watch_id) {
+        match handle {
+            WatchHandle::Native(stop_flag) => stop_flag.store(true, Ordering::SeqCst),
+            WatchHandle::Remote(mut child) => {
+                let _ = child.kill();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn emit_event(app_handle: &AppHandle, watch_id: &str, path: &str, kind: &str) {
+    let _ = app_handle.emit(
+        "project-file-change",
+        FsEvent {
+            watch_id: watch_id.to_string(),
+            path: path.to_string(),
+            kind: kind.to_string(),
+        },
+    );
+}
+
+fn event_kind_label(kind: &EventKind) -> &'static str {
+    match kind {
+        EventKind::Create(_) => "create",
+        EventKind::Remove(_) => "delete",
+        EventKind::Modify(ModifyKind::Name(_)) => "rename",
+        _ => "modify",
+    }
+}
+
+/// Native `notify` backend for local sessions, mirroring `watch.rs`'s
+/// debounced event loop but reporting the typed change kind to the
+/// frontend directly instead of mirroring it to a destination tree.
+fn spawn_native_watch(watch_id: String, path: String, app_handle: AppHandle, stop_flag: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        let watch_root = PathBuf::from(&path);
+
+        let (tx, rx) = channel::<notify::Result<Event>>();
+        let mut watcher = match notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        }) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                tracing::warn!(target: "file_watcher", %err, "failed to create filesystem watcher");
+                return;
+            }
+        };
+
+        if let Err(err) = watcher.watch(&watch_root, RecursiveMode::Recursive) {
+            tracing::warn!(target: "file_watcher", %err, path = %watch_root.display(), "failed to start watching");
+            return;
+        }
+
+        // Paths touched since they were last flushed, with the most recent
+        // event kind seen for them and the instant of that event.
+        let mut pending: HashMap<PathBuf, (Instant, &'static str)> = HashMap::new();
+
+        while !stop_flag.load(Ordering::SeqCst) {
+            match rx.recv_timeout(Duration::from_millis(20)) {
+                Ok(Ok(event)) => {
+                    let kind = event_kind_label(&event.kind);
+                    for changed_path in event.paths {
+                        pending.insert(changed_path, (Instant::now(), kind));
+                    }
+                }
+                Ok(Err(err)) => {
+                    // The watch backend dropped events (e.g. inotify queue
+                    // overflow) - rather than risk missing a change, tell
+                    // the caller to resync this whole path from scratch.
+                    tracing::warn!(target: "file_watcher", %err, "filesystem watch overflow, requesting resync");
+                    pending.clear();
+                    emit_event(&app_handle, &watch_id, &path, "resync");
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = Instant::now();
+            let ready: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, (touched, _))| now.duration_since(*touched) >= DEBOUNCE_WINDOW)
+                .map(|(changed_path, _)| changed_path.clone())
+                .collect();
+
+            for changed_path in ready {
+                let Some((_, kind)) = pending.remove(&changed_path) else {
+                    continue;
+                };
+                emit_event(&app_handle, &watch_id, &changed_path.to_string_lossy(), kind);
+            }
+        }
+
+        let _ = watcher.unwatch(&watch_root);
+    });
+}
+
+/// WSL backend: `OsSession` has no SSH variant (only `Local`/`Wsl`), so the
+/// long-lived watcher process whose line-oriented output gets parsed here
+/// runs inside WSL - the same `inotifywait -m -r` approach the SSH-backed
+/// `remote_fs_watch` command already uses in the other Tauri app here,
+/// just invoked through `wsl.exe` rather than `ssh`.
+#[cfg(target_os = "windows")]
+fn spawn_wsl_watch(
+    watch_id: &str,
+    path: &str,
+    distribution: &str,
+    app_handle: AppHandle,
+) -> Result<Child, String> {
+    let inotify_command = "inotifywait -m -r -e create,modify,delete,moved_to,moved_from --format '%e|%w%f'";
+
+    let mut child = {
+        use std::os::windows::process::CommandExt;
+        std::process::Command::new("wsl")
+            .args(["-d", distribution, "--cd", path, "--", "sh", "-c", inotify_command])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start WSL watch on {} ({}): {}", path, distribution, e))?
+    };
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to open stdout for WSL watch".to_string())?;
+
+    let watch_id = watch_id.to_string();
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            let mut parts = line.splitn(2, '|');
+            let Some(events) = parts.next() else { continue };
+            let Some(changed_path) = parts.next() else {
+                continue;
+            };
+
+            let kind = if events.contains("CREATE") {
+                "create"
+            } else if events.contains("DELETE") {
+                "delete"
+            } else if events.contains("MOVED_TO") || events.contains("MOVED_FROM") {
+                "rename"
+            } else {
+                "modify"
+            };
+
+            emit_event(&app_handle, &watch_id, changed_path, kind);
+        }
+    });
+
+    Ok(child)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn spawn_wsl_watch(
+    _watch_id: &str,
+    _path: &str,
+    _distribution: &str,
+    _app_handle: AppHandle,
+) -> Result<Child, String> {
+    Err("WSL is only supported on Windows".to_string())
+}
@@ -1,7 +1,45 @@
+use crate::cargo_deps;
 use crate::commands::CommandExecutor;
+use crate::ignore::{IgnoreMatcher, IgnoreSource};
 use crate::os::OsSession;
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Backup policy applied to a destination file a copy is about to
+/// overwrite, modeled on coreutils `install --backup`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum BackupMode {
+    #[default]
+    None,
+    /// Renames the existing file by appending `suffix` (coreutils defaults
+    /// this to `~`).
+    Simple { suffix: String },
+    /// Renames the existing file to `<name>.~N~`, incrementing `N` to the
+    /// next free index rather than clobbering a previous backup.
+    Numbered,
+}
+
+/// Controls how `FileSystemManager::copy_files_optimized` treats files that
+/// already exist at the destination, and whether source attributes (mode,
+/// owner/group where the platform supports it, mtime) are preserved.
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    pub preserve_attributes: bool,
+    pub backup: BackupMode,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        // Matches the copy's pre-existing behavior: rsync -a / robocopy's
+        // default /COPY:DAT already preserve attributes, and there was no
+        // backup step at all.
+        Self {
+            preserve_attributes: true,
+            backup: BackupMode::None,
+        }
+    }
+}
 
 /// Filesystem operations with OS session awareness
 pub struct FileSystemManager;
@@ -71,29 +109,245 @@ impl FileSystemManager {
     }
 
     pub fn copy_files_optimized(
-        source: &str, 
-        destination: &str, 
+        source: &str,
+        destination: &str,
         os_session: &OsSession,
-        exclude_git: bool
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+        options: &CopyOptions,
     ) -> Result<(), String> {
         match os_session {
             OsSession::Local(_) => {
-                Self::copy_files_optimized_local(source, destination, exclude_git)
+                Self::copy_files_optimized_local(source, destination, exclude_git, exclude_rules, options)?;
             }
             OsSession::Wsl(wsl_session) => {
                 // Check if both paths are Windows mount points (/mnt/c/, /mnt/d/, etc.)
                 // If so, use Windows native copy for maximum efficiency
                 if Self::is_windows_mount_path(source) && Self::is_windows_mount_path(destination) {
-                    Self::copy_files_windows_from_wsl_paths(source, destination, exclude_git, wsl_session)
+                    Self::copy_files_windows_from_wsl_paths(source, destination, exclude_git, exclude_rules, options, wsl_session)?;
                 } else {
-                    Self::copy_files_optimized_wsl(source, destination, &wsl_session.distribution, exclude_git)
+                    Self::copy_files_optimized_wsl(source, destination, &wsl_session.distribution, exclude_git, exclude_rules, options)?;
                 }
             }
         }
+
+        Self::copy_external_path_dependencies(source, destination, exclude_git, exclude_rules)
+    }
+
+    /// After the main copy, resolves any `path = "../shared"`-style Cargo
+    /// dependencies referenced under `source` that point outside of it (the
+    /// main copy never reaches them), copies each one into a stable
+    /// `_external_deps/...` location under `destination`, and rewrites the
+    /// copied manifests to point at it, leaving a `mapping.json` record so a
+    /// later incremental sync can find them again. Only reachable when
+    /// `source` is locally readable - same 9p-boundary limitation
+    /// `prepare_backups` already documents - a no-op otherwise.
+    fn copy_external_path_dependencies(
+        source: &str,
+        destination: &str,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+    ) -> Result<(), String> {
+        let src_path = Path::new(source);
+        if !src_path.is_dir() {
+            return Ok(());
+        }
+
+        let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Local(source.to_string()));
+        if !contains_cargo_projects {
+            return Ok(());
+        }
+
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        let deps = cargo_deps::find_external_path_dependencies(src_path, &matcher);
+        if deps.is_empty() {
+            return Ok(());
+        }
+
+        let dest_path = Path::new(destination);
+        let mut copied: HashSet<PathBuf> = HashSet::new();
+        let mut recorded: HashSet<PathBuf> = HashSet::new();
+        let mut mapping = Vec::new();
+
+        for dep in &deps {
+            let relocated_dest = dest_path.join(&dep.relocated_relative);
+
+            if copied.insert(dep.relocated_relative.clone()) && !relocated_dest.exists() {
+                let dep_contains_cargo_projects = Self::contains_cargo_projects(
+                    &dep.external_path.to_string_lossy(),
+                    &OsSession::Local(dep.external_path.to_string_lossy().to_string()),
+                );
+                let dep_matcher = Self::build_matcher(
+                    &dep.external_path.to_string_lossy(),
+                    exclude_git,
+                    dep_contains_cargo_projects,
+                    exclude_rules,
+                );
+                Self::copy_tree_with_matcher(&dep.external_path, &relocated_dest, &dep_matcher)?;
+            }
+
+            // Record the mapping for every distinct dependency regardless of
+            // whether it was copied just now or already existed from a
+            // previous sync, so `mapping.json` stays a complete record.
+            if recorded.insert(dep.relocated_relative.clone()) {
+                mapping.push(serde_json::json!({
+                    "originalPath": dep.external_path.to_string_lossy(),
+                    "relocatedPath": relocated_dest.to_string_lossy(),
+                }));
+            }
+
+            let Ok(relative_manifest) = dep.manifest_path.strip_prefix(src_path) else {
+                continue;
+            };
+            let copied_manifest = dest_path.join(relative_manifest);
+            cargo_deps::rewrite_manifest_path(&copied_manifest, &dep.external_path, &relocated_dest)?;
+        }
+
+        let mapping_path = dest_path.join(cargo_deps::EXTERNAL_DEPS_DIR).join("mapping.json");
+        if let Some(parent) = mapping_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+        }
+        fs::write(&mapping_path, serde_json::to_string_pretty(&mapping).unwrap_or_default())
+            .map_err(|e| format!("Failed to write external dependency mapping '{}': {}", mapping_path.display(), e))?;
+
+        Ok(())
+    }
+
+    /// Recursively copies `source` to `destination`, skipping anything
+    /// `matcher` ignores - used to bring in external Cargo path
+    /// dependencies that live outside the main copy's source root.
+    fn copy_tree_with_matcher(source: &Path, destination: &Path, matcher: &IgnoreMatcher) -> Result<(), String> {
+        if source.is_file() {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+            }
+            return fs::copy(source, destination)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to copy '{}': {}", source.display(), e));
+        }
+
+        fs::create_dir_all(destination)
+            .map_err(|e| format!("Failed to create directory '{}': {}", destination.display(), e))?;
+
+        let entries = fs::read_dir(source)
+            .map_err(|e| format!("Failed to read directory '{}': {}", source.display(), e))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            let entry_path = entry.path();
+            let is_dir = entry_path.is_dir();
+            if matcher.is_ignored(&entry_path, is_dir) {
+                continue;
+            }
+            let Some(name) = entry_path.file_name() else {
+                continue;
+            };
+            Self::copy_tree_with_matcher(&entry_path, &destination.join(name), matcher)?;
+        }
+
+        Ok(())
+    }
+
+    /// Walks `source` (skipping anything `matcher` ignores) looking for
+    /// files that already exist at the mirrored path under `destination`,
+    /// and backs each one up per `backup` before the real copy can
+    /// overwrite it. No-op for `BackupMode::None` or when `destination`
+    /// doesn't exist yet (nothing can be overwritten).
+    fn prepare_backups(source: &Path, destination: &Path, matcher: &IgnoreMatcher, backup: &BackupMode) -> Result<(), String> {
+        if *backup == BackupMode::None || !destination.exists() {
+            return Ok(());
+        }
+
+        let entries = match fs::read_dir(source) {
+            Ok(entries) => entries,
+            // Source isn't locally readable from here (e.g. a pure-WSL path
+            // seen from the Windows host) - nothing we can proactively back up.
+            Err(_) => return Ok(()),
+        };
+
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            let is_dir = entry_path.is_dir();
+            if matcher.is_ignored(&entry_path, is_dir) {
+                continue;
+            }
+
+            let Ok(relative) = entry_path.strip_prefix(source) else {
+                continue;
+            };
+            let dest_path = destination.join(relative);
+
+            if is_dir {
+                Self::prepare_backups(&entry_path, &dest_path, matcher, backup)?;
+            } else if dest_path.is_file() {
+                Self::apply_backup(&dest_path, backup)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renames a single existing destination file out of the way per
+    /// `backup`, so the upcoming copy writes a fresh file instead of
+    /// clobbering it.
+    fn apply_backup(dest_path: &Path, backup: &BackupMode) -> Result<(), String> {
+        let backup_path = match backup {
+            BackupMode::None => return Ok(()),
+            BackupMode::Simple { suffix } => {
+                let mut name = dest_path.as_os_str().to_os_string();
+                name.push(suffix);
+                PathBuf::from(name)
+            }
+            BackupMode::Numbered => {
+                let mut n: u32 = 1;
+                loop {
+                    let mut name = dest_path.as_os_str().to_os_string();
+                    name.push(format!(".~{}~", n));
+                    let candidate = PathBuf::from(name);
+                    if !candidate.exists() {
+                        break candidate;
+                    }
+                    n += 1;
+                }
+            }
+        };
+
+        fs::rename(dest_path, &backup_path)
+            .map_err(|e| format!("Failed to back up existing file '{}': {}", dest_path.display(), e))
+    }
+
+    /// Builds the effective `IgnoreMatcher` for a copy: folds the legacy
+    /// `exclude_git`/Cargo-`target` toggles in as synthetic inline rules
+    /// (lowest precedence, so a project's own `.gitignore` can still
+    /// re-include something under them) alongside any caller-supplied
+    /// `exclude_rules`, then walks `source` for `.gitignore`/`.ignore` files.
+    pub fn build_matcher(
+        source: &str,
+        exclude_git: bool,
+        contains_cargo_projects: bool,
+        exclude_rules: &[IgnoreSource],
+    ) -> IgnoreMatcher {
+        let mut builtins = Vec::new();
+        if exclude_git {
+            builtins.push(".git/".to_string());
+        }
+        if contains_cargo_projects {
+            builtins.push("target/".to_string());
+        }
+
+        let mut sources = Vec::with_capacity(exclude_rules.len() + 1);
+        if !builtins.is_empty() {
+            sources.push(IgnoreSource::Inline(builtins));
+        }
+        sources.extend(exclude_rules.iter().cloned());
+
+        IgnoreMatcher::build(Path::new(source), &sources)
     }
 
     /// Check if a directory contains any Cargo projects (anywhere in the tree)
-    fn contains_cargo_projects(directory: &str, os_session: &OsSession) -> bool {
+    pub fn contains_cargo_projects(directory: &str, os_session: &OsSession) -> bool {
         let result = match os_session {
             OsSession::Local(_) => {
                 // Use appropriate command based on OS
@@ -129,109 +383,89 @@ impl FileSystemManager {
             }
         };
         
-        println!("Cargo projects check: {} -> {}", directory, result);
+        tracing::debug!(target: "filesystem", directory, contains_cargo_projects = result, "cargo projects check");
         result
     }
 
-    fn copy_files_optimized_local(source: &str, destination: &str, exclude_git: bool) -> Result<(), String> {
+    fn copy_files_optimized_local(
+        source: &str,
+        destination: &str,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+        options: &CopyOptions,
+    ) -> Result<(), String> {
         let src_path = Path::new(source);
         if !src_path.exists() {
             return Err("Source path does not exist".to_string());
         }
-        
+
         // Check if this is a Cargo project to determine smart exclusions
         let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Local(source.to_string()));
-        
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        Self::prepare_backups(src_path, Path::new(destination), &matcher, &options.backup)?;
+
         #[cfg(target_os = "windows")]
         {
             use crate::system::SystemManager;
-            
+
             // Create destination directory if it doesn't exist
             if let Some(parent) = Path::new(&destination).parent() {
                 fs::create_dir_all(parent)
                     .map_err(|e| format!("Failed to create destination directory: {}", e))?;
             }
-            
+
             // Use PowerShell for better performance and progress tracking
             let mut ps_command = format!(
                 "$src = '{}'; $dst = '{}'; ",
                 source.replace("'", "''"),
                 destination.replace("'", "''")
             );
-            
-            let mut exclude_patterns = Vec::new();
-            if exclude_git {
-                exclude_patterns.push(".git");
-            }
-            if contains_cargo_projects {
-                exclude_patterns.push("target");
-            }
-            
+
+            let exclude_patterns = matcher.to_powershell_exclude_list();
+
             if exclude_patterns.is_empty() {
                 ps_command.push_str("Copy-Item -Path $src -Destination $dst -Recurse -Force");
             } else {
                 ps_command.push_str(&format!(
                     "Copy-Item -Path $src -Destination $dst -Recurse -Force -Exclude @({})",
-                    exclude_patterns.iter().map(|p| format!("'{}'", p)).collect::<Vec<_>>().join(",")
+                    exclude_patterns.iter().map(|p| format!("'{}'", p.replace('\'', "''"))).collect::<Vec<_>>().join(",")
                 ));
             }
-            
+
             SystemManager::execute_command("powershell", &["-Command", &ps_command]).map(|_| ())?
         }
-        
+
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         {
             use crate::system::SystemManager;
-            
-            // Use rsync for better performance
-            let mut args = vec!["-a", "--info=progress2"];
-            
-            if exclude_git {
-                args.push("--exclude=.git");
-            }
-            if contains_cargo_projects {
-                args.push("--exclude=target");
-            }
-            
+
+            let exclude_lines = matcher.to_rsync_exclude_lines();
+
+            // Use rsync, feeding the gitignore-derived rules in on stdin so
+            // negation/anchoring is preserved (a flat --exclude arg list
+            // can't express that). Backups were already applied above via
+            // prepare_backups, so rsync doesn't need its own --backup flags
+            // here (which can't express numbered backups anyway).
+            let archive_flag = if options.preserve_attributes { "-a" } else { "-r" };
             let source_with_slash = format!("{}/", source);
-            args.push(&source_with_slash);
-            args.push(destination);
-            
-            SystemManager::execute_command("rsync", &args).or_else(|_| {
-                // Fallback to manual copy if rsync is not available
-                // Standard cp doesn't support --exclude, so we need a different approach
-                if exclude_git || contains_cargo_projects {
-                    // Use tar with exclusions for better control
-                    let mut tar_args = vec!["-cf", "-"];
-                    if exclude_git {
-                        tar_args.extend(vec!["--exclude", ".git"]);
-                    }
-                    if contains_cargo_projects {
-                        tar_args.extend(vec!["--exclude", "target"]);
-                    }
-                    tar_args.push("-C");
-                    tar_args.push(source);
-                    tar_args.push(".");
-                    
+            let args = vec![archive_flag, "--info=progress2", "--exclude-from=-", &source_with_slash, destination];
+
+            SystemManager::execute_command_with_stdin("rsync", &args, &exclude_lines.join("\n")).or_else(|_| {
+                // Fallback to manual copy if rsync is not available. Standard
+                // cp doesn't support excludes, so fall back to tar, which at
+                // least handles the flattened (negation-less) pattern list.
+                let exclude_patterns = matcher.effective_exclude_patterns();
+                if !exclude_patterns.is_empty() {
+                    let exclude_flags = exclude_patterns
+                        .iter()
+                        .map(|p| format!("--exclude={}", p))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+
                     // Create destination directory
                     let _ = SystemManager::execute_command("mkdir", &["-p", destination]);
-                    
-                    // Use tar to copy with exclusions
-                    let tar_cmd = format!("cd '{}' && tar -cf - . ", source);
-                    let exclude_flags = if exclude_git || contains_cargo_projects {
-                        let mut flags = Vec::new();
-                        if exclude_git {
-                            flags.push("--exclude=.git");
-                        }
-                        if contains_cargo_projects {
-                            flags.push("--exclude=target");
-                        }
-                        flags.join(" ")
-                    } else {
-                        String::new()
-                    };
-                    
-                    SystemManager::execute_command("sh", &["-c", &format!("cd '{}' && tar -cf - {} . | (cd '{}' && tar -xf -)", 
+
+                    SystemManager::execute_command("sh", &["-c", &format!("cd '{}' && tar -cf - {} . | (cd '{}' && tar -xf -)",
                         source, exclude_flags, destination)])
                 } else {
                     // Simple cp without exclusions
@@ -239,60 +473,142 @@ impl FileSystemManager {
                 }
             }).map(|_| ())?
         }
-        
+
         Ok(())
     }
 
     #[cfg(target_os = "windows")]
-    fn copy_files_optimized_wsl(source: &str, destination: &str, distribution: &str, exclude_git: bool) -> Result<(), String> {
+    fn copy_files_optimized_wsl(
+        source: &str,
+        destination: &str,
+        distribution: &str,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+        options: &CopyOptions,
+    ) -> Result<(), String> {
         let wsl_session = crate::os::WslSession {
             distribution: distribution.to_string(),
             working_directory: "/".to_string(),
         };
-        
+
+        // Exactly one side mounted via 9p (the other native to the WSL
+        // filesystem) is the slow case rsync/cp struggle with - stream it
+        // through tar+xz instead. When neither side is a mount, the whole
+        // copy stays inside the WSL filesystem and rsync is already fast.
+        if Self::is_windows_mount_path(source) != Self::is_windows_mount_path(destination) {
+            // Note: the streamed tar pipeline always preserves attributes
+            // (that's just how tar works) and doesn't support the backup
+            // policy - `--backup` has no equivalent to insert mid-pipeline
+            // without abandoning the single-pass stream this path exists for.
+            return Self::copy_files_compressed(source, destination, distribution, exclude_git, exclude_rules);
+        }
+
         // Check if this is a Cargo project to determine smart exclusions
         let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Wsl(wsl_session.clone()));
-        
-        let mut args = vec!["-av", "--info=progress2"];
-        
-        if exclude_git {
-            args.push("--exclude=.git");
-        }
-        if contains_cargo_projects {
-            args.push("--exclude=target");
-        }
-        
+        // `source` lives inside the WSL filesystem, which this host process
+        // can't walk directly for .gitignore/.ignore files across the 9p
+        // boundary, so the matcher here only carries the built-in and
+        // caller-supplied rules (no tree walk), flattened into plain
+        // --exclude args since CommandExecutor has no stdin-piping support.
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        let exclude_patterns = matcher.effective_exclude_patterns();
+        let exclude_args: Vec<String> = exclude_patterns.iter().map(|p| format!("--exclude={}", p)).collect();
+
+        // rsync runs entirely inside the WSL shell here, so backups (unlike
+        // the local-fs path's prepare_backups) have to be its own --backup
+        // flags - rsync has no native numbered-backup support, so that mode
+        // degrades to a fixed ".~bak~" suffix in this cross-process path.
+        let backup_args: Vec<String> = match &options.backup {
+            BackupMode::None => Vec::new(),
+            BackupMode::Simple { suffix } => vec!["--backup".to_string(), format!("--suffix={}", suffix)],
+            BackupMode::Numbered => vec!["--backup".to_string(), "--suffix=.~bak~".to_string()],
+        };
+
+        let archive_flag = if options.preserve_attributes { "-av" } else { "-rv" };
+        let mut args = vec![archive_flag, "--info=progress2"];
+        args.extend(exclude_args.iter().map(|s| s.as_str()));
+        args.extend(backup_args.iter().map(|s| s.as_str()));
+
         let source_with_slash = format!("{}/", source);
         args.push(&source_with_slash);
         args.push(destination);
-        
+
         CommandExecutor::execute_with_os_session(
-            "rsync", 
+            "rsync",
             &args,
-            None, 
+            None,
             &OsSession::Wsl(wsl_session.clone())
         ).or_else(|_| {
             // Fallback to optimized cp
             let mut cp_args = vec!["-r"];
-            if exclude_git {
-                cp_args.push("--exclude=.git");
-            }
-            if contains_cargo_projects {
-                cp_args.push("--exclude=target");
-            }
+            cp_args.extend(exclude_args.iter().map(|s| s.as_str()));
             cp_args.push(source);
             cp_args.push(destination);
-            
+
             CommandExecutor::execute_with_os_session(
-                "cp", 
+                "cp",
                 &cp_args,
-                None, 
+                None,
                 &OsSession::Wsl(wsl_session)
             )
         }).map(|_| ())
     }
 
-    fn is_windows_mount_path(path: &str) -> bool {
+    /// Streams a copy across the 9p mount boundary (WSL-native <-> `/mnt/`
+    /// Windows drive) as a single `tar | xz | xz -d | tar` pipeline instead
+    /// of rsync/cp's per-file syscalls, which is what makes plain copies so
+    /// slow here. The xz step uses a large (64 MiB) dictionary with a
+    /// low-to-moderate preset, trading some ratio for bounded CPU cost,
+    /// since the goal is cutting bytes crossing the mount, not maximum
+    /// compression. Falls back to an uncompressed tar-to-tar pipe if `xz`
+    /// isn't installed.
+    #[cfg(target_os = "windows")]
+    fn copy_files_compressed(
+        source: &str,
+        destination: &str,
+        distribution: &str,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+    ) -> Result<(), String> {
+        let wsl_session = crate::os::WslSession {
+            distribution: distribution.to_string(),
+            working_directory: "/".to_string(),
+        };
+
+        let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Wsl(wsl_session.clone()));
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        let exclude_flags: String = matcher
+            .effective_exclude_patterns()
+            .iter()
+            .map(|p| format!(" --exclude={}", shell_quote(p)))
+            .collect();
+
+        let mkdir_cmd = format!("mkdir -p {}", shell_quote(destination));
+        CommandExecutor::execute_with_os_session("sh", &["-c", &mkdir_cmd], None, &OsSession::Wsl(wsl_session.clone()))?;
+
+        let xz_pipeline = format!(
+            "tar -cf -{} -C {} . | xz -T0 --lzma2=preset=3,dict=64MiB | xz -dc | tar -xf - -C {}",
+            exclude_flags,
+            shell_quote(source),
+            shell_quote(destination),
+        );
+
+        CommandExecutor::execute_with_os_session("sh", &["-c", &xz_pipeline], None, &OsSession::Wsl(wsl_session.clone()))
+            .or_else(|_| {
+                // xz not available (or some other pipeline failure) - fall
+                // back to an uncompressed but still streamed tar-to-tar pipe.
+                let tar_pipeline = format!(
+                    "tar -cf -{} -C {} . | tar -xf - -C {}",
+                    exclude_flags,
+                    shell_quote(source),
+                    shell_quote(destination),
+                );
+                CommandExecutor::execute_with_os_session("sh", &["-c", &tar_pipeline], None, &OsSession::Wsl(wsl_session))
+            })
+            .map(|_| ())
+    }
+
+    pub fn is_windows_mount_path(path: &str) -> bool {
         // Check if path starts with /mnt/[c-z]/ (Windows drive mount in WSL)
         if path.len() >= 6 && path.starts_with("/mnt/") {
             let drive_char = path.chars().nth(5);
@@ -304,26 +620,36 @@ impl FileSystemManager {
     }
 
     #[cfg(target_os = "windows")]
-    fn copy_files_windows_from_wsl_paths(source: &str, destination: &str, exclude_git: bool, wsl_session: &crate::os::WslSession) -> Result<(), String> {
+    fn copy_files_windows_from_wsl_paths(
+        source: &str,
+        destination: &str,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+        options: &CopyOptions,
+        wsl_session: &crate::os::WslSession,
+    ) -> Result<(), String> {
         use crate::system::SystemManager;
-        
+
         // Convert WSL mount paths to Windows paths
         let windows_source = Self::convert_wsl_mount_to_windows_path(source);
         let windows_dest = Self::convert_wsl_mount_to_windows_path(destination);
-        
-        println!("Optimized copy: WSL paths detected, using Windows native copy");
-        println!("  {} -> {}", windows_source, windows_dest);
-        
+
+        tracing::debug!(target: "filesystem", source = %windows_source, destination = %windows_dest, "optimized copy: WSL paths detected, using Windows native copy");
+
         // Check if this contains Cargo projects to determine smart exclusions
         // Use the original WSL path with WSL osSession to respect user's choice
         let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Wsl(wsl_session.clone()));
-        
+        // Windows mount paths are directly readable by this host process, so
+        // the matcher can walk the tree for .gitignore/.ignore files.
+        let matcher = Self::build_matcher(&windows_source, exclude_git, contains_cargo_projects, exclude_rules);
+
         // Create destination directory if it doesn't exist
         if let Some(parent) = std::path::Path::new(&windows_dest).parent() {
             fs::create_dir_all(parent)
                 .map_err(|e| format!("Failed to create destination directory: {}", e))?;
         }
-        
+        Self::prepare_backups(Path::new(&windows_source), Path::new(&windows_dest), &matcher, &options.backup)?;
+
         // Use robocopy for maximum efficiency (multi-threaded, optimized for Windows)
         let mut robocopy_args = vec![
             windows_source.clone(),
@@ -337,17 +663,23 @@ impl FileSystemManager {
             "/NDL".to_string(),    // No directory list
             "/NFL".to_string(),    // No file list
         ];
-        
-        if exclude_git {
-            robocopy_args.extend(vec!["/XD".to_string(), ".git".to_string()]);
+        // Default /COPY:DAT (Data, Attributes, Timestamps) already preserves
+        // mtime; ask for owner/security too when attribute preservation is on.
+        if options.preserve_attributes {
+            robocopy_args.push("/COPY:DATSOU".to_string());
         }
-        if contains_cargo_projects {
-            robocopy_args.extend(vec!["/XD".to_string(), "target".to_string()]);
+
+        let (xd_patterns, xf_patterns) = matcher.to_robocopy_excludes();
+        for pattern in &xd_patterns {
+            robocopy_args.extend(vec!["/XD".to_string(), pattern.clone()]);
         }
-        
+        for pattern in &xf_patterns {
+            robocopy_args.extend(vec!["/XF".to_string(), pattern.clone()]);
+        }
+
         let args_str: Vec<&str> = robocopy_args.iter().map(|s| s.as_str()).collect();
         let result = SystemManager::execute_command("robocopy", &args_str);
-        
+
         // Robocopy returns 0-7 for success, 8+ for errors
         match result {
             Ok(_) => Ok(()),
@@ -357,73 +689,86 @@ impl FileSystemManager {
                     Ok(())
                 } else {
                     // Fallback to PowerShell copy
-                    println!("Robocopy failed, falling back to PowerShell");
-                    Self::copy_files_powershell_fallback(&windows_source, &windows_dest, exclude_git, contains_cargo_projects)
+                    tracing::warn!(target: "filesystem", "robocopy failed, falling back to PowerShell");
+                    Self::copy_files_powershell_fallback(&windows_source, &windows_dest, &matcher)
                 }
             }
         }
     }
 
+    /// PowerShell has no built-in backup-on-overwrite, and this fallback
+    /// doesn't apply one itself: the numbered/simple rename logic has already
+    /// run in Rust (via `prepare_backups`) in
+    /// `copy_files_windows_from_wsl_paths`, before robocopy was tried.
     #[cfg(target_os = "windows")]
-    fn copy_files_powershell_fallback(source: &str, destination: &str, exclude_git: bool, contains_cargo_projects: bool) -> Result<(), String> {
+    fn copy_files_powershell_fallback(source: &str, destination: &str, matcher: &IgnoreMatcher) -> Result<(), String> {
         use crate::system::SystemManager;
-        
+
         let mut ps_command = format!(
             "$src = '{}'; $dst = '{}'; ",
             source.replace("'", "''"),
             destination.replace("'", "''")
         );
-        
-        let mut exclude_patterns = Vec::new();
-        if exclude_git {
-            exclude_patterns.push(".git");
-        }
-        if contains_cargo_projects {
-            exclude_patterns.push("target");
-        }
-        
+
+        let exclude_patterns = matcher.to_powershell_exclude_list();
+
         if exclude_patterns.is_empty() {
             ps_command.push_str("Copy-Item -Path $src -Destination $dst -Recurse -Force");
         } else {
             ps_command.push_str(&format!(
                 "Copy-Item -Path $src -Destination $dst -Recurse -Force -Exclude @({})",
-                exclude_patterns.iter().map(|p| format!("'{}'", p)).collect::<Vec<_>>().join(",")
+                exclude_patterns.iter().map(|p| format!("'{}'", p.replace('\'', "''"))).collect::<Vec<_>>().join(",")
             ));
         }
-        
+
         SystemManager::execute_command("powershell", &["-Command", &ps_command])
             .map(|_| ())
             .map_err(|e| format!("PowerShell copy failed: {}", e))
     }
 
+    /// Thin wrapper around `path_translation::wsl_to_windows` for callers that
+    /// already know `wsl_path` is a Windows-mounted path (so the distribution
+    /// name, only needed for the non-mount UNC fallback, is irrelevant here).
     #[cfg(target_os = "windows")]
-    fn convert_wsl_mount_to_windows_path(wsl_path: &str) -> String {
-        // Convert /mnt/c/path/to/file to C:\path\to\file
-        if wsl_path.len() >= 6 && wsl_path.starts_with("/mnt/") {
-            let drive_char = wsl_path.chars().nth(5).unwrap().to_ascii_uppercase();
-            let rest_of_path = &wsl_path[6..]; // Skip "/mnt/c"
-            let windows_path = rest_of_path.replace('/', "\\");
-            format!("{}:{}", drive_char, windows_path)
-        } else {
-            wsl_path.to_string()
-        }
+    pub fn convert_wsl_mount_to_windows_path(wsl_path: &str) -> String {
+        crate::path_translation::wsl_to_windows(wsl_path, "").to_string_lossy().to_string()
     }
 
     #[cfg(not(target_os = "windows"))]
-    fn copy_files_windows_from_wsl_paths(_source: &str, _destination: &str, _exclude_git: bool, _wsl_session: &crate::os::WslSession) -> Result<(), String> {
+    fn copy_files_windows_from_wsl_paths(
+        _source: &str,
+        _destination: &str,
+        _exclude_git: bool,
+        _exclude_rules: &[IgnoreSource],
+        _options: &CopyOptions,
+        _wsl_session: &crate::os::WslSession,
+    ) -> Result<(), String> {
         Err("Windows native copy is only available on Windows".to_string())
     }
 
     #[cfg(not(target_os = "windows"))]
-    fn copy_files_optimized_wsl(_source: &str, _destination: &str, _distribution: &str, _exclude_git: bool) -> Result<(), String> {
+    fn copy_files_optimized_wsl(
+        _source: &str,
+        _destination: &str,
+        _distribution: &str,
+        _exclude_git: bool,
+        _exclude_rules: &[IgnoreSource],
+        _options: &CopyOptions,
+    ) -> Result<(), String> {
         Err("WSL is only supported on Windows".to_string())
     }
 
 
-    pub fn get_copy_stats(source: &str, destination: &str, os_session: &OsSession) -> Result<serde_json::Value, String> {
+    pub fn get_copy_stats(
+        source: &str,
+        destination: &str,
+        os_session: &OsSession,
+        exclude_git: bool,
+        exclude_rules: &[IgnoreSource],
+    ) -> Result<serde_json::Value, String> {
         match os_session {
             OsSession::Local(_) => {
-                Self::get_copy_stats_local(source, destination)
+                Self::get_copy_stats_local(source, destination, exclude_git, exclude_rules)
             }
             OsSession::Wsl(wsl_session) => {
                 // If both paths are Windows mounts, use Windows native stats for accuracy
@@ -432,44 +777,88 @@ impl FileSystemManager {
                     {
                         let windows_source = Self::convert_wsl_mount_to_windows_path(source);
                         let windows_dest = Self::convert_wsl_mount_to_windows_path(destination);
-                        
+
                         // Use the original source path with WSL session for Cargo detection
                         let contains_cargo_projects = Self::contains_cargo_projects(source, os_session);
-                        
-                        Self::get_copy_stats_local_with_exclusions(&windows_source, &windows_dest, contains_cargo_projects)
+                        let matcher = Self::build_matcher(&windows_source, exclude_git, contains_cargo_projects, exclude_rules);
+
+                        Self::get_copy_stats_local_with_matcher(&windows_source, &windows_dest, &matcher)
                     }
                     #[cfg(not(target_os = "windows"))]
                     {
-                        Self::get_copy_stats_wsl(source, destination, &wsl_session.distribution)
+                        Self::get_copy_stats_wsl(source, destination, &wsl_session.distribution, exclude_git, exclude_rules)
                     }
                 } else {
-                    Self::get_copy_stats_wsl(source, destination, &wsl_session.distribution)
+                    Self::get_copy_stats_wsl(source, destination, &wsl_session.distribution, exclude_git, exclude_rules)
                 }
             }
         }
     }
 
-    fn get_copy_stats_local(source: &str, destination: &str) -> Result<serde_json::Value, String> {
+    fn get_copy_stats_local(source: &str, destination: &str, exclude_git: bool, exclude_rules: &[IgnoreSource]) -> Result<serde_json::Value, String> {
         // Check if this contains Cargo projects to determine what to exclude
         let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Local(source.to_string()));
-        Self::get_copy_stats_local_with_exclusions(source, destination, contains_cargo_projects)
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        let mut stats = Self::get_copy_stats_local_with_matcher(source, destination, &matcher)?;
+
+        // Fold in any out-of-tree `path = "..."` Cargo dependencies
+        // `copy_files_optimized` also copies into `destination` - otherwise
+        // progress would look complete before those extra trees land.
+        if contains_cargo_projects {
+            let dest_path = Path::new(destination);
+            let mut seen: HashSet<PathBuf> = HashSet::new();
+            let (mut extra_total, mut extra_copied) = (0u64, 0u64);
+
+            for dep in cargo_deps::find_external_path_dependencies(Path::new(source), &matcher) {
+                if !seen.insert(dep.relocated_relative.clone()) {
+                    continue;
+                }
+
+                let dep_contains_cargo_projects = Self::contains_cargo_projects(
+                    &dep.external_path.to_string_lossy(),
+                    &OsSession::Local(dep.external_path.to_string_lossy().to_string()),
+                );
+                let dep_matcher = Self::build_matcher(
+                    &dep.external_path.to_string_lossy(),
+                    exclude_git,
+                    dep_contains_cargo_projects,
+                    exclude_rules,
+                );
+
+                extra_total += Self::get_directory_size_with_exclusions(&dep.external_path, &dep_matcher).unwrap_or(0);
+
+                let relocated_dest = dest_path.join(&dep.relocated_relative);
+                if relocated_dest.exists() {
+                    extra_copied += Self::get_directory_size_with_exclusions(&relocated_dest, &dep_matcher).unwrap_or(0);
+                }
+            }
+
+            if extra_total > 0 {
+                let total = stats.get("total").and_then(|v| v.as_u64()).unwrap_or(0);
+                let copied = stats.get("copied").and_then(|v| v.as_u64()).unwrap_or(0);
+                stats["total"] = serde_json::json!(total + extra_total);
+                stats["copied"] = serde_json::json!(copied + extra_copied);
+            }
+        }
+
+        Ok(stats)
     }
-    
-    fn get_copy_stats_local_with_exclusions(source: &str, destination: &str, exclude_target: bool) -> Result<serde_json::Value, String> {
+
+    fn get_copy_stats_local_with_matcher(source: &str, destination: &str, matcher: &IgnoreMatcher) -> Result<serde_json::Value, String> {
         let src_path = Path::new(source);
         let dst_path = Path::new(destination);
-        
+
         if !src_path.exists() {
             return Err("Source path does not exist".to_string());
         }
-        
-        let total_size = Self::get_directory_size_with_exclusions(src_path, exclude_target)?;
+
+        let total_size = Self::get_directory_size_with_exclusions(src_path, matcher)?;
         let copied_size = if dst_path.exists() {
-            Self::get_directory_size_with_exclusions(dst_path, exclude_target)?
+            Self::get_directory_size_with_exclusions(dst_path, matcher)?
         } else {
             0
         };
-        
+
         Ok(serde_json::json!({
             "total": total_size,
             "copied": copied_size,
@@ -478,41 +867,43 @@ impl FileSystemManager {
     }
 
     #[cfg(target_os = "windows")]
-    fn get_copy_stats_wsl(source: &str, destination: &str, distribution: &str) -> Result<serde_json::Value, String> {
+    fn get_copy_stats_wsl(source: &str, destination: &str, distribution: &str, exclude_git: bool, exclude_rules: &[IgnoreSource]) -> Result<serde_json::Value, String> {
         let wsl_session = crate::os::WslSession {
             distribution: distribution.to_string(),
             working_directory: "/".to_string(),
         };
-        
+
         // Check if this contains Cargo projects to determine what to exclude
         let contains_cargo_projects = Self::contains_cargo_projects(source, &OsSession::Wsl(wsl_session.clone()));
-        
-        // Build exclusion pattern for du command
-        let exclusions = if contains_cargo_projects {
-            " --exclude='target'"
-        } else {
-            ""
-        };
-        
+        // Same 9p-boundary limitation as copy_files_optimized_wsl: no local
+        // tree walk for .gitignore files, so `du` is fed the flattened
+        // built-in + caller-supplied patterns only.
+        let matcher = Self::build_matcher(source, exclude_git, contains_cargo_projects, exclude_rules);
+        let exclusions: String = matcher
+            .effective_exclude_patterns()
+            .iter()
+            .map(|p| format!(" --exclude='{}'", p.replace('\'', "'\\''")))
+            .collect();
+
         let total_cmd = format!("du -sb{} '{}' 2>/dev/null | cut -f1", exclusions, source);
         let total_result = CommandExecutor::execute_with_os_session(
-            "bash", 
+            "bash",
             &["-c", &total_cmd],
-            None, 
+            None,
             &OsSession::Wsl(wsl_session.clone())
         )?;
-        
+
         let copied_cmd = format!("du -sb{} '{}' 2>/dev/null | cut -f1 || echo 0", exclusions, destination);
         let copied_result = CommandExecutor::execute_with_os_session(
-            "bash", 
+            "bash",
             &["-c", &copied_cmd],
-            None, 
+            None,
             &OsSession::Wsl(wsl_session)
         )?;
-        
+
         let total_size: u64 = total_result.trim().parse().unwrap_or(0);
         let copied_size: u64 = copied_result.trim().parse().unwrap_or(0);
-        
+
         Ok(serde_json::json!({
             "total": total_size,
             "copied": copied_size,
@@ -521,118 +912,319 @@ impl FileSystemManager {
     }
 
     #[cfg(not(target_os = "windows"))]
-    fn get_copy_stats_wsl(_source: &str, _destination: &str, _distribution: &str) -> Result<serde_json::Value, String> {
+    fn get_copy_stats_wsl(_source: &str, _destination: &str, _distribution: &str, _exclude_git: bool, _exclude_rules: &[IgnoreSource]) -> Result<serde_json::Value, String> {
         Err("WSL is only supported on Windows".to_string())
     }
 
-    fn get_directory_size(path: &Path) -> Result<u64, String> {
-        Self::get_directory_size_with_exclusions(path, false)
-    }
-    
-    fn get_directory_size_with_exclusions(path: &Path, exclude_target: bool) -> Result<u64, String> {
+    /// Recursively sums file sizes under `path`, skipping anything
+    /// `matcher` ignores - including not descending into ignored
+    /// directories at all, so totals match what actually gets copied.
+    fn get_directory_size_with_exclusions(path: &Path, matcher: &IgnoreMatcher) -> Result<u64, String> {
         let mut total_size = 0;
-        
+
         if path.is_file() {
             let metadata = path.metadata()
                 .map_err(|e| format!("Failed to get file metadata: {}", e))?;
             return Ok(metadata.len());
         }
-        
+
         if path.is_dir() {
             let entries = fs::read_dir(path)
                 .map_err(|e| format!("Failed to read directory: {}", e))?;
-            
+
             for entry in entries {
                 let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
                 let entry_path = entry.path();
-                let entry_name = entry.file_name().to_string_lossy().to_string();
-                
-                // Skip target directories if exclusion is enabled
-                if exclude_target && entry_name == "target" && entry_path.is_dir() {
+                let is_dir = entry_path.is_dir();
+
+                if matcher.is_ignored(&entry_path, is_dir) {
                     continue;
                 }
-                
-                total_size += Self::get_directory_size_with_exclusions(&entry_path, exclude_target)?;
+
+                total_size += Self::get_directory_size_with_exclusions(&entry_path, matcher)?;
             }
         }
-        
+
         Ok(total_size)
     }
 
     pub fn open_in_explorer(path: &str) -> Result<(), String> {
+        Self::open_path_local(path, RevealMode::Reveal).map_err(String::from)
+    }
+
+    pub fn open_in_explorer_with_os_session(path: &str, os_session: &OsSession) -> Result<(), String> {
+        Self::open_path_with_mode(path, os_session, RevealMode::Reveal).map_err(String::from)
+    }
+
+    /// Launches `path` in its system default handler (a document viewer, an
+    /// editor, a browser for a URL, ...) rather than revealing it in a file
+    /// manager window.
+    pub fn open_path(path: &str) -> Result<(), String> {
+        Self::open_path_local(path, RevealMode::Open).map_err(String::from)
+    }
+
+    pub fn open_path_with_os_session(path: &str, os_session: &OsSession) -> Result<(), String> {
+        Self::open_path_with_mode(path, os_session, RevealMode::Open).map_err(String::from)
+    }
+
+    /// Opens `url` in the user's default browser, honoring a `$BROWSER`
+    /// environment variable override (the same convention tools like `git
+    /// send-email` and `xdg-open`-wrapping CLIs use) before falling back to
+    /// the platform's default handler for `open_path`.
+    pub fn open_url(url: &str) -> Result<(), String> {
+        if let Some(browser) = browser_override() {
+            return crate::system::SystemManager::execute_command(&browser, &[url]).map(|_| ());
+        }
+        Self::open_path(url)
+    }
+
+    pub fn open_url_with_os_session(url: &str, os_session: &OsSession) -> Result<(), String> {
+        if let Some(browser) = browser_override() {
+            return crate::system::SystemManager::execute_command(&browser, &[url]).map(|_| ());
+        }
+        Self::open_path_with_os_session(url, os_session)
+    }
+
+    fn open_path_local(path: &str, mode: RevealMode) -> Result<(), OpenError> {
         #[cfg(target_os = "windows")]
         {
             use crate::system::SystemManager;
             let windows_path = path.replace('/', "\\");
             let path_obj = std::path::Path::new(&windows_path);
-            
-            if path_obj.is_dir() {
-                SystemManager::execute_command("explorer", &[&windows_path]).map(|_| ())
-            } else {
-                SystemManager::execute_command("explorer", &["/select,", &windows_path]).map(|_| ())
-            }?
+
+            match mode {
+                RevealMode::Reveal => {
+                    if path_obj.is_dir() {
+                        SystemManager::execute_command("explorer", &[&windows_path]).map(|_| ())
+                    } else {
+                        SystemManager::execute_command("explorer", &["/select,", &windows_path]).map(|_| ())
+                    }
+                    .map_err(OpenError::SpawnFailed)?
+                }
+                // `explorer` itself doubles as a handler launcher, but `cmd
+                // /C start` is the documented way to hand a path/URL to
+                // whatever's registered as its default handler.
+                RevealMode::Open => SystemManager::execute_command("cmd", &["/C", "start", "", &windows_path])
+                    .map(|_| ())
+                    .map_err(OpenError::SpawnFailed)?,
+            }
         }
-        
+
         #[cfg(target_os = "macos")]
         {
             use crate::system::SystemManager;
-            SystemManager::execute_command("open", &[path]).map(|_| ())?
+            match mode {
+                // `-R` reveals and selects the item in Finder instead of
+                // opening it, mirroring `explorer /select,` on Windows.
+                RevealMode::Reveal => SystemManager::execute_command("open", &["-R", path])
+                    .map(|_| ())
+                    .map_err(OpenError::SpawnFailed)?,
+                RevealMode::Open => SystemManager::execute_command("open", &[path])
+                    .map(|_| ())
+                    .map_err(OpenError::SpawnFailed)?,
+            }
         }
-        
+
         #[cfg(target_os = "linux")]
+        {
+            use crate::system::SystemManager;
+            match mode {
+                RevealMode::Reveal => {
+                    if reveal_via_dbus(path).is_ok() {
+                        return Ok(());
+                    }
+
+                    if is_wsl_guest() && SystemManager::execute_command("wslview", &[path]).is_ok() {
+                        return Ok(());
+                    }
+
+                    let file_managers = ["xdg-open", "nautilus", "dolphin", "thunar", "pcmanfm"];
+
+                    for manager in &file_managers {
+                        if SystemManager::execute_command(manager, &[path]).is_ok() {
+                            return Ok(());
+                        }
+                    }
+
+                    return Err(OpenError::NoHandler("no file manager found on Linux".to_string()));
+                }
+                RevealMode::Open => {
+                    if is_wsl_guest() && SystemManager::execute_command("wslview", &[path]).is_ok() {
+                        return Ok(());
+                    }
+                    SystemManager::execute_command("xdg-open", &[path])
+                        .map(|_| ())
+                        .map_err(OpenError::SpawnFailed)?
+                }
+            }
+        }
+
+        // The BSDs don't ship a single canonical opener, but the xdg-style
+        // file-manager chain Linux desktops use is present on the ones that
+        // run a desktop environment (GNOME/KDE-on-BSD, etc.), so reuse it.
+        #[cfg(any(target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
         {
             use crate::system::SystemManager;
             let file_managers = ["xdg-open", "nautilus", "dolphin", "thunar", "pcmanfm"];
-            
+
             for manager in &file_managers {
                 if SystemManager::execute_command(manager, &[path]).is_ok() {
                     return Ok(());
                 }
             }
-            
-            return Err("Failed to open file manager on Linux".to_string());
+
+            return Err(OpenError::NoHandler("no file manager found on this BSD".to_string()));
         }
-        
+
+        #[cfg(target_os = "haiku")]
+        {
+            use crate::system::SystemManager;
+            let _ = mode;
+            SystemManager::execute_command("/bin/open", &[path])
+                .map(|_| ())
+                .map_err(OpenError::SpawnFailed)?
+        }
+
         Ok(())
     }
 
-    pub fn open_in_explorer_with_os_session(path: &str, os_session: &OsSession) -> Result<(), String> {
+    fn open_path_with_mode(path: &str, os_session: &OsSession, mode: RevealMode) -> Result<(), OpenError> {
         match os_session {
             OsSession::Local(_) => {
                 // For local sessions, use the path directly
-                Self::open_in_explorer(path)
+                Self::open_path_local(path, mode)
             }
             OsSession::Wsl(wsl_session) => {
                 // For WSL sessions, convert to Windows path format
                 #[cfg(target_os = "windows")]
                 {
                     use crate::system::SystemManager;
-                    
-                    // Convert WSL path to Windows explorer format
-                    let windows_path = if path.starts_with("/mnt/") {
-                        // Path like /mnt/c/Users/... -> C:\Users\...
-                        Self::convert_wsl_mount_to_windows_path(path)
-                    } else {
-                        // Path like /home/user/... -> \\wsl$\Ubuntu\home\user\...
-                        let wsl_path = format!("\\\\wsl$\\{}\\{}", 
-                            wsl_session.distribution, 
-                            path.trim_start_matches('/').replace('/', "\\"));
-                        wsl_path
-                    };
-                    
+
+                    let windows_path = crate::path_translation::wsl_to_windows(path, &wsl_session.distribution)
+                        .to_string_lossy()
+                        .to_string();
+
                     let path_obj = std::path::Path::new(&windows_path);
-                    
-                    if path_obj.is_dir() {
-                        SystemManager::execute_command("explorer", &[&windows_path]).map(|_| ())
-                    } else {
-                        SystemManager::execute_command("explorer", &["/select,", &windows_path]).map(|_| ())
+
+                    match mode {
+                        RevealMode::Reveal => {
+                            if path_obj.is_dir() {
+                                SystemManager::execute_command("explorer", &[&windows_path]).map(|_| ())
+                            } else {
+                                SystemManager::execute_command("explorer", &["/select,", &windows_path]).map(|_| ())
+                            }
+                            .map_err(OpenError::SpawnFailed)
+                        }
+                        RevealMode::Open => SystemManager::execute_command("cmd", &["/C", "start", "", &windows_path])
+                            .map(|_| ())
+                            .map_err(OpenError::SpawnFailed),
                     }
                 }
                 #[cfg(not(target_os = "windows"))]
                 {
-                    Err("WSL path opening is only supported on Windows".to_string())
+                    let _ = mode;
+                    Err(OpenError::NoHandler("WSL path opening is only supported on Windows".to_string()))
                 }
             }
         }
     }
+}
+
+/// Why opening/revealing a path failed. Distinguishes "nothing on this
+/// platform knows how to handle it" (no point retrying) from "a handler was
+/// found but invoking it failed" (e.g. a transient spawn error) - useful now
+/// that the platform/handler matrix here spans several fallback chains per
+/// OS. Converts to `String` at the public API boundary, matching the rest
+/// of this module's error type.
+#[derive(Debug, Clone)]
+pub enum OpenError {
+    NoHandler(String),
+    SpawnFailed(String),
+}
+
+impl std::fmt::Display for OpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenError::NoHandler(msg) => write!(f, "{}", msg),
+            OpenError::SpawnFailed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OpenError {}
+
+impl From<OpenError> for String {
+    fn from(err: OpenError) -> String {
+        err.to_string()
+    }
+}
+
+/// Whether a path should be revealed/selected in a file manager window, or
+/// simply handed to its system default handler - Windows needs a different
+/// command for each (`explorer /select,` vs `cmd /C start`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealMode {
+    Reveal,
+    Open,
+}
+
+/// The `$BROWSER` override, if set to a non-empty value.
+fn browser_override() -> Option<String> {
+    std::env::var("BROWSER").ok().filter(|b| !b.is_empty())
+}
+
+/// Asks the desktop's actual default file manager to open and select
+/// `path` via the freedesktop `org.freedesktop.FileManager1` D-Bus service,
+/// rather than just opening its parent directory - the Linux equivalent of
+/// Windows' `explorer /select,` and macOS' `open -R`. Only compiled in when
+/// the `reveal` feature is enabled, so headless builds don't pull in a
+/// D-Bus dependency; a stub that always fails stands in otherwise, which
+/// the caller treats the same as "no session bus available" and falls
+/// through to the command-spawning chain.
+#[cfg(all(target_os = "linux", feature = "reveal"))]
+fn reveal_via_dbus(path: &str) -> Result<(), OpenError> {
+    let canonical =
+        fs::canonicalize(path).map_err(|e| OpenError::SpawnFailed(format!("Failed to resolve '{}': {}", path, e)))?;
+    let uri = format!("file://{}", canonical.display());
+
+    let connection = zbus::blocking::Connection::session()
+        .map_err(|e| OpenError::NoHandler(format!("Failed to connect to session bus: {}", e)))?;
+
+    connection
+        .call_method(
+            Some("org.freedesktop.FileManager1"),
+            "/org/freedesktop/FileManager1",
+            Some("org.freedesktop.FileManager1"),
+            "ShowItems",
+            &(vec![uri], ""),
+        )
+        .map(|_| ())
+        .map_err(|e| OpenError::SpawnFailed(format!("ShowItems call failed: {}", e)))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "reveal")))]
+fn reveal_via_dbus(_path: &str) -> Result<(), OpenError> {
+    Err(OpenError::NoHandler("reveal feature not enabled".to_string()))
+}
+
+/// True when this binary is itself running inside a WSL Linux guest (as
+/// opposed to a plain Linux host, or the Windows host with a separate WSL
+/// session) - detected by looking for `microsoft`/`wsl` in the kernel
+/// release/version strings the WSL kernel advertises there.
+#[cfg(target_os = "linux")]
+fn is_wsl_guest() -> bool {
+    for path in ["/proc/sys/kernel/osrelease", "/proc/version"] {
+        if let Ok(contents) = fs::read_to_string(path) {
+            let lower = contents.to_lowercase();
+            if lower.contains("microsoft") || lower.contains("wsl") {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(target_os = "windows")]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
 }
\ No newline at end of file
@@ -1,8 +1,56 @@
 use crate::commands::CommandExecutor;
 use crate::os::OsSession;
+use serde::Serialize;
 use std::path::Path;
 use std::process::Command;
 
+mod remote;
+pub mod recovery;
+
+/// One entry from `git status --porcelain=v2`, with rename detection and
+/// conflict state already split out instead of leaving the caller to
+/// re-parse the two-letter `XY` code.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatusEntry {
+    pub path: String,
+    pub original_path: Option<String>,
+    pub staged_status: char,
+    pub unstaged_status: char,
+    pub is_untracked: bool,
+    pub is_conflicted: bool,
+}
+
+/// One line of a diff hunk's body.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: String, // "context" | "added" | "removed"
+    pub content: String,
+}
+
+/// One `@@ -a,b +c,d @@` hunk, with its body already split into
+/// added/removed/context lines.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A single file's diff, parsed out of `git diff`'s unified format.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    pub original_path: Option<String>,
+    pub is_binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
 /// Git repository management functions
 pub struct GitManager;
 
@@ -28,24 +76,30 @@ impl GitManager {
     }
 
     pub fn commit(directory: &str, message: &str, os_session: &OsSession) -> Result<String, String> {
+        recovery::with_corruption_retry(directory, os_session, || {
+            Self::commit_once(directory, message, os_session)
+        })
+    }
+
+    fn commit_once(directory: &str, message: &str, os_session: &OsSession) -> Result<String, String> {
         // First, add all changes
         CommandExecutor::execute_with_os_session("git", &["add", "."], Some(directory), os_session)?;
-        
+
         // Then commit
         let commit_result = CommandExecutor::execute_with_os_session(
-            "git", 
-            &["commit", "-m", message], 
-            Some(directory), 
+            "git",
+            &["commit", "-m", message],
+            Some(directory),
             os_session
         );
-        
+
         match commit_result {
             Ok(_) => {
                 // Get the commit hash
                 CommandExecutor::execute_with_os_session(
-                    "git", 
-                    &["rev-parse", "HEAD"], 
-                    Some(directory), 
+                    "git",
+                    &["rev-parse", "HEAD"],
+                    Some(directory),
                     os_session
                 ).map(|hash| hash.trim().to_string())
             }
@@ -61,12 +115,14 @@ impl GitManager {
     }
 
     pub fn revert_to_commit(directory: &str, commit_hash: &str, os_session: &OsSession) -> Result<(), String> {
-        CommandExecutor::execute_with_os_session(
-            "git", 
-            &["reset", "--hard", commit_hash], 
-            Some(directory), 
-            os_session
-        ).map(|_| ())
+        recovery::with_corruption_retry(directory, os_session, || {
+            CommandExecutor::execute_with_os_session(
+                "git",
+                &["reset", "--hard", commit_hash],
+                Some(directory),
+                os_session
+            ).map(|_| ())
+        })
     }
 
     pub fn create_branch(directory: &str, branch_name: &str, os_session: &OsSession) -> Result<(), String> {
@@ -146,9 +202,20 @@ impl GitManager {
     }
 
     pub fn merge_branch(
-        directory: &str, 
-        source_branch: &str, 
-        target_branch: &str, 
+        directory: &str,
+        source_branch: &str,
+        target_branch: &str,
+        os_session: &OsSession
+    ) -> Result<String, String> {
+        recovery::with_corruption_retry(directory, os_session, || {
+            Self::merge_branch_once(directory, source_branch, target_branch, os_session)
+        })
+    }
+
+    fn merge_branch_once(
+        directory: &str,
+        source_branch: &str,
+        target_branch: &str,
         os_session: &OsSession
     ) -> Result<String, String> {
         // First checkout target branch
@@ -180,12 +247,457 @@ impl GitManager {
         }
     }
 
+    pub fn fetch(directory: &str, remote: &str, os_session: &OsSession) -> Result<(), String> {
+        CommandExecutor::execute_with_os_session("git", &["fetch", remote], Some(directory), os_session)
+            .map(|_| ())
+    }
+
+    /// Fast-forwards the current branch to its upstream if it's strictly
+    /// behind. Never creates a merge commit. Returns one of
+    /// `ALREADY_UP_TO_DATE`, `FAST_FORWARDED`, or `DIVERGED` (which needs a
+    /// manual merge).
+    pub fn pull_fast_forward(directory: &str, os_session: &OsSession) -> Result<String, String> {
+        CommandExecutor::execute_with_os_session("git", &["fetch"], Some(directory), os_session)?;
+
+        let local = Self::get_current_hash(directory, os_session)?;
+        let upstream = CommandExecutor::execute_with_os_session(
+            "git",
+            &["rev-parse", "@{u}"],
+            Some(directory),
+            os_session,
+        )?
+        .trim()
+        .to_string();
+
+        if local == upstream {
+            return Ok("ALREADY_UP_TO_DATE".to_string());
+        }
+
+        let merge_base = CommandExecutor::execute_with_os_session(
+            "git",
+            &["merge-base", "HEAD", "@{u}"],
+            Some(directory),
+            os_session,
+        )?
+        .trim()
+        .to_string();
+
+        if merge_base != local {
+            // HEAD is not an ancestor of upstream: history has diverged.
+            return Ok("DIVERGED".to_string());
+        }
+
+        CommandExecutor::execute_with_os_session(
+            "git",
+            &["merge", "--ff-only", "@{u}"],
+            Some(directory),
+            os_session,
+        )?;
+        Ok("FAST_FORWARDED".to_string())
+    }
+
+    /// Resolves the remote's default branch via
+    /// `refs/remotes/origin/HEAD`, falling back to probing for `main` then
+    /// `master` if that ref hasn't been set locally.
+    pub fn get_default_branch(directory: &str, os_session: &OsSession) -> Result<String, String> {
+        if let Ok(output) = CommandExecutor::execute_with_os_session(
+            "git",
+            &["symbolic-ref", "refs/remotes/origin/HEAD"],
+            Some(directory),
+            os_session,
+        ) {
+            if let Some(branch) = output.trim().strip_prefix("refs/remotes/origin/") {
+                return Ok(branch.to_string());
+            }
+        }
+
+        for candidate in ["main", "master"] {
+            let refname = format!("refs/remotes/origin/{}", candidate);
+            let exists = CommandExecutor::execute_with_os_session(
+                "git",
+                &["rev-parse", "--verify", "--quiet", &refname],
+                Some(directory),
+                os_session,
+            ).is_ok();
+
+            if exists {
+                return Ok(candidate.to_string());
+            }
+        }
+
+        Err("Could not determine default branch".to_string())
+    }
+
+    /// Attempts escalating repair of a repository with corrupt refs or a
+    /// broken index (stale `index.lock`, hard reset, re-fetch + hard reset
+    /// to upstream). Exposed directly so the frontend can offer a manual
+    /// "repair repository" action.
+    pub fn repair_repository(directory: &str, os_session: &OsSession) -> Result<(), String> {
+        recovery::repair_repository(directory, os_session)
+    }
+
+    /// Returns the canonical, provider-aware browseable URL for the `origin`
+    /// remote (e.g. `https://github.com/owner/repo`), falling back to the raw
+    /// remote URL if it doesn't match any known shape.
+    pub fn get_origin_url(directory: &str, os_session: &OsSession) -> Result<String, String> {
+        let url = CommandExecutor::execute_with_os_session(
+            "git",
+            &["remote", "get-url", "origin"],
+            Some(directory),
+            os_session,
+        )?
+        .trim()
+        .to_string();
+
+        match remote::parse_remote_url(&url) {
+            Some(remote_info) => Ok(remote_info.web_url),
+            None => Ok(url),
+        }
+    }
+
     pub fn get_current_hash(directory: &str, os_session: &OsSession) -> Result<String, String> {
         CommandExecutor::execute_with_os_session(
-            "git", 
-            &["rev-parse", "HEAD"], 
-            Some(directory), 
+            "git",
+            &["rev-parse", "HEAD"],
+            Some(directory),
             os_session
         ).map(|output| output.trim().to_string())
     }
+
+    /// Creates a new worktree at `worktree_path` checked out to `branch_name`,
+    /// sharing `directory`'s object store instead of copying the tree.
+    /// `worktree_path` can live on WSL (e.g. `/mnt/...`) via `os_session`.
+    pub fn create_worktree(
+        directory: &str,
+        branch_name: &str,
+        worktree_path: &str,
+        os_session: &OsSession,
+    ) -> Result<(), String> {
+        if Self::branch_checked_out_elsewhere(directory, branch_name, os_session)? {
+            return Err(format!(
+                "Branch '{}' is already checked out in another worktree",
+                branch_name
+            ));
+        }
+
+        let branch_exists = CommandExecutor::execute_with_os_session(
+            "git",
+            &["rev-parse", "--verify", "--quiet", branch_name],
+            Some(directory),
+            os_session,
+        ).is_ok();
+
+        let args: Vec<&str> = if branch_exists {
+            vec!["worktree", "add", worktree_path, branch_name]
+        } else {
+            vec!["worktree", "add", "-b", branch_name, worktree_path]
+        };
+
+        CommandExecutor::execute_with_os_session("git", &args, Some(directory), os_session)
+            .map(|_| ())
+    }
+
+    /// Lists the worktrees registered against the repository at `directory`.
+    pub fn list_worktrees(
+        directory: &str,
+        os_session: &OsSession,
+    ) -> Result<Vec<serde_json::Value>, String> {
+        let output = CommandExecutor::execute_with_os_session(
+            "git",
+            &["worktree", "list", "--porcelain"],
+            Some(directory),
+            os_session,
+        )?;
+
+        Ok(Self::parse_worktree_list(&output))
+    }
+
+    fn parse_worktree_list(output: &str) -> Vec<serde_json::Value> {
+        let mut worktrees = Vec::new();
+        let mut path: Option<String> = None;
+        let mut head: Option<String> = None;
+        let mut branch: Option<String> = None;
+        let mut locked = false;
+
+        let flush = |path: &mut Option<String>, head: &mut Option<String>, branch: &mut Option<String>, locked: &mut bool, out: &mut Vec<serde_json::Value>| {
+            if let Some(path) = path.take() {
+                out.push(serde_json::json!({
+                    "path": path,
+                    "head": head.take(),
+                    "branch": branch.take(),
+                    "locked": *locked,
+                }));
+            }
+            *locked = false;
+        };
+
+        for line in output.lines() {
+            if let Some(value) = line.strip_prefix("worktree ") {
+                flush(&mut path, &mut head, &mut branch, &mut locked, &mut worktrees);
+                path = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("HEAD ") {
+                head = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("branch ") {
+                branch = Some(
+                    value
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(value)
+                        .to_string(),
+                );
+            } else if line == "locked" || line.starts_with("locked ") {
+                locked = true;
+            }
+        }
+        flush(&mut path, &mut head, &mut branch, &mut locked, &mut worktrees);
+
+        worktrees
+    }
+
+    /// Returns true if `branch_name` is already checked out in a worktree
+    /// other than `directory` itself.
+    fn branch_checked_out_elsewhere(
+        directory: &str,
+        branch_name: &str,
+        os_session: &OsSession,
+    ) -> Result<bool, String> {
+        let worktrees = Self::list_worktrees(directory, os_session)?;
+        let current_branch = Self::get_current_branch(directory, os_session).ok();
+
+        Ok(worktrees.iter().any(|worktree| {
+            let branch = worktree.get("branch").and_then(|b| b.as_str());
+            branch == Some(branch_name) && current_branch.as_deref() != Some(branch_name)
+        }))
+    }
+
+    /// Removes the worktree at `worktree_path`. Refuses to remove a worktree
+    /// with uncommitted changes unless `force` is set.
+    pub fn remove_worktree(
+        worktree_path: &str,
+        force: bool,
+        os_session: &OsSession,
+    ) -> Result<(), String> {
+        if !force {
+            let status = CommandExecutor::execute_with_os_session(
+                "git",
+                &["status", "--porcelain"],
+                Some(worktree_path),
+                os_session,
+            )?;
+            if !status.trim().is_empty() {
+                return Err("WORKTREE_HAS_UNCOMMITTED_CHANGES".to_string());
+            }
+        }
+
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(worktree_path);
+
+        CommandExecutor::execute_with_os_session("git", &args, None, os_session).map(|_| ())
+    }
+
+    /// Merges a worktree's branch back into `target_branch`, mirroring
+    /// `merge_branch` but sourced from a worktree checkout.
+    pub fn merge_worktree(
+        directory: &str,
+        worktree_branch: &str,
+        target_branch: &str,
+        os_session: &OsSession,
+    ) -> Result<String, String> {
+        Self::merge_branch(directory, worktree_branch, target_branch, os_session)
+    }
+
+    /// Returns parsed working tree status, including untracked files and
+    /// rename detection, via `git status --porcelain=v2`. More structured
+    /// than `get_conflict_files`/`check_merge_conflicts`, which only expose
+    /// conflict state as a flat file list or a yes/no flag.
+    pub fn get_status(directory: &str, os_session: &OsSession) -> Result<Vec<GitStatusEntry>, String> {
+        let output = CommandExecutor::execute_with_os_session(
+            "git",
+            &["status", "--porcelain=v2", "--untracked-files=all"],
+            Some(directory),
+            os_session,
+        )?;
+
+        Ok(Self::parse_status_v2(&output))
+    }
+
+    fn parse_status_v2(output: &str) -> Vec<GitStatusEntry> {
+        let mut entries = Vec::new();
+
+        for line in output.lines() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut head = line.splitn(2, ' ');
+            let marker = head.next().unwrap_or("");
+            let rest = head.next().unwrap_or("");
+
+            match marker {
+                // 1 XY sub mH mI mW hH hI path
+                "1" => {
+                    let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+                    let (Some(xy), Some(path)) = (fields.first(), fields.get(7)) else {
+                        continue;
+                    };
+                    entries.push(Self::status_entry_from_xy(xy, path, None, false));
+                }
+                // 2 XY sub mH mI mW hH hI score path\toriginalPath
+                "2" => {
+                    let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+                    let (Some(xy), Some(path_and_original)) = (fields.first(), fields.get(8)) else {
+                        continue;
+                    };
+                    let mut parts = path_and_original.splitn(2, '\t');
+                    let path = parts.next().unwrap_or("");
+                    let original_path = parts.next().map(|p| p.to_string());
+                    entries.push(Self::status_entry_from_xy(xy, path, original_path, false));
+                }
+                // u XY sub m1 m2 m3 mW h1 h2 h3 path
+                "u" => {
+                    let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+                    let (Some(xy), Some(path)) = (fields.first(), fields.get(9)) else {
+                        continue;
+                    };
+                    entries.push(Self::status_entry_from_xy(xy, path, None, true));
+                }
+                // ? path
+                "?" => {
+                    entries.push(GitStatusEntry {
+                        path: rest.to_string(),
+                        original_path: None,
+                        staged_status: '.',
+                        unstaged_status: '?',
+                        is_untracked: true,
+                        is_conflicted: false,
+                    });
+                }
+                // ! path (ignored) - not relevant to a status/diff UI
+                _ => {}
+            }
+        }
+
+        entries
+    }
+
+    fn status_entry_from_xy(xy: &str, path: &str, original_path: Option<String>, is_conflicted: bool) -> GitStatusEntry {
+        let mut chars = xy.chars();
+        GitStatusEntry {
+            path: path.to_string(),
+            original_path,
+            staged_status: chars.next().unwrap_or('.'),
+            unstaged_status: chars.next().unwrap_or('.'),
+            is_untracked: false,
+            is_conflicted,
+        }
+    }
+
+    /// Returns a parsed diff against `rev_or_worktree` (a commit-ish, or an
+    /// empty string for the unstaged working tree diff), with hunks already
+    /// split into added/removed/context lines. Tolerant of binary files
+    /// (reported with no hunks) and renames (`original_path` set when git
+    /// detects one).
+    pub fn get_diff(directory: &str, rev_or_worktree: &str, os_session: &OsSession) -> Result<Vec<FileDiff>, String> {
+        let mut args = vec!["diff", "--no-color", "-M"];
+        if !rev_or_worktree.is_empty() {
+            args.push(rev_or_worktree);
+        }
+
+        let output = CommandExecutor::execute_with_os_session("git", &args, Some(directory), os_session)?;
+        Ok(Self::parse_unified_diff(&output))
+    }
+
+    fn parse_unified_diff(output: &str) -> Vec<FileDiff> {
+        let mut files = Vec::new();
+        let mut current: Option<FileDiff> = None;
+        let mut current_hunk: Option<DiffHunk> = None;
+
+        let flush_hunk = |current: &mut Option<FileDiff>, current_hunk: &mut Option<DiffHunk>| {
+            if let (Some(file), Some(hunk)) = (current.as_mut(), current_hunk.take()) {
+                file.hunks.push(hunk);
+            }
+        };
+
+        for line in output.lines() {
+            if let Some(rest) = line.strip_prefix("diff --git ") {
+                flush_hunk(&mut current, &mut current_hunk);
+                if let Some(file) = current.take() {
+                    files.push(file);
+                }
+
+                let (a_path, b_path) = Self::parse_diff_git_paths(rest);
+                let path = b_path.clone().unwrap_or_default();
+                let original_path = match (&a_path, &b_path) {
+                    (Some(a), Some(b)) if a != b => Some(a.clone()),
+                    _ => None,
+                };
+                current = Some(FileDiff {
+                    path,
+                    original_path,
+                    is_binary: false,
+                    hunks: Vec::new(),
+                });
+            } else if line.starts_with("Binary files") || line.starts_with("GIT binary patch") {
+                if let Some(file) = current.as_mut() {
+                    file.is_binary = true;
+                }
+            } else if line.starts_with("@@") {
+                flush_hunk(&mut current, &mut current_hunk);
+                current_hunk = Self::parse_hunk_header(line);
+            } else if line.starts_with("+++") || line.starts_with("---") || line.starts_with("index ") {
+                // Already have the path from the `diff --git` line.
+            } else if let Some(hunk) = current_hunk.as_mut() {
+                if let Some(content) = line.strip_prefix('+') {
+                    hunk.lines.push(DiffLine { kind: "added".to_string(), content: content.to_string() });
+                } else if let Some(content) = line.strip_prefix('-') {
+                    hunk.lines.push(DiffLine { kind: "removed".to_string(), content: content.to_string() });
+                } else if let Some(content) = line.strip_prefix(' ') {
+                    hunk.lines.push(DiffLine { kind: "context".to_string(), content: content.to_string() });
+                }
+                // `\ No newline at end of file` markers are dropped.
+            }
+        }
+
+        flush_hunk(&mut current, &mut current_hunk);
+        if let Some(file) = current.take() {
+            files.push(file);
+        }
+
+        files
+    }
+
+    /// Splits a `diff --git a/<path> b/<path>` line's tail into its two
+    /// paths. Paths containing literal " b/" would defeat this, but that's
+    /// the same tolerance every other string-based parser in this file
+    /// already accepts for git's plumbing output.
+    fn parse_diff_git_paths(rest: &str) -> (Option<String>, Option<String>) {
+        let Some(idx) = rest.find(" b/") else {
+            return (None, None);
+        };
+        let a_path = rest[..idx].strip_prefix("a/").unwrap_or(&rest[..idx]).to_string();
+        let b_path = rest[idx + 3..].to_string();
+        (Some(a_path), Some(b_path))
+    }
+
+    fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+        let inner = line.strip_prefix("@@ ")?;
+        let end = inner.find(" @@")?;
+        let mut ranges = inner[..end].split_whitespace();
+
+        let old_range = ranges.next()?.strip_prefix('-')?;
+        let new_range = ranges.next()?.strip_prefix('+')?;
+        let (old_start, old_lines) = Self::parse_hunk_range(old_range);
+        let (new_start, new_lines) = Self::parse_hunk_range(new_range);
+
+        Some(DiffHunk { old_start, old_lines, new_start, new_lines, lines: Vec::new() })
+    }
+
+    fn parse_hunk_range(range: &str) -> (u32, u32) {
+        let mut parts = range.splitn(2, ',');
+        let start = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let count = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+        (start, count)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,144 @@
+use crate::commands::CommandExecutor;
+use crate::os::OsSession;
+
+/// How a failed git subprocess should be treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    /// A repository corruption signature was recognized; safe to attempt
+    /// escalating, destructive repairs.
+    Corruption,
+    /// Anything else (merge conflicts, auth failures, network errors, ...).
+    /// Never triggers destructive recovery.
+    Other,
+}
+
+/// Signatures that, and only that, indicate repository corruption rather
+/// than a plain merge conflict, auth failure, or transient network error.
+const CORRUPTION_SIGNATURES: &[&str] = &[
+    "fatal: not a git repository",
+    "object file",
+    "is empty",
+    "unable to read",
+    "bad object head",
+    "bad object HEAD",
+    "index.lock",
+];
+
+/// Classifies a git subprocess failure based on its stderr output.
+pub fn classify_failure(stderr: &str) -> FailureClass {
+    let lower = stderr.to_lowercase();
+    let is_corruption = CORRUPTION_SIGNATURES
+        .iter()
+        .any(|signature| lower.contains(&signature.to_lowercase()));
+
+    if is_corruption {
+        FailureClass::Corruption
+    } else {
+        FailureClass::Other
+    }
+}
+
+/// Attempts escalating, non-destructive-to-destructive repairs for a
+/// repository showing corruption signatures:
+/// 1. Remove a stale `index.lock`.
+/// 2. `git reset --hard HEAD`.
+/// 3. Re-fetch and hard-reset to the upstream ref.
+///
+/// Stops at the first step that results in a usable repository (`git
+/// status` succeeding).
+pub fn repair_repository(directory: &str, os_session: &OsSession) -> Result<(), String> {
+    remove_stale_index_lock(directory, os_session);
+
+    if repository_is_usable(directory, os_session) {
+        return Ok(());
+    }
+
+    let _ = CommandExecutor::execute_with_os_session(
+        "git",
+        &["reset", "--hard", "HEAD"],
+        Some(directory),
+        os_session,
+    );
+
+    if repository_is_usable(directory, os_session) {
+        return Ok(());
+    }
+
+    let _ = CommandExecutor::execute_with_os_session(
+        "git",
+        &["fetch", "--all"],
+        Some(directory),
+        os_session,
+    );
+
+    if let Ok(upstream) = CommandExecutor::execute_with_os_session(
+        "git",
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+        Some(directory),
+        os_session,
+    ) {
+        let upstream = upstream.trim();
+        let _ = CommandExecutor::execute_with_os_session(
+            "git",
+            &["reset", "--hard", upstream],
+            Some(directory),
+            os_session,
+        );
+    }
+
+    if repository_is_usable(directory, os_session) {
+        Ok(())
+    } else {
+        Err("REPAIR_FAILED".to_string())
+    }
+}
+
+fn remove_stale_index_lock(directory: &str, os_session: &OsSession) {
+    let git_dir = CommandExecutor::execute_with_os_session(
+        "git",
+        &["rev-parse", "--git-dir"],
+        Some(directory),
+        os_session,
+    )
+    .map(|output| output.trim().to_string())
+    .unwrap_or_else(|_| ".git".to_string());
+
+    let lock_path = format!("{}/index.lock", git_dir);
+    let _ = CommandExecutor::execute_with_os_session(
+        "rm",
+        &["-f", &lock_path],
+        Some(directory),
+        os_session,
+    );
+}
+
+fn repository_is_usable(directory: &str, os_session: &OsSession) -> bool {
+    CommandExecutor::execute_with_os_session(
+        "git",
+        &["status", "--porcelain"],
+        Some(directory),
+        os_session,
+    )
+    .is_ok()
+}
+
+/// Runs `operation`, and if it fails with a recognized corruption
+/// signature, attempts repair and retries the operation exactly once.
+/// Never retries plain merge conflicts or auth/network failures.
+pub fn with_corruption_retry<T>(
+    directory: &str,
+    os_session: &OsSession,
+    operation: impl Fn() -> Result<T, String>,
+) -> Result<T, String> {
+    match operation() {
+        Ok(value) => Ok(value),
+        Err(err) => {
+            if classify_failure(&err) == FailureClass::Corruption {
+                repair_repository(directory, os_session)?;
+                operation()
+            } else {
+                Err(err)
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where an extra set of ignore rules should be loaded from, in addition to
+/// whatever `.gitignore`/`.ignore` files are found while walking the source
+/// tree itself.
+#[derive(Debug, Clone)]
+pub enum IgnoreSource {
+    /// An explicit `.gitignore`-style file, anchored at its parent directory.
+    File(PathBuf),
+    /// The user's global ignore file (e.g. `core.excludesFile`), anchored at
+    /// the copy's source root rather than its own directory.
+    Global(PathBuf),
+    /// Raw gitignore-syntax lines (e.g. `".git/"`, `"target/"`), anchored at
+    /// the copy's source root - used for the built-in exclusions that don't
+    /// come from a file on disk.
+    Inline(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    negated: bool,
+    anchored: bool,
+    dir_only: bool,
+    pattern: String,
+}
+
+fn parse_rules(contents: &str) -> Vec<IgnoreRule> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+
+            // Gitignore anchors a pattern to its directory if it contains a
+            // `/` anywhere but the end (the trailing slash was already
+            // stripped above for `dir_only`, so any remaining `/` counts,
+            // including a leading one).
+            let anchored = line.contains('/');
+            let pattern = line.trim_start_matches('/').to_string();
+
+            if pattern.is_empty() {
+                return None;
+            }
+
+            Some(IgnoreRule {
+                negated,
+                anchored,
+                dir_only,
+                pattern,
+            })
+        })
+        .collect()
+}
+
+/// One `.gitignore`/`.ignore`/global file's rules, anchored at the
+/// directory they apply relative to.
+struct RuleSet {
+    base_dir: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+/// Builds an effective set of gitignore-style exclusion rules by walking
+/// `root` for `.gitignore`/`.ignore` files (plus any `extra_sources`), and
+/// answers whether a given path under `root` is excluded.
+pub struct IgnoreMatcher {
+    root: PathBuf,
+    // Ordered root-to-leaf by directory depth, so nearer files are checked
+    // later - combined with "last matching rule wins" this reproduces
+    // gitignore's "nearest file, last rule" precedence.
+    rule_sets: Vec<RuleSet>,
+}
+
+impl IgnoreMatcher {
+    pub fn build(root: &Path, extra_sources: &[IgnoreSource]) -> Self {
+        let mut rule_sets = Vec::new();
+
+        for source in extra_sources {
+            match source {
+                IgnoreSource::Inline(lines) => {
+                    rule_sets.push(RuleSet {
+                        base_dir: root.to_path_buf(),
+                        rules: parse_rules(&lines.join("\n")),
+                    });
+                }
+                IgnoreSource::File(path) => {
+                    let base_dir = path.parent().unwrap_or(root).to_path_buf();
+                    if let Ok(contents) = fs::read_to_string(path) {
+                        rule_sets.push(RuleSet {
+                            base_dir,
+                            rules: parse_rules(&contents),
+                        });
+                    }
+                }
+                IgnoreSource::Global(path) => {
+                    if let Ok(contents) = fs::read_to_string(path) {
+                        rule_sets.push(RuleSet {
+                            base_dir: root.to_path_buf(),
+                            rules: parse_rules(&contents),
+                        });
+                    }
+                }
+            }
+        }
+
+        collect_tree_rule_sets(root, &mut rule_sets);
+        rule_sets.sort_by_key(|rs| rs.base_dir.components().count());
+
+        Self {
+            root: root.to_path_buf(),
+            rule_sets,
+        }
+    }
+
+    /// True if `path` (which must be under `root`) should be excluded.
+    /// `is_dir` controls whether directory-only (`trailing /`) rules apply.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+
+        for rule_set in &self.rule_sets {
+            let Ok(relative) = path.strip_prefix(&rule_set.base_dir) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            let relative_str = relative.to_string_lossy().replace('\\', "/");
+
+            for rule in &rule_set.rules {
+                if rule.dir_only && !is_dir {
+                    continue;
+                }
+                if rule_matches(rule, &relative_str) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+
+        ignored
+    }
+
+    /// All effective patterns, flattened to plain globs relative to `root`
+    /// with negations resolved away, suitable for handing to an external
+    /// tool that has no negation concept of its own (robocopy, PowerShell).
+    /// This is necessarily an approximation of the full rule set: an
+    /// external tool fed this list can't re-include a file under an
+    /// otherwise-excluded directory the way gitignore's negation can.
+    pub fn effective_exclude_patterns(&self) -> Vec<String> {
+        let mut patterns = Vec::new();
+        for rule_set in &self.rule_sets {
+            for rule in &rule_set.rules {
+                if rule.negated {
+                    continue;
+                }
+                patterns.push(rule.pattern.clone());
+            }
+        }
+        patterns.sort();
+        patterns.dedup();
+        patterns
+    }
+
+    /// Lines for `rsync --exclude-from=-`: rsync's own pattern syntax is
+    /// gitignore-compatible enough (anchoring, trailing `/` for dir-only)
+    /// that the patterns can be re-emitted almost verbatim. A `!` negation
+    /// becomes an explicit `+ ` (include) rule and every other rule a `- `
+    /// (exclude) rule, and the order is reversed, since rsync acts on the
+    /// first matching rule where gitignore lets the last one win.
+    pub fn to_rsync_exclude_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for rule_set in &self.rule_sets {
+            for rule in &rule_set.rules {
+                let mut line = String::from(if rule.negated { "+ " } else { "- " });
+                if rule.anchored {
+                    line.push('/');
+                }
+                line.push_str(&rule.pattern);
+                if rule.dir_only {
+                    line.push('/');
+                }
+                lines.push(line);
+            }
+        }
+        lines.reverse();
+        lines
+    }
+
+    /// `(dir_patterns, file_patterns)` for robocopy's `/XD`/`/XF` flags,
+    /// which have no negation support, so negated rules are dropped.
+    pub fn to_robocopy_excludes(&self) -> (Vec<String>, Vec<String>) {
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for pattern in self.effective_exclude_patterns() {
+            // robocopy matches these patterns against both files and dirs
+            // when passed as-is, but separating dir-only patterns into /XD
+            // keeps behavior closer to gitignore's trailing-`/` semantics.
+            let is_dir_only = self
+                .rule_sets
+                .iter()
+                .flat_map(|rs| &rs.rules)
+                .any(|r| !r.negated && r.dir_only && r.pattern == pattern);
+            if is_dir_only {
+                dirs.push(pattern);
+            } else {
+                files.push(pattern);
+            }
+        }
+        (dirs, files)
+    }
+
+    /// Flattened pattern list for PowerShell `Copy-Item -Exclude`.
+    pub fn to_powershell_exclude_list(&self) -> Vec<String> {
+        self.effective_exclude_patterns()
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+fn rule_matches(rule: &IgnoreRule, relative_str: &str) -> bool {
+    if rule.anchored {
+        glob_match(&rule.pattern, relative_str)
+            || relative_str.starts_with(&format!("{}/", rule.pattern))
+    } else {
+        relative_str
+            .split('/')
+            .any(|segment| glob_match(&rule.pattern, segment))
+            || glob_match(&rule.pattern, relative_str)
+    }
+}
+
+/// Minimal glob matcher supporting `*` and `?`, enough for the gitignore
+/// patterns actually seen in the wild - no `**` support, since none of our
+/// callers need to match across path separators within a single segment.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn helper(pattern: &[u8], text: &[u8]) -> bool {
+        match (pattern.first(), text.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => helper(&pattern[1..], text) || (!text.is_empty() && helper(pattern, &text[1..])),
+            (Some(b'?'), Some(_)) => helper(&pattern[1..], &text[1..]),
+            (Some(p), Some(t)) if p == t => helper(&pattern[1..], &text[1..]),
+            _ => false,
+        }
+    }
+    helper(pattern.as_bytes(), text.as_bytes())
+}
+
+/// Walks `dir` looking for `.gitignore`/`.ignore` files, recording one
+/// `RuleSet` per file found, anchored at the directory it lives in.
+fn collect_tree_rule_sets(dir: &Path, rule_sets: &mut Vec<RuleSet>) {
+    for name in [".gitignore", ".ignore"] {
+        let candidate = dir.join(name);
+        if let Ok(contents) = fs::read_to_string(&candidate) {
+            rule_sets.push(RuleSet {
+                base_dir: dir.to_path_buf(),
+                rules: parse_rules(&contents),
+            });
+        }
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_tree_rule_sets(&path, rule_sets);
+        }
+    }
+}
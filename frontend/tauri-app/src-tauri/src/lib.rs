@@ -6,7 +6,7 @@
 
 use std::sync::Arc;
 use std::path::Path;
-use tauri::State;
+use tauri::{Emitter, State};
 
 mod terminal;
 use terminal::TerminalManager;
@@ -17,8 +17,18 @@ mod custom_terminal_commands;
 mod os;
 mod commands;
 mod git;
+mod cargo_deps;
 mod filesystem;
+mod ignore;
+mod path_translation;
 mod system;
+mod logging;
+mod watch;
+mod file_watcher;
+mod pty;
+mod webhook;
+
+use logging::set_log_level;
 
 use custom_terminal_commands::{
 	custom_connect_terminal, custom_kill_terminal, custom_resize_terminal,
@@ -33,9 +43,15 @@ use crate::{
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+	logging::init();
+
 	let terminals_manager = Arc::new(TerminalManager::new());
 	let custom_terminals_manager = Arc::new(CustomTerminalManager::new());
 	let git_search_manager = Arc::new(GitSearchManager::new());
+	let sync_watches = Arc::new(watch::SyncWatches::new());
+	let file_watchers = Arc::new(file_watcher::FileWatchers::new());
+	let pty_manager = Arc::new(pty::PtyManager::new());
+	let webhook_manager = Arc::new(webhook::WebhookManager::new());
 
 	tauri::Builder::default()
 		.plugin(tauri_plugin_os::init())
@@ -46,6 +62,10 @@ pub fn run() {
 		.manage(terminals_manager)
 		.manage(custom_terminals_manager)
 		.manage(git_search_manager)
+		.manage(sync_watches)
+		.manage(file_watchers)
+		.manage(pty_manager)
+		.manage(webhook_manager)
 		.invoke_handler(tauri::generate_handler![
 			// Original terminal commands
 			create_terminal_connection,
@@ -74,6 +94,19 @@ pub fn run() {
 			// Canvas management commands
 			copy_files_optimized,
 			get_copy_stats,
+			start_watch_and_sync,
+			stop_watch_and_sync,
+			start_project_watch,
+			stop_project_watch,
+			start_pty,
+			write_pty_stdin,
+			resize_pty,
+			signal_pty,
+			close_pty,
+			start_github_webhook_server,
+			register_webhook_repo,
+			list_webhook_jobs,
+			rerun_webhook_job,
 			get_git_hash,
 			create_git_branch,
 			execute_command,
@@ -82,6 +115,10 @@ pub fn run() {
 			// System integration commands
 			open_path_in_explorer,
 			open_path_in_explorer_with_os_session,
+			open_path,
+			open_path_with_os_session,
+			open_url,
+			open_url_with_os_session,
 			delete_path,
 			delete_path_with_os_session,
 			// Git repository commands
@@ -93,8 +130,18 @@ pub fn run() {
 			git_check_merge_conflicts,
 			git_get_conflict_files,
 			git_merge_branch,
+			git_get_status,
+			git_get_diff,
 			git_get_current_branch,
 			git_get_origin_url,
+			git_create_worktree,
+			git_list_worktrees,
+			git_remove_worktree,
+			git_repair_repository,
+			set_log_level,
+			git_fetch,
+			git_pull_fast_forward,
+			git_get_default_branch,
 		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
@@ -173,24 +220,213 @@ async fn get_file_tree(
 		.map_err(|e| e.to_string())
 }
 
+/// Builds the extra `IgnoreSource`s requested over IPC: explicit
+/// `.gitignore`-style files plus an optional global ignore file, on top of
+/// whatever `.gitignore`/`.ignore` files `FileSystemManager` finds while
+/// walking the source tree itself.
+fn build_ignore_sources(extra_ignore_files: Option<Vec<String>>, global_ignore_file: Option<String>) -> Vec<ignore::IgnoreSource> {
+	let mut sources = Vec::new();
+	for path in extra_ignore_files.unwrap_or_default() {
+		sources.push(ignore::IgnoreSource::File(Path::new(&path).to_path_buf()));
+	}
+	if let Some(path) = global_ignore_file {
+		sources.push(ignore::IgnoreSource::Global(Path::new(&path).to_path_buf()));
+	}
+	sources
+}
+
+/// Builds a `CopyOptions` from the flat IPC-friendly fields `copy_files_optimized`
+/// accepts: `backup_mode` is one of `"none"` (default), `"simple"`, or
+/// `"numbered"`; `backup_suffix` only applies to `"simple"` (defaults to `~`,
+/// matching coreutils `install`).
+fn build_copy_options(preserve_attributes: Option<bool>, backup_mode: Option<String>, backup_suffix: Option<String>) -> filesystem::CopyOptions {
+	let backup = match backup_mode.as_deref() {
+		Some("simple") => filesystem::BackupMode::Simple { suffix: backup_suffix.unwrap_or_else(|| "~".to_string()) },
+		Some("numbered") => filesystem::BackupMode::Numbered,
+		_ => filesystem::BackupMode::None,
+	};
+	filesystem::CopyOptions {
+		preserve_attributes: preserve_attributes.unwrap_or(true),
+		backup,
+	}
+}
+
 #[tauri::command]
 async fn copy_files_optimized(
-	source: String, 
-	destination: String, 
+	source: String,
+	destination: String,
 	os_session: OsSession,
-	exclude_git: Option<bool>
+	exclude_git: Option<bool>,
+	extra_ignore_files: Option<Vec<String>>,
+	global_ignore_file: Option<String>,
+	preserve_attributes: Option<bool>,
+	backup_mode: Option<String>,
+	backup_suffix: Option<String>,
 ) -> Result<(), String> {
 	let should_exclude = exclude_git.unwrap_or(false);
-	filesystem::FileSystemManager::copy_files_optimized(&source, &destination, &os_session, should_exclude)
+	let exclude_rules = build_ignore_sources(extra_ignore_files, global_ignore_file);
+	let options = build_copy_options(preserve_attributes, backup_mode, backup_suffix);
+	filesystem::FileSystemManager::copy_files_optimized(&source, &destination, &os_session, should_exclude, &exclude_rules, &options)
 }
 
 #[tauri::command]
 async fn get_copy_stats(
-	source: String, 
-	destination: String, 
-	os_session: OsSession
+	source: String,
+	destination: String,
+	os_session: OsSession,
+	exclude_git: Option<bool>,
+	extra_ignore_files: Option<Vec<String>>,
+	global_ignore_file: Option<String>,
 ) -> Result<serde_json::Value, String> {
-	filesystem::FileSystemManager::get_copy_stats(&source, &destination, &os_session)
+	let should_exclude = exclude_git.unwrap_or(false);
+	let exclude_rules = build_ignore_sources(extra_ignore_files, global_ignore_file);
+	filesystem::FileSystemManager::get_copy_stats(&source, &destination, &os_session, should_exclude, &exclude_rules)
+}
+
+/// Performs an initial copy, then watches `source` for changes and
+/// propagates them to `destination` until `stop_watch_and_sync` is called
+/// with the returned watch id.
+#[tauri::command]
+async fn start_watch_and_sync(
+	source: String,
+	destination: String,
+	os_session: OsSession,
+	exclude_git: Option<bool>,
+	extra_ignore_files: Option<Vec<String>>,
+	global_ignore_file: Option<String>,
+	preserve_attributes: Option<bool>,
+	backup_mode: Option<String>,
+	backup_suffix: Option<String>,
+	app_handle: tauri::AppHandle,
+	sync_watches: State<'_, Arc<watch::SyncWatches>>,
+) -> Result<String, String> {
+	let should_exclude = exclude_git.unwrap_or(false);
+	let exclude_rules = build_ignore_sources(extra_ignore_files, global_ignore_file);
+	let options = build_copy_options(preserve_attributes, backup_mode, backup_suffix);
+	watch::start_watch(source, destination, os_session, should_exclude, exclude_rules, options, app_handle, &sync_watches)
+}
+
+#[tauri::command]
+async fn stop_watch_and_sync(
+	watch_id: String,
+	sync_watches: State<'_, Arc<watch::SyncWatches>>,
+) -> Result<(), String> {
+	watch::stop_watch(&watch_id, &sync_watches)
+}
+
+/// Watches `path` for create/modify/delete/rename events and emits them as
+/// `project-file-change`, without mirroring anything to a destination -
+/// for callers (editor integrations, the explorer view) that just want to
+/// know a project directory changed, until `stop_project_watch` is called
+/// with the returned watch id.
+#[tauri::command]
+async fn start_project_watch(
+	path: String,
+	os_session: OsSession,
+	app_handle: tauri::AppHandle,
+	file_watchers: State<'_, Arc<file_watcher::FileWatchers>>,
+) -> Result<String, String> {
+	file_watcher::start_project_watch(path, os_session, app_handle, &file_watchers)
+}
+
+#[tauri::command]
+async fn stop_project_watch(
+	watch_id: String,
+	file_watchers: State<'_, Arc<file_watcher::FileWatchers>>,
+) -> Result<(), String> {
+	file_watcher::stop_project_watch(&watch_id, &file_watchers)
+}
+
+/// Allocates a PTY against `os_session` and starts an interactive shell in
+/// it, streaming raw output on `pty-output-<id>` and an exit marker on
+/// `pty-exit-<id>`. Returns the session id to pass to the other pty_*
+/// commands.
+#[tauri::command]
+async fn start_pty(
+	os_session: OsSession,
+	cols: u16,
+	rows: u16,
+	app_handle: tauri::AppHandle,
+	pty_manager: State<'_, Arc<pty::PtyManager>>,
+) -> Result<String, String> {
+	pty_manager.start(os_session, cols, rows, app_handle).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn write_pty_stdin(
+	session_id: String,
+	data: Vec<u8>,
+	pty_manager: State<'_, Arc<pty::PtyManager>>,
+) -> Result<(), String> {
+	pty_manager.write_stdin(&session_id, &data).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn resize_pty(
+	session_id: String,
+	cols: u16,
+	rows: u16,
+	pty_manager: State<'_, Arc<pty::PtyManager>>,
+) -> Result<(), String> {
+	pty_manager.resize(&session_id, cols, rows).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn signal_pty(
+	session_id: String,
+	signal: String,
+	pty_manager: State<'_, Arc<pty::PtyManager>>,
+) -> Result<(), String> {
+	pty_manager.signal(&session_id, &signal).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn close_pty(
+	session_id: String,
+	pty_manager: State<'_, Arc<pty::PtyManager>>,
+) -> Result<(), String> {
+	pty_manager.close(&session_id).map_err(|e| e.to_string())
+}
+
+/// Starts listening for signed GitHub push webhooks on `127.0.0.1:<port>`.
+/// Repositories must be registered with `register_webhook_repo` first -
+/// pushes for an unregistered repository are rejected outright.
+#[tauri::command]
+async fn start_github_webhook_server(
+	port: u16,
+	app_handle: tauri::AppHandle,
+	webhook_manager: State<'_, Arc<webhook::WebhookManager>>,
+) -> Result<(), String> {
+	webhook::start_server(webhook_manager.inner().clone(), app_handle, port);
+	Ok(())
+}
+
+#[tauri::command]
+async fn register_webhook_repo(
+	full_name: String,
+	workspace_dir: String,
+	secret: String,
+	webhook_manager: State<'_, Arc<webhook::WebhookManager>>,
+) -> Result<(), String> {
+	webhook_manager.register_repo(full_name, workspace_dir, secret);
+	Ok(())
+}
+
+#[tauri::command]
+async fn list_webhook_jobs(
+	app_handle: tauri::AppHandle,
+	webhook_manager: State<'_, Arc<webhook::WebhookManager>>,
+) -> Result<Vec<webhook::WebhookJob>, String> {
+	Ok(webhook_manager.list_jobs(&app_handle))
+}
+
+#[tauri::command]
+async fn rerun_webhook_job(
+	job_id: String,
+	app_handle: tauri::AppHandle,
+	webhook_manager: State<'_, Arc<webhook::WebhookManager>>,
+) -> Result<(), String> {
+	webhook_manager.rerun_job(app_handle, job_id)
 }
 
 #[tauri::command]
@@ -211,6 +447,30 @@ async fn open_path_in_explorer_with_os_session(path: String, os_session: OsSessi
 	filesystem::FileSystemManager::open_in_explorer_with_os_session(&path, &os_session)
 }
 
+/// Launches `path` in its system default handler (not a file manager
+/// window) - the local-only sibling of `open_path_with_os_session`.
+#[tauri::command]
+async fn open_path(path: String) -> Result<(), String> {
+	filesystem::FileSystemManager::open_path(&path)
+}
+
+#[tauri::command]
+async fn open_path_with_os_session(path: String, os_session: OsSession) -> Result<(), String> {
+	filesystem::FileSystemManager::open_path_with_os_session(&path, &os_session)
+}
+
+/// Opens `url` in the user's default browser, honoring a `$BROWSER`
+/// environment variable override.
+#[tauri::command]
+async fn open_url(url: String) -> Result<(), String> {
+	filesystem::FileSystemManager::open_url(&url)
+}
+
+#[tauri::command]
+async fn open_url_with_os_session(url: String, os_session: OsSession) -> Result<(), String> {
+	filesystem::FileSystemManager::open_url_with_os_session(&url, &os_session)
+}
+
 #[tauri::command]
 async fn delete_path(path: String) -> Result<(), String> {
 	system::SystemManager::delete_path_simple(&path)
@@ -288,6 +548,46 @@ async fn git_get_origin_url(directory: String, os_session: OsSession) -> Result<
 	git::GitManager::get_origin_url(&directory, &os_session)
 }
 
+#[tauri::command]
+async fn git_create_worktree(
+	directory: String,
+	branch_name: String,
+	worktree_path: String,
+	os_session: OsSession,
+) -> Result<(), String> {
+	git::GitManager::create_worktree(&directory, &branch_name, &worktree_path, &os_session)
+}
+
+#[tauri::command]
+async fn git_list_worktrees(directory: String, os_session: OsSession) -> Result<Vec<serde_json::Value>, String> {
+	git::GitManager::list_worktrees(&directory, &os_session)
+}
+
+#[tauri::command]
+async fn git_remove_worktree(worktree_path: String, force: bool, os_session: OsSession) -> Result<(), String> {
+	git::GitManager::remove_worktree(&worktree_path, force, &os_session)
+}
+
+#[tauri::command]
+async fn git_repair_repository(directory: String, os_session: OsSession) -> Result<(), String> {
+	git::GitManager::repair_repository(&directory, &os_session)
+}
+
+#[tauri::command]
+async fn git_fetch(directory: String, remote: String, os_session: OsSession) -> Result<(), String> {
+	git::GitManager::fetch(&directory, &remote, &os_session)
+}
+
+#[tauri::command]
+async fn git_pull_fast_forward(directory: String, os_session: OsSession) -> Result<String, String> {
+	git::GitManager::pull_fast_forward(&directory, &os_session)
+}
+
+#[tauri::command]
+async fn git_get_default_branch(directory: String, os_session: OsSession) -> Result<String, String> {
+	git::GitManager::get_default_branch(&directory, &os_session)
+}
+
 #[tauri::command]
 async fn git_check_merge_conflicts(
 	directory: String,
@@ -313,6 +613,16 @@ async fn git_merge_branch(
 	git::GitManager::merge_branch(&directory, &source_branch, &target_branch, &os_session)
 }
 
+#[tauri::command]
+async fn git_get_status(directory: String, os_session: OsSession) -> Result<Vec<git::GitStatusEntry>, String> {
+	git::GitManager::get_status(&directory, &os_session)
+}
+
+#[tauri::command]
+async fn git_get_diff(directory: String, rev_or_worktree: String, os_session: OsSession) -> Result<Vec<git::FileDiff>, String> {
+	git::GitManager::get_diff(&directory, &rev_or_worktree, &os_session)
+}
+
 // ===== GIT SEARCH =====
 
 #[tauri::command]
@@ -327,38 +637,54 @@ async fn start_git_directories_search(
 #[tauri::command]
 async fn get_found_git_directories_so_far(
 	search_id: String,
+	app_handle: tauri::AppHandle,
 	git_search_manager: State<'_, Arc<GitSearchManager>>,
 ) -> Result<GitSearchResult, String> {
 	let mut result = git_search_manager
 		.get_results(&search_id)
 		.ok_or_else(|| "Search ID not found".to_string())?;
-	
-	println!("Backend - Raw search results before filtering: {} directories", result.directories.len());
-	
-	// Filter out deleted directories using appropriate method for each path type
+
+	tracing::debug!(target: "git_search", count = result.directories.len(), "raw search results before filtering");
+
+	// Filter out deleted directories using appropriate method for each path type.
+	// Local paths are checked in-process; WSL paths are validated in a single
+	// wsl.exe invocation instead of one subprocess per path.
 	let original_count = result.directories.len();
+	let is_wsl_path = |path: &str| path.starts_with("/mnt/") || (path.starts_with("/home") && cfg!(target_os = "windows"));
+
+	let wsl_paths: Vec<String> = result.directories.iter().filter(|path| is_wsl_path(path)).cloned().collect();
+	let wsl_exists = system::SystemManager::check_wsl_paths_exist(&wsl_paths);
+	let mut wsl_exists = wsl_exists.into_iter();
+
 	let mut filtered_dirs = Vec::new();
-	
 	for path in &result.directories {
-		let exists = if path.starts_with("/mnt/") || (path.starts_with("/home") && cfg!(target_os = "windows")) {
-			// WSL path - check existence using WSL command
-			system::SystemManager::check_wsl_path_exists(path)
+		let exists = if is_wsl_path(path) {
+			wsl_exists.next().unwrap_or(false)
 		} else {
 			// Local path - use standard filesystem check
 			let path_obj = Path::new(path);
 			path_obj.exists() && path_obj.is_dir()
 		};
-		
+
 		if exists {
 			filtered_dirs.push(path.clone());
 		} else {
-			println!("Backend - Filtering out non-existent directory: {}", path);
+			tracing::debug!(target: "git_search", %path, "filtering out non-existent directory");
 		}
 	}
-	
+
 	result.directories = filtered_dirs;
-	println!("Backend - After existence filtering: {} directories (removed {})", result.directories.len(), original_count - result.directories.len());
-	
+	let removed = original_count - result.directories.len();
+	tracing::debug!(target: "git_search", count = result.directories.len(), removed, "after existence filtering");
+
+	let _ = app_handle.emit(
+		&format!("git-search-progress-{}", search_id),
+		serde_json::json!({
+			"directoriesFound": result.directories.len(),
+			"removed": removed,
+		}),
+	);
+
 	Ok(result)
 }
 
@@ -0,0 +1,47 @@
+use std::sync::OnceLock;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{reload, EnvFilter};
+
+type ReloadHandle = reload::Handle<EnvFilter, tracing_subscriber::Registry>;
+
+static RELOAD_HANDLE: OnceLock<ReloadHandle> = OnceLock::new();
+
+/// Installs the `tracing` subscriber for the app. Verbose (`debug`-level and
+/// below) output is only emitted when the `debug` Cargo feature is enabled;
+/// release builds stay quiet unless `set_log_level` raises the verbosity at
+/// runtime.
+pub fn init() {
+    let default_filter = if cfg!(feature = "debug") {
+        "git=debug,terminal=debug,filesystem=debug,git_search=debug"
+    } else {
+        "warn"
+    };
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        // Already initialized (e.g. in tests); nothing to do.
+        return;
+    }
+
+    let _ = RELOAD_HANDLE.set(handle);
+}
+
+/// Bumps (or lowers) log verbosity at runtime, e.g. from a frontend debug panel.
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&level).map_err(|e| format!("Invalid log level '{}': {}", level, e))?;
+
+    let handle = RELOAD_HANDLE
+        .get()
+        .ok_or_else(|| "Logging subsystem not initialized".to_string())?;
+
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to apply log level: {}", e))
+}
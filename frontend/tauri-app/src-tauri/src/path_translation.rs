@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+/// Converts a WSL-guest path to the equivalent Windows path, usable by
+/// Windows-side tools like `explorer.exe`. `/mnt/<drive>/...` paths
+/// (drive letter of any case) map to `<DRIVE>:\...`; anything else is
+/// assumed to live inside the WSL guest's own filesystem and is addressed
+/// through the `\\wsl.localhost\<distribution>\` UNC share.
+pub fn wsl_to_windows(path: &str, distribution: &str) -> PathBuf {
+    let components = posix_components(path);
+
+    if components.len() >= 2 && components[0].eq_ignore_ascii_case("mnt") && is_drive_letter(components[1]) {
+        let drive = components[1].to_ascii_uppercase();
+        return PathBuf::from(join_windows(&format!("{}:", drive), &components[2..]));
+    }
+
+    PathBuf::from(join_windows(&format!("\\\\wsl.localhost\\{}", distribution), &components))
+}
+
+/// Converts a Windows path to the equivalent WSL path. Drive-letter paths
+/// (`C:\...`, any case) map to `/mnt/<drive>/...`; both UNC forms WSL
+/// exposes (`\\wsl$\<distro>\...` and the newer `\\wsl.localhost\<distro>\...`)
+/// map to the guest-rooted `/...` path, dropping the distribution segment
+/// (the caller already knows which distribution it's talking to). Anything
+/// else is returned with separators flipped, best effort.
+pub fn windows_to_wsl(path: &str) -> PathBuf {
+    let trimmed = path.trim_end_matches(['\\', '/']);
+
+    if let Some(rest) = strip_unc_prefix(trimmed, "\\\\wsl.localhost\\") {
+        return unc_rest_to_wsl_path(rest);
+    }
+    if let Some(rest) = strip_unc_prefix(trimmed, "\\\\wsl$\\") {
+        return unc_rest_to_wsl_path(rest);
+    }
+
+    let mut chars = trimmed.chars();
+    if let (Some(drive), Some(':')) = (chars.next(), chars.next()) {
+        if drive.is_ascii_alphabetic() {
+            let rest = trimmed[2..].trim_start_matches(['\\', '/']).replace('\\', "/");
+            let drive_lower = drive.to_ascii_lowercase();
+            return if rest.is_empty() {
+                PathBuf::from(format!("/mnt/{}", drive_lower))
+            } else {
+                PathBuf::from(format!("/mnt/{}/{}", drive_lower, rest))
+            };
+        }
+    }
+
+    PathBuf::from(trimmed.replace('\\', "/"))
+}
+
+fn is_drive_letter(segment: &str) -> bool {
+    segment.len() == 1 && segment.chars().next().map(|c| c.is_ascii_alphabetic()).unwrap_or(false)
+}
+
+/// Splits a POSIX-style path into its non-empty components, tolerating a
+/// trailing separator.
+fn posix_components(path: &str) -> Vec<&str> {
+    path.trim_end_matches('/').split('/').filter(|c| !c.is_empty()).collect()
+}
+
+fn join_windows(prefix: &str, components: &[&str]) -> String {
+    if components.is_empty() {
+        format!("{}\\", prefix)
+    } else {
+        format!("{}\\{}", prefix, components.join("\\"))
+    }
+}
+
+fn strip_unc_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if path.len() >= prefix.len() && path[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        Some(&path[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// `rest` is `<distribution>\rest\of\path` (from either UNC form) - drop
+/// the distribution segment and return the guest-rooted POSIX path.
+fn unc_rest_to_wsl_path(rest: &str) -> PathBuf {
+    let mut parts = rest.splitn(2, '\\');
+    let _distribution = parts.next();
+    let remainder = parts.next().unwrap_or("").replace('\\', "/");
+
+    if remainder.is_empty() {
+        PathBuf::from("/")
+    } else {
+        PathBuf::from(format!("/{}", remainder))
+    }
+}
+
+#[cfg(test)]
+mod round_trip_tests {
+    use super::*;
+
+    const DISTRIBUTION: &str = "Ubuntu";
+
+    // (wsl path, expected windows path) - `wsl_to_windows` is checked
+    // directly against the expected path, then fed back through
+    // `windows_to_wsl` to confirm it lands back on the original.
+    const ROUND_TRIP_CASES: &[(&str, &str)] = &[
+        ("/mnt/c/Users/alice/project", "C:\\Users\\alice\\project"),
+        ("/mnt/d/Games", "D:\\Games"),
+        ("/mnt/c", "C:\\"),
+        ("/home/alice/project", "\\\\wsl.localhost\\Ubuntu\\home\\alice\\project"),
+        ("/", "\\\\wsl.localhost\\Ubuntu\\"),
+    ];
+
+    #[test]
+    fn wsl_to_windows_round_trip_table() {
+        for (wsl_path, expected_windows_path) in ROUND_TRIP_CASES {
+            let windows_path = wsl_to_windows(wsl_path, DISTRIBUTION);
+            assert_eq!(
+                windows_path.to_str().unwrap(),
+                *expected_windows_path,
+                "wsl_to_windows({:?}) mismatch",
+                wsl_path
+            );
+
+            let back = windows_to_wsl(expected_windows_path);
+            assert_eq!(
+                back.to_str().unwrap(),
+                *wsl_path,
+                "windows_to_wsl({:?}) did not round-trip",
+                expected_windows_path
+            );
+        }
+    }
+
+    #[test]
+    fn windows_to_wsl_accepts_both_unc_forms() {
+        let wsl_localhost = windows_to_wsl("\\\\wsl.localhost\\Ubuntu\\home\\alice\\project");
+        let wsl_dollar = windows_to_wsl("\\\\wsl$\\Ubuntu\\home\\alice\\project");
+        assert_eq!(wsl_localhost.to_str().unwrap(), "/home/alice/project");
+        assert_eq!(wsl_dollar.to_str().unwrap(), "/home/alice/project");
+    }
+
+    #[test]
+    fn windows_to_wsl_lowercases_drive_letter() {
+        assert_eq!(windows_to_wsl("D:\\Games").to_str().unwrap(), "/mnt/d/Games");
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::{anyhow, Result};
+use portable_pty::{Child, CommandBuilder, PtyPair, PtySize};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::os::OsSession;
+
+/// One signal `signal_pty` can deliver. PTYs don't carry real OS signals
+/// over the wire the way a direct process handle would, so these are
+/// approximated the way terminal emulators actually do it: `Interrupt` and
+/// `Quit` write the default control character (Ctrl-C, 0x03, and Ctrl-\,
+/// 0x1c), `Kill` tears the child down outright.
+pub enum PtySignal {
+	Interrupt,
+	Quit,
+	Kill,
+}
+
+impl PtySignal {
+	fn parse(name: &str) -> Result<Self> {
+		match name {
+			"SIGINT" | "interrupt" => Ok(PtySignal::Interrupt),
+			"SIGQUIT" | "quit" => Ok(PtySignal::Quit),
+			"SIGKILL" | "SIGTERM" | "kill" => Ok(PtySignal::Kill),
+			other => Err(anyhow!("Unsupported PTY signal: {}", other)),
+		}
+	}
+}
+
+pub struct PtySession {
+	pub id: String,
+	pty_pair: PtyPair,
+	child: Box<dyn Child + Send + Sync>,
+}
+
+impl PtySession {
+	fn spawn(id: String, os_session: &OsSession, cols: u16, rows: u16) -> Result<Self> {
+		let pty_system = portable_pty::native_pty_system();
+		let pty_pair = pty_system.openpty(PtySize {
+			rows,
+			cols,
+			pixel_width: 0,
+			pixel_height: 0,
+		})?;
+
+		let cmd = build_command(os_session)?;
+		let child = pty_pair.slave.spawn_command(cmd)?;
+
+		Ok(Self { id, pty_pair, child })
+	}
+
+	fn is_alive(&mut self) -> bool {
+		matches!(self.child.try_wait(), Ok(None))
+	}
+
+	fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+		self.pty_pair.master.resize(PtySize {
+			rows,
+			cols,
+			pixel_width: 0,
+			pixel_height: 0,
+		})?;
+		Ok(())
+	}
+}
+
+/// Picks the command a PTY session spawns for `os_session`: the user's
+/// default shell for a local session, or `wsl.exe -d <distribution>` (which
+/// drops straight into that distro's login shell) for a WSL one. There's no
+/// SSH variant of `OsSession` in this app.
+fn build_command(os_session: &OsSession) -> Result<CommandBuilder> {
+	match os_session {
+		OsSession::Local(working_directory) => {
+			let mut cmd = {
+				#[cfg(any(target_os = "macos", target_os = "linux"))]
+				{
+					let shell_path = std::env::var("SHELL").unwrap_or_else(|_| {
+						if std::path::Path::new("/bin/zsh").exists() {
+							"/bin/zsh".to_string()
+						} else if std::path::Path::new("/bin/bash").exists() {
+							"/bin/bash".to_string()
+						} else {
+							"/bin/sh".to_string()
+						}
+					});
+					let mut cmd = CommandBuilder::new(shell_path);
+					cmd.arg("-l");
+					cmd
+				}
+				#[cfg(target_os = "windows")]
+				{
+					let mut cmd = CommandBuilder::new("powershell.exe");
+					cmd.arg("-NoExit");
+					cmd
+				}
+			};
+			cmd.cwd(working_directory);
+			cmd.env("TERM", "xterm-256color");
+			Ok(cmd)
+		}
+		#[cfg(target_os = "windows")]
+		OsSession::Wsl(wsl_session) => {
+			let mut cmd = CommandBuilder::new("wsl.exe");
+			cmd.arg("-d");
+			cmd.arg(&wsl_session.distribution);
+			cmd.env("TERM", "xterm-256color");
+			Ok(cmd)
+		}
+		#[cfg(not(target_os = "windows"))]
+		OsSession::Wsl(_) => Err(anyhow!("WSL is only supported on Windows")),
+	}
+}
+
+fn start_io_loop(session_id: String, pty_pair: &PtyPair, app_handle: AppHandle) -> Result<()> {
+	let mut reader = pty_pair.master.try_clone_reader()?;
+
+	thread::spawn(move || {
+		let mut buffer = [0u8; 4096];
+		loop {
+			match reader.read(&mut buffer) {
+				Ok(0) => break,
+				Ok(n) => {
+					let _ = app_handle.emit(&format!("pty-output-{}", session_id), &buffer[..n]);
+				}
+				Err(_) => break,
+			}
+		}
+		let _ = app_handle.emit(&format!("pty-exit-{}", session_id), ());
+	});
+
+	Ok(())
+}
+
+/// Tracks live PTY sessions, keyed by the id `start_pty` returns.
+#[derive(Default)]
+pub struct PtyManager {
+	sessions: Arc<Mutex<HashMap<String, PtySession>>>,
+	writers: Arc<Mutex<HashMap<String, Box<dyn Write + Send>>>>,
+}
+
+impl PtyManager {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Allocates a pseudo-terminal against `os_session` and starts its shell,
+	/// streaming raw output bytes on `pty-output-<id>` and an exit marker on
+	/// `pty-exit-<id>` once the child terminates. Returns the session id to
+	/// pass to `write_stdin`/`resize`/`signal`/`close`.
+	pub fn start(&self, os_session: OsSession, cols: u16, rows: u16, app_handle: AppHandle) -> Result<String> {
+		let session_id = Uuid::new_v4().to_string();
+		let session = PtySession::spawn(session_id.clone(), &os_session, cols, rows)?;
+
+		let writer = session.pty_pair.master.take_writer()?;
+		start_io_loop(session_id.clone(), &session.pty_pair, app_handle)?;
+
+		self.sessions.lock().unwrap().insert(session_id.clone(), session);
+		self.writers.lock().unwrap().insert(session_id.clone(), writer);
+
+		Ok(session_id)
+	}
+
+	pub fn write_stdin(&self, session_id: &str, data: &[u8]) -> Result<()> {
+		let mut writers = self.writers.lock().unwrap();
+		let writer = writers
+			.get_mut(session_id)
+			.ok_or_else(|| anyhow!("PTY session not found: {}", session_id))?;
+		writer.write_all(data)?;
+		writer.flush()?;
+		Ok(())
+	}
+
+	pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<()> {
+		let mut sessions = self.sessions.lock().unwrap();
+		let session = sessions
+			.get_mut(session_id)
+			.ok_or_else(|| anyhow!("PTY session not found: {}", session_id))?;
+		session.resize(cols, rows)
+	}
+
+	pub fn signal(&self, session_id: &str, signal_name: &str) -> Result<()> {
+		let signal = PtySignal::parse(signal_name)?;
+		match signal {
+			PtySignal::Interrupt => self.write_stdin(session_id, &[0x03]), // Ctrl-C
+			PtySignal::Quit => self.write_stdin(session_id, &[0x1c]),      // Ctrl-\
+			PtySignal::Kill => {
+				let mut sessions = self.sessions.lock().unwrap();
+				let session = sessions
+					.get_mut(session_id)
+					.ok_or_else(|| anyhow!("PTY session not found: {}", session_id))?;
+				session.child.kill().map_err(|e| anyhow!("Failed to kill PTY child: {}", e))
+			}
+		}
+	}
+
+	pub fn is_alive(&self, session_id: &str) -> Result<bool> {
+		let mut sessions = self.sessions.lock().unwrap();
+		let session = sessions
+			.get_mut(session_id)
+			.ok_or_else(|| anyhow!("PTY session not found: {}", session_id))?;
+		Ok(session.is_alive())
+	}
+
+	pub fn close(&self, session_id: &str) -> Result<()> {
+		if let Some(mut writer) = self.writers.lock().unwrap().remove(session_id) {
+			let _ = writer.flush();
+		}
+
+		if let Some(mut session) = self.sessions.lock().unwrap().remove(session_id) {
+			let _ = session.child.kill();
+			let _ = session.child.wait();
+			drop(session.pty_pair);
+		}
+
+		Ok(())
+	}
+}
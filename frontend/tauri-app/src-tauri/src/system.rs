@@ -1,5 +1,7 @@
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::path::Path;
+use std::io::Read;
+use std::time::{Duration, Instant};
 use crate::os::OsSessionKind;
 
 /// System integration operations
@@ -56,6 +58,123 @@ impl SystemManager {
         }
     }
 
+    /// Like `execute_command`, but pipes `stdin_data` to the child's stdin
+    /// before reading its output - for commands like `rsync
+    /// --exclude-from=-` that read a pattern list from standard input.
+    pub fn execute_command_with_stdin(command: &str, args: &[&str], stdin_data: &str) -> Result<String, String> {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        #[cfg(target_os = "windows")]
+        let mut child = {
+            use std::os::windows::process::CommandExt;
+            Command::new(command)
+                .args(args)
+                .creation_flags(0x08000000) // CREATE_NO_WINDOW
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Failed to execute command: {}", e))?
+        };
+
+        #[cfg(not(target_os = "windows"))]
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| "Failed to open child stdin".to_string())?
+            .write_all(stdin_data.as_bytes())
+            .map_err(|e| format!("Failed to write to child stdin: {}", e))?;
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to wait for command: {}", e))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).to_string())
+        }
+    }
+
+    /// Like `execute_command_in_dir`, but bounds how long the child may run.
+    /// Stdout/stderr are piped and drained on background threads (so a full
+    /// pipe buffer can't deadlock the child before the deadline), and if the
+    /// child hasn't exited by `timeout` it's killed and reaped - never left
+    /// as a zombie - and a distinct timeout error is returned instead of the
+    /// child's output. `directory` of `None` runs in the current directory.
+    pub fn execute_command_with_timeout(
+        command: &str,
+        args: &[&str],
+        directory: Option<&str>,
+        timeout: Duration,
+    ) -> Result<String, String> {
+        let mut builder = Command::new(command);
+        builder.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        if let Some(directory) = directory {
+            builder.current_dir(directory);
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            builder.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+
+        let mut child = builder.spawn().map_err(|e| format!("Failed to execute command: {}", e))?;
+
+        let mut stdout_pipe = child.stdout.take().ok_or_else(|| "Failed to capture stdout".to_string())?;
+        let mut stderr_pipe = child.stderr.take().ok_or_else(|| "Failed to capture stderr".to_string())?;
+
+        let stdout_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stdout_pipe.read_to_end(&mut buf);
+            buf
+        });
+        let stderr_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr_pipe.read_to_end(&mut buf);
+            buf
+        });
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| format!("Failed to poll command: {}", e))? {
+                break status;
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                // Join the reader threads so they don't outlive the child's
+                // now-closed pipes, but don't let their output leak through.
+                let _ = stdout_reader.join();
+                let _ = stderr_reader.join();
+                return Err(format!("command timed out after {}s", timeout.as_secs()));
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        let stdout = stdout_reader.join().unwrap_or_default();
+        let stderr = stderr_reader.join().unwrap_or_default();
+
+        if status.success() {
+            Ok(String::from_utf8_lossy(&stdout).to_string())
+        } else {
+            Err(String::from_utf8_lossy(&stderr).to_string())
+        }
+    }
+
     pub fn delete_path_simple(path: &str) -> Result<(), String> {
         use std::fs;
         
@@ -106,4 +225,53 @@ impl SystemManager {
         // On non-Windows, WSL paths don't make sense, so return false
         false
     }
+
+    /// Checks existence of many WSL paths in a single `wsl.exe` invocation,
+    /// preserving `paths`' order. Far cheaper than one subprocess per path
+    /// when filtering dozens of discovered repos.
+    #[cfg(target_os = "windows")]
+    pub fn check_wsl_paths_exist(paths: &[String]) -> Vec<bool> {
+        if paths.is_empty() {
+            return Vec::new();
+        }
+
+        let Some(dist_name) = OsSessionKind::list_available().ok().and_then(|available| {
+            available.into_iter().find_map(|session| match session {
+                OsSessionKind::Wsl(dist_name) => Some(dist_name),
+                _ => None,
+            })
+        }) else {
+            return vec![false; paths.len()];
+        };
+
+        use std::os::windows::process::CommandExt;
+        // Test each path as a distinct argv entry (not string-embedded) so
+        // spaces in paths can't break the loop; emit one "1"/"0" line per path.
+        let script = r#"for p in "$@"; do if [ -d "$p" ]; then echo 1; else echo 0; fi; done"#;
+        let output = Command::new("wsl")
+            .arg("-d")
+            .arg(&dist_name)
+            .arg("sh")
+            .arg("-c")
+            .arg(script)
+            .arg("_")
+            .args(paths)
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output();
+
+        match output {
+            Ok(result) if result.status.success() => {
+                let stdout = String::from_utf8_lossy(&result.stdout);
+                let mut flags: Vec<bool> = stdout.lines().map(|line| line.trim() == "1").collect();
+                flags.resize(paths.len(), false);
+                flags
+            }
+            _ => vec![false; paths.len()],
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn check_wsl_paths_exist(paths: &[String]) -> Vec<bool> {
+        vec![false; paths.len()]
+    }
 }
\ No newline at end of file
@@ -0,0 +1,316 @@
+use crate::filesystem::{CopyOptions, FileSystemManager};
+use crate::ignore::{IgnoreMatcher, IgnoreSource};
+use crate::os::OsSession;
+use notify::{Event, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// How long to wait after the last filesystem event touching a path before
+/// treating the burst as settled and propagating it - long enough to
+/// coalesce editor save-then-rewrite bursts (and a quick create-then-delete
+/// of a temp file into a no-op, since by the time the window elapses only
+/// the path's current on-disk state is consulted), short enough to feel live.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(75);
+
+/// One change `watch_and_sync` applied to the destination.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncChangeEvent {
+    pub watch_id: String,
+    pub path: String,
+    pub kind: String, // "upsert" | "remove"
+}
+
+/// Tracks running `watch_and_sync` background threads, keyed by a
+/// caller-visible watch id, so `stop_watch` can tear one down.
+#[derive(Default)]
+pub struct SyncWatches {
+    stop_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl SyncWatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Performs an initial `copy_files_optimized`, then watches `source` for
+/// create/modify/delete/rename events and propagates only the changed paths
+/// to `destination`. Returns a watch id to pass to `stop_watch`.
+pub fn start_watch(
+    source: String,
+    destination: String,
+    os_session: OsSession,
+    exclude_git: bool,
+    exclude_rules: Vec<IgnoreSource>,
+    options: CopyOptions,
+    app_handle: AppHandle,
+    watches: &SyncWatches,
+) -> Result<String, String> {
+    FileSystemManager::copy_files_optimized(&source, &destination, &os_session, exclude_git, &exclude_rules, &options)?;
+
+    // Watching only makes sense where the watching process can see the
+    // filesystem directly: the local session, or a WSL session where both
+    // sides are Windows-mounted paths (reachable from the host once
+    // converted). A purely WSL-internal path lives behind the 9p boundary,
+    // same limitation `copy_files_optimized_wsl` already documents for the
+    // one-shot copy.
+    let mount_to_mount = match &os_session {
+        OsSession::Local(_) => false,
+        OsSession::Wsl(_) => {
+            if FileSystemManager::is_windows_mount_path(&source) && FileSystemManager::is_windows_mount_path(&destination) {
+                true
+            } else {
+                return Err("watch_and_sync only supports local paths or Windows-mounted WSL paths".to_string());
+            }
+        }
+    };
+
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    watches
+        .stop_flags
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .insert(watch_id.clone(), stop_flag.clone());
+
+    spawn_watch(watch_id.clone(), source, destination, exclude_git, exclude_rules, mount_to_mount, app_handle, stop_flag);
+
+    Ok(watch_id)
+}
+
+/// Stops a watch started by `start_watch`.
+pub fn stop_watch(watch_id: &str, watches: &SyncWatches) -> Result<(), String> {
+    let mut flags = watches
+        .stop_flags
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if let Some(flag) = flags.remove(watch_id) {
+        flag.store(true, Ordering::SeqCst);
+    }
+
+    Ok(())
+}
+
+fn spawn_watch(
+    watch_id: String,
+    source: String,
+    destination: String,
+    exclude_git: bool,
+    exclude_rules: Vec<IgnoreSource>,
+    mount_to_mount: bool,
+    app_handle: AppHandle,
+    stop_flag: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let (watch_root, dest_root) = if mount_to_mount {
+            #[cfg(target_os = "windows")]
+            {
+                (
+                    crate::path_translation::wsl_to_windows(&source, ""),
+                    crate::path_translation::wsl_to_windows(&destination, ""),
+                )
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                tracing::warn!(target: "watch_sync", "Windows-mount watch path requested on a non-Windows build");
+                return;
+            }
+        } else {
+            (PathBuf::from(&source), PathBuf::from(&destination))
+        };
+
+        let contains_cargo_projects = FileSystemManager::contains_cargo_projects(&source, &OsSession::Local(source.clone()));
+        let matcher = FileSystemManager::build_matcher(
+            &watch_root.to_string_lossy(),
+            exclude_git,
+            contains_cargo_projects,
+            &exclude_rules,
+        );
+
+        let (tx, rx) = channel::<notify::Result<Event>>();
+        let mut watcher = match notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        }) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                tracing::warn!(target: "watch_sync", %err, "failed to create filesystem watcher");
+                return;
+            }
+        };
+
+        if let Err(err) = watcher.watch(&watch_root, RecursiveMode::Recursive) {
+            tracing::warn!(target: "watch_sync", %err, path = %watch_root.display(), "failed to start watching");
+            return;
+        }
+
+        // Paths touched since they were last flushed, with the instant of
+        // their most recent event - flushed once they've been quiet for
+        // `DEBOUNCE_WINDOW`.
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+        while !stop_flag.load(Ordering::SeqCst) {
+            match rx.recv_timeout(Duration::from_millis(20)) {
+                Ok(Ok(event)) => {
+                    for path in event.paths {
+                        pending.insert(path, Instant::now());
+                    }
+                }
+                Ok(Err(err)) => {
+                    tracing::warn!(target: "watch_sync", %err, "filesystem watch error");
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = Instant::now();
+            let ready: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, touched)| now.duration_since(**touched) >= DEBOUNCE_WINDOW)
+                .map(|(path, _)| path.clone())
+                .collect();
+
+            for path in ready {
+                pending.remove(&path);
+                apply_change(&watch_root, &dest_root, &path, &matcher, mount_to_mount, &watch_id, &app_handle);
+            }
+        }
+
+        let _ = watcher.unwatch(&watch_root);
+    });
+}
+
+/// Propagates one changed path to the destination: copies it if it still
+/// exists on the source, or removes the mirrored destination path if it
+/// doesn't (which also covers a create-then-delete burst, since only the
+/// path's state at flush time matters - if the destination never had it
+/// either, this is a no-op).
+fn apply_change(
+    watch_root: &Path,
+    dest_root: &Path,
+    changed_path: &Path,
+    matcher: &IgnoreMatcher,
+    mount_to_mount: bool,
+    watch_id: &str,
+    app_handle: &AppHandle,
+) {
+    let Ok(relative) = changed_path.strip_prefix(watch_root) else {
+        return;
+    };
+    if relative.as_os_str().is_empty() {
+        return;
+    }
+
+    let is_dir = changed_path.is_dir();
+    if matcher.is_ignored(changed_path, is_dir) {
+        return;
+    }
+
+    let dest_path = dest_root.join(relative);
+
+    let (kind, result) = if changed_path.exists() {
+        (
+            "upsert",
+            if mount_to_mount {
+                copy_item_windows(changed_path, &dest_path)
+            } else {
+                copy_item_local(changed_path, &dest_path)
+            },
+        )
+    } else if dest_path.exists() {
+        (
+            "remove",
+            if mount_to_mount {
+                remove_item_windows(&dest_path)
+            } else {
+                crate::system::SystemManager::delete_path_simple(&dest_path.to_string_lossy())
+            },
+        )
+    } else {
+        return;
+    };
+
+    match result {
+        Ok(()) => {
+            let _ = app_handle.emit(
+                "sync-file-change",
+                SyncChangeEvent {
+                    watch_id: watch_id.to_string(),
+                    path: dest_path.to_string_lossy().to_string(),
+                    kind: kind.to_string(),
+                },
+            );
+        }
+        Err(err) => {
+            tracing::warn!(target: "watch_sync", %err, path = %changed_path.display(), "failed to propagate change");
+        }
+    }
+}
+
+fn copy_item_local(source: &Path, destination: &Path) -> Result<(), String> {
+    if source.is_dir() {
+        return fs::create_dir_all(destination)
+            .map_err(|e| format!("Failed to create directory '{}': {}", destination.display(), e));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+
+    fs::copy(source, destination)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy '{}': {}", source.display(), e))
+}
+
+/// Mirrors a single changed file/directory across two Windows-mounted WSL
+/// paths via `Copy-Item`, rather than a bare `fs::copy` - matching the
+/// attribute-preserving behavior of the robocopy path used for the initial
+/// seed instead of silently dropping it for incremental updates.
+#[cfg(target_os = "windows")]
+fn copy_item_windows(source: &Path, destination: &Path) -> Result<(), String> {
+    use crate::system::SystemManager;
+
+    if let Some(parent) = destination.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let ps_command = format!(
+        "Copy-Item -LiteralPath '{}' -Destination '{}' -Recurse -Force",
+        source.display().to_string().replace('\'', "''"),
+        destination.display().to_string().replace('\'', "''")
+    );
+
+    SystemManager::execute_command("powershell", &["-Command", &ps_command]).map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn copy_item_windows(_source: &Path, _destination: &Path) -> Result<(), String> {
+    Err("Windows mount-to-mount sync is only available on Windows".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn remove_item_windows(path: &Path) -> Result<(), String> {
+    use crate::system::SystemManager;
+
+    let ps_command = format!(
+        "Remove-Item -LiteralPath '{}' -Force -Recurse -ErrorAction SilentlyContinue",
+        path.display().to_string().replace('\'', "''")
+    );
+
+    SystemManager::execute_command("powershell", &["-Command", &ps_command]).map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_item_windows(_path: &Path) -> Result<(), String> {
+    Err("Windows mount-to-mount sync is only available on Windows".to_string())
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::git::GitManager;
+use crate::os::OsSession;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Status of one dispatched webhook job, in the order it actually
+/// progresses through - `rerun_webhook_job` takes a finished/failed job
+/// back to `Queued`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookJobStatus {
+    Queued,
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookJob {
+    pub id: String,
+    pub repository: String,
+    pub commit_sha: String,
+    pub status: WebhookJobStatus,
+    pub error: Option<String>,
+    pub created_at_unix: u64,
+}
+
+/// Where a repository's pushed commits should be checked out, and the
+/// shared secret GitHub signs its webhook payloads with for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RepoWebhookConfig {
+    workspace_dir: String,
+    secret: String,
+}
+
+/// Owns the configured repo -> workspace mappings and the job history,
+/// persisting the latter to `<app data dir>/webhook-jobs.json` so history
+/// survives an app restart. Job history is loaded lazily (on the first
+/// command that touches it) since `AppHandle` isn't available until the
+/// Tauri builder has started, matching how `app_handle` is threaded
+/// through `file_watcher`/`pty` rather than captured at construction time.
+#[derive(Default)]
+pub struct WebhookManager {
+    loaded: Mutex<bool>,
+    repos: Mutex<HashMap<String, RepoWebhookConfig>>,
+    jobs: Mutex<Vec<WebhookJob>>,
+    server_handle: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl WebhookManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn ensure_loaded(&self, app_handle: &AppHandle) {
+        let mut loaded = self.loaded.lock().unwrap();
+        if *loaded {
+            return;
+        }
+        if let Ok(jobs) = load_jobs(app_handle) {
+            *self.jobs.lock().unwrap() = jobs;
+        }
+        *loaded = true;
+    }
+
+    pub fn register_repo(&self, full_name: String, workspace_dir: String, secret: String) {
+        self.repos.lock().unwrap().insert(full_name, RepoWebhookConfig { workspace_dir, secret });
+    }
+
+    pub fn list_jobs(&self, app_handle: &AppHandle) -> Vec<WebhookJob> {
+        self.ensure_loaded(app_handle);
+        self.jobs.lock().unwrap().clone()
+    }
+
+    fn push_job(&self, app_handle: &AppHandle, job: WebhookJob) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.push(job);
+        let _ = save_jobs(app_handle, &jobs);
+    }
+
+    fn update_job(&self, app_handle: &AppHandle, job_id: &str, status: WebhookJobStatus, error: Option<String>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.iter_mut().find(|j| j.id == job_id) {
+            job.status = status;
+            job.error = error;
+        }
+        let _ = save_jobs(app_handle, &jobs);
+        let _ = app_handle.emit("webhook-job-update", jobs.clone());
+    }
+
+    pub fn rerun_job(self: &Arc<Self>, app_handle: AppHandle, job_id: String) -> Result<(), String> {
+        self.ensure_loaded(&app_handle);
+        let job = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|j| j.id == job_id)
+            .cloned()
+            .ok_or_else(|| format!("Webhook job not found: {}", job_id))?;
+
+        self.update_job(&app_handle, &job.id, WebhookJobStatus::Queued, None);
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            manager.run_job(app_handle, job).await;
+        });
+        Ok(())
+    }
+
+    /// Records a new queued job and dispatches it in the background. The
+    /// signature is verified by the caller (`handle_github_push`) before
+    /// this runs. Returns the new job's id.
+    fn dispatch_new(self: &Arc<Self>, app_handle: AppHandle, repository: String, commit_sha: String) -> String {
+        self.ensure_loaded(&app_handle);
+        let job = WebhookJob {
+            id: Uuid::new_v4().to_string(),
+            repository,
+            commit_sha,
+            status: WebhookJobStatus::Queued,
+            error: None,
+            created_at_unix: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        self.push_job(&app_handle, job.clone());
+        let job_id = job.id.clone();
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            manager.run_job(app_handle, job).await;
+        });
+
+        job_id
+    }
+
+    /// Checks out the job's commit in the repo's registered workspace, then
+    /// emits `webhook-agent-run-requested` so whatever in this app (or the
+    /// Ariana backend it talks to) actually owns starting agent runs can
+    /// pick it up - this codebase has no local "start an agent run" API to
+    /// call directly.
+    async fn run_job(self: Arc<Self>, app_handle: AppHandle, job: WebhookJob) {
+        self.update_job(&app_handle, &job.id, WebhookJobStatus::Running, None);
+
+        let workspace_dir = {
+            let repos = self.repos.lock().unwrap();
+            repos.get(&job.repository).map(|cfg| cfg.workspace_dir.clone())
+        };
+
+        let Some(workspace_dir) = workspace_dir else {
+            self.update_job(
+                &app_handle,
+                &job.id,
+                WebhookJobStatus::Failed,
+                Some(format!("No workspace registered for repository {}", job.repository)),
+            );
+            return;
+        };
+
+        let os_session = OsSession::Local(workspace_dir.clone());
+        let checkout_result = GitManager::fetch(&workspace_dir, "origin", &os_session)
+            .and_then(|_| GitManager::revert_to_commit(&workspace_dir, &job.commit_sha, &os_session));
+
+        if let Err(err) = checkout_result {
+            self.update_job(&app_handle, &job.id, WebhookJobStatus::Failed, Some(err));
+            return;
+        }
+
+        let _ = app_handle.emit(
+            "webhook-agent-run-requested",
+            serde_json::json!({
+                "jobId": job.id,
+                "repository": job.repository,
+                "commitSha": job.commit_sha,
+                "workspaceDir": workspace_dir,
+            }),
+        );
+
+        self.update_job(&app_handle, &job.id, WebhookJobStatus::Finished, None);
+    }
+}
+
+fn jobs_file_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join("webhook-jobs.json"))
+}
+
+fn load_jobs(app_handle: &AppHandle) -> Result<Vec<WebhookJob>, String> {
+    let path = jobs_file_path(app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read webhook job history: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse webhook job history: {}", e))
+}
+
+fn save_jobs(app_handle: &AppHandle, jobs: &[WebhookJob]) -> Result<(), String> {
+    let path = jobs_file_path(app_handle)?;
+    let contents = serde_json::to_string_pretty(jobs).map_err(|e| format!("Failed to serialize webhook job history: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write webhook job history: {}", e))
+}
+
+fn decode_hex(input: &str) -> Option<Vec<u8>> {
+    if input.len() % 2 != 0 {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Verifies `body` against the `sha256=<hex>`-formatted `X-Hub-Signature-256`
+/// header value using `secret`, the way GitHub documents for webhook
+/// delivery verification. Uses `Mac::verify_slice` rather than a hand-rolled
+/// byte comparison so the check runs in constant time.
+fn verify_signature(secret: &str, body: &[u8], header_value: &str) -> bool {
+    let Some(hex_sig) = header_value.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Some(expected) = decode_hex(hex_sig) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+struct WebhookState {
+    manager: Arc<WebhookManager>,
+    app_handle: AppHandle,
+}
+
+async fn handle_github_push(State(state): State<Arc<WebhookState>>, headers: HeaderMap, body: axum::body::Bytes) -> StatusCode {
+    let Some(signature) = headers.get("X-Hub-Signature-256").and_then(|v| v.to_str().ok()) else {
+        return StatusCode::UNAUTHORIZED;
+    };
+
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let Some(repository) = payload
+        .get("repository")
+        .and_then(|r| r.get("full_name"))
+        .and_then(|v| v.as_str())
+    else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let secret = {
+        let repos = state.manager.repos.lock().unwrap();
+        repos.get(repository).map(|cfg| cfg.secret.clone())
+    };
+    let Some(secret) = secret else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    if !verify_signature(&secret, &body, signature) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let Some(commit_sha) = payload.get("after").and_then(|v| v.as_str()) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    state
+        .manager
+        .dispatch_new(state.app_handle.clone(), repository.to_string(), commit_sha.to_string());
+
+    StatusCode::ACCEPTED
+}
+
+/// Starts listening for GitHub push webhooks on `127.0.0.1:<port>`. Each
+/// call replaces any previously running listener started by this manager.
+pub fn start_server(manager: Arc<WebhookManager>, app_handle: AppHandle, port: u16) {
+    let state = Arc::new(WebhookState { manager: manager.clone(), app_handle });
+    let router = axum::Router::new()
+        .route("/webhooks/github", axum::routing::post(handle_github_push))
+        .with_state(state);
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::error!(target: "webhook", %err, port, "failed to bind webhook listener");
+                return;
+            }
+        };
+        if let Err(err) = axum::serve(listener, router).await {
+            tracing::error!(target: "webhook", %err, "webhook listener exited");
+        }
+    });
+
+    if let Some(previous) = manager.server_handle.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
@@ -10,6 +10,10 @@ use crate::StreamConnection;
 
 pub(crate) struct StreamAudioDecoder {
     pub(crate) stream: Weak<StreamConnection>,
+    /// User-facing preference capping the negotiated channel count, e.g.
+    /// `Some(2)` to force stereo down to a 2-channel output device even
+    /// when the host could otherwise send 5.1/7.1. `None` means no cap.
+    pub(crate) max_channels: Option<u8>,
 }
 
 impl AudioDecoder for StreamAudioDecoder {
@@ -63,10 +67,44 @@ impl AudioDecoder for StreamAudioDecoder {
     }
 
     fn config(&self) -> AudioConfig {
-        AudioConfig::STEREO
+        let Some(stream) = self.stream.upgrade() else {
+            return AudioConfig::STEREO;
+        };
+
+        let negotiated = stream.stream_setup.blocking_lock().audio.clone();
+        let channel_count = negotiated.map(|cfg| cfg.channel_count).unwrap_or(2);
+
+        audio_config_for_channel_count(self.capped_channel_count(channel_count))
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities::empty()
+        if self.capped_channel_count(8) > 2 {
+            Capabilities::SUPPORTS_5_1_SURROUND | Capabilities::SUPPORTS_7_1_SURROUND
+        } else {
+            Capabilities::empty()
+        }
+    }
+}
+
+impl StreamAudioDecoder {
+    /// Clamps `channel_count` to the user's configured `max_channels`
+    /// preference, if any (e.g. forcing stereo on a 2-channel output
+    /// device even though the host could otherwise send 5.1/7.1).
+    fn capped_channel_count(&self, channel_count: u8) -> u8 {
+        match self.max_channels {
+            Some(max) => channel_count.min(max),
+            None => channel_count,
+        }
+    }
+}
+
+/// Maps a negotiated Opus multistream channel count to the corresponding
+/// `AudioConfig` layout, falling back to stereo for anything unrecognized.
+fn audio_config_for_channel_count(channel_count: u8) -> AudioConfig {
+    match channel_count {
+        0..=2 => AudioConfig::STEREO,
+        6 => AudioConfig::SURROUND_5_1,
+        8 => AudioConfig::SURROUND_7_1,
+        _ => AudioConfig::STEREO,
     }
 }
@@ -1,4 +1,7 @@
-use std::{sync::Weak, time::Duration};
+use std::{
+    sync::{Arc, Weak},
+    time::Duration,
+};
 
 use bytes::Bytes;
 use log::{error, info, warn};
@@ -12,7 +15,10 @@ use webrtc::{
     track::track_local::track_local_static_sample::TrackLocalStaticSample,
 };
 
-use crate::transport::webrtc::{WebRtcInner, sender::TrackLocalSender};
+use crate::transport::webrtc::{
+    WebRtcInner,
+    sender::{PlayoutDelayMs, TrackLocalSender},
+};
 
 pub fn register_audio_codecs(media_engine: &mut MediaEngine) -> Result<(), webrtc::Error> {
     media_engine.register_codec(
@@ -36,6 +42,17 @@ pub fn register_audio_codecs(media_engine: &mut MediaEngine) -> Result<(), webrt
 pub struct WebRtcAudio {
     sender: TrackLocalSender<TrackLocalStaticSample>,
     config: Option<OpusMultistreamConfig>,
+    /// RFC 7273 offset (ms) recovered from the peer's SDP, applied on top of
+    /// our own accumulated RTP clock so `presentation_time_ms` lands on the
+    /// same wall-clock base the video track uses.
+    reference_clock_offset_ms: i64,
+    /// Running RTP timestamp (in the negotiated Opus clock), advanced by
+    /// exactly `samples_per_frame` per sample sent, or re-based on the PTS
+    /// when `send_audio_sample` is given one.
+    rtp_timestamp: u32,
+    /// Total samples sent since the stream started, used to derive
+    /// `presentation_time_ms` independently of `rtp_timestamp`'s u32 wrap.
+    samples_sent: u64,
 }
 
 impl WebRtcAudio {
@@ -43,14 +60,39 @@ impl WebRtcAudio {
         Self {
             sender: TrackLocalSender::new(runtime, peer, channel_queue_size),
             config: None,
+            reference_clock_offset_ms: 0,
+            rtp_timestamp: 0,
+            samples_sent: 0,
         }
     }
+
+    pub fn set_reference_clock_offset_ms(&mut self, offset_ms: i64) {
+        self.reference_clock_offset_ms = offset_ms;
+    }
+
+    /// Sets the playout-delay bounds advertised for frames sent from now on;
+    /// see `TrackLocalSender::set_playout_delay_policy`.
+    pub fn set_playout_delay_policy(&mut self, policy: PlayoutDelayMs) {
+        self.sender.set_playout_delay_policy(policy);
+    }
+
+    /// The accumulated RTP-clock presentation time (ms since the first
+    /// sample), shifted by the RFC 7273 reference-clock offset so the video
+    /// track can align its own frame timestamps against the same wall-clock
+    /// base for lip-sync.
+    pub fn presentation_time_ms(&self) -> i64 {
+        let Some(config) = self.config.as_ref() else {
+            return self.reference_clock_offset_ms;
+        };
+        let elapsed_ms = (self.samples_sent as i128 * 1000 / config.sample_rate as i128) as i64;
+        elapsed_ms + self.reference_clock_offset_ms
+    }
 }
 
 impl WebRtcAudio {
     pub async fn setup(
         &mut self,
-        inner: &WebRtcInner,
+        inner: &Arc<WebRtcInner>,
         audio_config: AudioConfig,
         stream_config: OpusMultistreamConfig,
     ) -> i32 {
@@ -78,18 +120,27 @@ impl WebRtcAudio {
         }
 
         info!("[AUDIO-SETUP] Creating audio track...");
+        self.sender
+            .set_bandwidth_estimation(inner.bwe_send_times.clone(), inner.bwe_target_bitrate_bps.clone());
+        // TWCC feedback is transport-wide, not per-media, so it's fine (and
+        // simplest) to read it off this track's RTCP reader.
+        let inner_for_rtcp = Arc::downgrade(inner);
         if let Err(err) = self
             .sender
             .create_track(
                 TrackLocalStaticSample::new(
-                    RTCRtpCodecCapability {
-                        mime_type: MIME_TYPE_OPUS.to_string(),
-                        ..Default::default()
-                    },
+                    audio_track_capability(&stream_config),
                     "audio".to_string(),
                     "moonlight".to_string(),
                 ),
-                |_| {},
+                move |packet| {
+                    let inner = inner_for_rtcp.clone();
+                    tokio::spawn(async move {
+                        if let Some(inner) = inner.upgrade() {
+                            inner.on_rtcp_packet(packet).await;
+                        }
+                    });
+                },
             )
             .await
         {
@@ -123,33 +174,114 @@ impl WebRtcAudio {
             warn!("[AUDIO-SETUP] RENEGOTIATION FAILED! Audio was added but renegotiation failed.");
         } else {
             info!("[AUDIO-SETUP] Renegotiation succeeded");
+
+            // Check what the peer's SDP answer actually accepted, since a
+            // browser that can't decode a surround Opus stream may answer
+            // with a plain-stereo codec line even though we offered more.
+            let requested_channels = self.config.as_ref().map(|c| c.channel_count).unwrap_or(2);
+            match self.sender.negotiated_codec().await {
+                Some(accepted) if accepted.capability.channels != requested_channels as u16 => {
+                    warn!(
+                        "[AUDIO-SETUP] Peer accepted {} channel(s), but we negotiated a {}-channel stream",
+                        accepted.capability.channels, requested_channels
+                    );
+                }
+                Some(_) => info!("[AUDIO-SETUP] Peer accepted the requested channel layout"),
+                None => warn!("[AUDIO-SETUP] Could not read back the peer's accepted audio codec"),
+            }
         }
 
         info!("[AUDIO-SETUP {}] ========== AUDIO SETUP COMPLETE ==========", inner.t_plus());
         0
     }
 
-    pub async fn send_audio_sample(&mut self, data: &[u8]) {
+    /// `pts` is the moonlight presentation timestamp (microseconds) for this
+    /// frame, when the caller has one. `AudioDecoder::decode_and_play_sample`
+    /// doesn't carry a PTS today, so callers currently always pass `None`
+    /// and we fall back to our own running sample counter. With a PTS the
+    /// RTP timestamp is derived from it (and the counter continues from
+    /// there); without one it advances by exactly `samples_per_frame`, so a
+    /// late or lost frame shifts the whole stream forward instead of
+    /// silently realigning to wall-clock "now".
+    pub async fn send_audio_sample(&mut self, data: &[u8], pts: Option<i64>) {
         let Some(config) = self.config.as_ref() else {
             return;
         };
 
-        let duration =
-            Duration::from_secs_f64(config.samples_per_frame as f64 / config.sample_rate as f64);
+        let samples_per_frame = config.samples_per_frame;
+        let sample_rate = config.sample_rate;
+
+        let packet_timestamp = match pts {
+            Some(pts) => {
+                let timestamp = ((pts as i128 * sample_rate as i128) / 1_000_000) as u32;
+                self.rtp_timestamp = timestamp.wrapping_add(samples_per_frame);
+                timestamp
+            }
+            None => {
+                let timestamp = self.rtp_timestamp;
+                self.rtp_timestamp = self.rtp_timestamp.wrapping_add(samples_per_frame);
+                timestamp
+            }
+        };
+        self.samples_sent += samples_per_frame as u64;
+
+        let duration = Duration::from_secs_f64(samples_per_frame as f64 / sample_rate as f64);
 
         let data = Bytes::copy_from_slice(data);
 
         let sample = Sample {
             data,
             duration,
-            // Time should be set if you want fine-grained sync
+            packet_timestamp,
             ..Default::default()
         };
 
-        self.sender.send_samples(vec![sample], false).await;
+        self.sender.send_samples(vec![sample], false, None).await;
     }
 
     fn config(&self) -> AudioConfig {
-        AudioConfig::STEREO
+        match self.config.as_ref().map(|c| c.channel_count) {
+            Some(8) => AudioConfig::SURROUND_7_1,
+            Some(6) => AudioConfig::SURROUND_5_1,
+            _ => AudioConfig::STEREO,
+        }
+    }
+}
+
+/// Builds the RTP codec capability for the negotiated Opus multistream
+/// layout, advertising the channel mapping/stream/coupled-stream counts in
+/// the fmtp line so the receiving Opus decoder can decode the host's
+/// surround mix rather than assuming plain stereo.
+fn audio_track_capability(stream_config: &OpusMultistreamConfig) -> RTCRtpCodecCapability {
+    let mut sdp_fmtp_line = "minptime=10;useinbandfec=1".to_string();
+
+    if stream_config.channel_count > 2 {
+        sdp_fmtp_line.push_str(&format!(
+            ";num_streams={};coupled_streams={};channel_mapping={}",
+            stream_config.streams,
+            stream_config.coupled_streams,
+            channel_mapping(stream_config.channel_count),
+        ));
+    }
+
+    RTCRtpCodecCapability {
+        mime_type: MIME_TYPE_OPUS.to_string(),
+        clock_rate: stream_config.sample_rate,
+        channels: stream_config.channel_count as u16,
+        sdp_fmtp_line,
+        rtcp_feedback: vec![],
+    }
+}
+
+/// Opus multistream channel-to-speaker mapping table (RFC 7845 section
+/// 5.1.1) for the layouts Moonlight can negotiate.
+fn channel_mapping(channel_count: u8) -> String {
+    match channel_count {
+        6 => "0,4,1,2,3,5".to_string(),
+        8 => "0,6,1,2,3,4,5,7".to_string(),
+        _ => (0..channel_count)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
     }
 }
@@ -0,0 +1,343 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex as StdMutex,
+    time::{Duration, Instant},
+};
+
+use log::{debug, trace};
+use webrtc::rtcp::{packet::Packet, transport_feedbacks::transport_layer_cc::TransportLayerCc};
+
+/// TWCC header extension URI, registered on both audio and video. Only a
+/// `TrackLocalSender` with `set_bandwidth_estimation` enabled tags its
+/// packets with a transport-wide sequence number; that is the audio track,
+/// so video packets carry none.
+pub const TRANSPORT_CC_URI: &str = "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+
+const MULTIPLICATIVE_DECREASE: f64 = 0.85;
+const ADDITIVE_INCREASE_BPS: f64 = 50_000.0;
+const MULTIPLICATIVE_INCREASE: f64 = 1.05;
+const LOSS_RATIO_DECREASE_THRESHOLD: f64 = 0.1;
+/// If no TWCC feedback arrives for this long, the trendline's delay history
+/// is stale (renegotiation, a long stall) - the next feedback packet resets
+/// the estimator instead of treating the gap as a delay spike.
+const FEEDBACK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Google-Congestion-Control-style delay-based bandwidth estimator.
+///
+/// Feeds on inter-arrival delay variation derived from TWCC feedback and
+/// drives a target bitrate via a trendline (exponentially-weighted linear
+/// regression) over the recent delay-variation samples.
+pub struct GccEstimator {
+    trendline: TrendlineEstimator,
+    target_bitrate_bps: u64,
+    min_bitrate_bps: u64,
+    max_bitrate_bps: u64,
+    last_feedback_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageState {
+    Overuse,
+    Underuse,
+    Normal,
+}
+
+impl GccEstimator {
+    pub fn new(initial_bitrate_bps: u64, min_bitrate_bps: u64, max_bitrate_bps: u64) -> Self {
+        Self {
+            trendline: TrendlineEstimator::new(),
+            target_bitrate_bps: initial_bitrate_bps.clamp(min_bitrate_bps, max_bitrate_bps),
+            min_bitrate_bps,
+            max_bitrate_bps,
+            last_feedback_at: None,
+        }
+    }
+
+    pub fn target_bitrate_bps(&self) -> u64 {
+        self.target_bitrate_bps
+    }
+
+    /// Sets the ceiling used to clamp the target bitrate (lowering the
+    /// current target if it is above the new ceiling), e.g. when
+    /// `StreamSettings` changes the requested max bitrate.
+    pub fn set_max_bitrate_bps(&mut self, max_bitrate_bps: u64) {
+        self.max_bitrate_bps = max_bitrate_bps;
+        self.target_bitrate_bps = self.target_bitrate_bps.min(self.max_bitrate_bps);
+    }
+
+    /// Feeds one inter-group delay-variation sample (in ms, at `arrival`)
+    /// into the detector, updating the target bitrate in place. Returns the
+    /// usage state that was detected.
+    pub fn on_delay_variation(&mut self, delay_variation_ms: f64, arrival: Instant) -> UsageState {
+        let state = self.trendline.update(delay_variation_ms, arrival);
+
+        match state {
+            UsageState::Overuse => {
+                self.target_bitrate_bps = ((self.target_bitrate_bps as f64) * MULTIPLICATIVE_DECREASE) as u64;
+            }
+            UsageState::Underuse => {
+                // Hold: back off from increasing until the link stabilizes.
+            }
+            UsageState::Normal => {
+                let additive = self.target_bitrate_bps as f64 + ADDITIVE_INCREASE_BPS;
+                let multiplicative = self.target_bitrate_bps as f64 * MULTIPLICATIVE_INCREASE;
+                // Prefer the gentler additive step once we're within reach of
+                // the ceiling, multiplicative while still far below it.
+                let candidate = if (self.target_bitrate_bps as f64) < (self.max_bitrate_bps as f64) * 0.5 {
+                    multiplicative
+                } else {
+                    additive
+                };
+                self.target_bitrate_bps = candidate as u64;
+            }
+        }
+
+        self.target_bitrate_bps = self.target_bitrate_bps.clamp(self.min_bitrate_bps, self.max_bitrate_bps);
+        state
+    }
+
+    /// Backs off the target bitrate when the fraction of packets a TWCC
+    /// feedback packet reports as lost exceeds
+    /// `LOSS_RATIO_DECREASE_THRESHOLD`, independent of the delay-based
+    /// decision - persistent loss without rising delay still means the
+    /// link can't sustain the current rate.
+    pub fn on_loss_ratio(&mut self, loss_ratio: f64) {
+        if loss_ratio > LOSS_RATIO_DECREASE_THRESHOLD {
+            self.target_bitrate_bps = ((self.target_bitrate_bps as f64) * MULTIPLICATIVE_DECREASE) as u64;
+            self.target_bitrate_bps = self.target_bitrate_bps.clamp(self.min_bitrate_bps, self.max_bitrate_bps);
+        }
+    }
+
+    /// Marks that a TWCC feedback packet arrived at `now`. If the previous
+    /// one arrived more than `FEEDBACK_TIMEOUT` ago, the trendline's delay
+    /// history no longer reflects the current link, so it's dropped and the
+    /// target bitrate falls back to `min_bitrate_bps` to ramp up from a
+    /// known-safe floor rather than keep whatever rate was last estimated.
+    pub fn note_feedback_received(&mut self, now: Instant) {
+        let stale = self
+            .last_feedback_at
+            .is_some_and(|at| now.duration_since(at) > FEEDBACK_TIMEOUT);
+
+        if stale {
+            debug!("[BWE] no TWCC feedback for over {FEEDBACK_TIMEOUT:?}, resetting estimator");
+            self.trendline.reset();
+            self.target_bitrate_bps = self.min_bitrate_bps;
+        }
+
+        self.last_feedback_at = Some(now);
+    }
+}
+
+/// Exponentially-weighted linear regression over the last ~20 inter-arrival
+/// delay-variation samples, with an adaptive overuse threshold that grows
+/// when consistently exceeded and shrinks otherwise (as in the GCC draft).
+struct TrendlineEstimator {
+    samples: Vec<(f64, f64)>, // (time_ms since first sample, accumulated delay_ms)
+    accumulated_delay_ms: f64,
+    first_arrival: Option<Instant>,
+    gamma: f64,
+    last_state: UsageState,
+    overuse_streak: u32,
+    underuse_streak: u32,
+}
+
+const MAX_SAMPLES: usize = 20;
+const INITIAL_GAMMA: f64 = 12.5;
+const OVERUSE_TIME_THRESHOLD_MS: f64 = 10.0;
+
+impl TrendlineEstimator {
+    fn new() -> Self {
+        Self {
+            samples: Vec::with_capacity(MAX_SAMPLES),
+            accumulated_delay_ms: 0.0,
+            first_arrival: None,
+            gamma: INITIAL_GAMMA,
+            last_state: UsageState::Normal,
+            overuse_streak: 0,
+            underuse_streak: 0,
+        }
+    }
+
+    /// Discards all accumulated delay-variation history, as if the
+    /// estimator had just started - used when a feedback gap makes the
+    /// existing history stale.
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn update(&mut self, delay_variation_ms: f64, arrival: Instant) -> UsageState {
+        let first_arrival = *self.first_arrival.get_or_insert(arrival);
+        let time_ms = arrival.duration_since(first_arrival).as_secs_f64() * 1000.0;
+
+        self.accumulated_delay_ms += delay_variation_ms;
+        self.samples.push((time_ms, self.accumulated_delay_ms));
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples.remove(0);
+        }
+
+        let slope = self.linear_regression_slope();
+
+        // Adapt gamma to the overuse magnitude: grow it while overuse
+        // persists (resist flapping), shrink it back down otherwise.
+        if slope > self.gamma {
+            self.overuse_streak += 1;
+            self.underuse_streak = 0;
+            self.gamma += 0.02 * (slope.abs() - self.gamma).max(0.0);
+        } else if slope < -self.gamma {
+            self.underuse_streak += 1;
+            self.overuse_streak = 0;
+            self.gamma = (self.gamma - 0.02 * self.gamma).max(6.0);
+        } else {
+            self.overuse_streak = 0;
+            self.underuse_streak = 0;
+            self.gamma = (self.gamma - 0.001 * self.gamma).max(6.0);
+        }
+
+        let state = if slope > self.gamma && self.overuse_streak as f64 * 1000.0 / 30.0 > OVERUSE_TIME_THRESHOLD_MS {
+            UsageState::Overuse
+        } else if slope < -self.gamma {
+            UsageState::Underuse
+        } else {
+            UsageState::Normal
+        };
+
+        trace!("[BWE] trendline slope={slope:.3} gamma={:.3} -> {state:?}", self.gamma);
+        self.last_state = state;
+        state
+    }
+
+    fn linear_regression_slope(&self) -> f64 {
+        let n = self.samples.len();
+        if n < 2 {
+            return 0.0;
+        }
+
+        // The newest sample weighs 1, each older one `DECAY` times the next.
+        const DECAY: f64 = 0.9;
+        let weights: Vec<f64> = (0..n).map(|i| DECAY.powi((n - 1 - i) as i32)).collect();
+        let total_weight: f64 = weights.iter().sum();
+
+        let mean_x = self.samples.iter().zip(&weights).map(|(&(x, _), &w)| x * w).sum::<f64>() / total_weight;
+        let mean_y = self.samples.iter().zip(&weights).map(|(&(_, y), &w)| y * w).sum::<f64>() / total_weight;
+
+        let mut numerator = 0.0;
+        let mut denominator = 0.0;
+        for (&(x, y), &w) in self.samples.iter().zip(&weights) {
+            numerator += w * (x - mean_x) * (y - mean_y);
+            denominator += w * (x - mean_x) * (x - mean_x);
+        }
+
+        if denominator.abs() < f64::EPSILON {
+            0.0
+        } else {
+            numerator / denominator
+        }
+    }
+}
+
+/// Tracks when each outgoing, TWCC-tagged RTP packet was sent so incoming
+/// feedback can be turned into per-packet arrival/send deltas.
+pub struct SendTimeTracker {
+    send_times: StdMutex<HashMap<u16, Instant>>,
+}
+
+const MAX_TRACKED_PACKETS: usize = 2000;
+
+impl Default for SendTimeTracker {
+    fn default() -> Self {
+        Self {
+            send_times: StdMutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SendTimeTracker {
+    pub fn record_sent(&self, transport_sequence_number: u16, sent_at: Instant) {
+        let mut send_times = self.send_times.lock().expect("send_times poisoned");
+        if send_times.len() >= MAX_TRACKED_PACKETS {
+            send_times.clear();
+        }
+        send_times.insert(transport_sequence_number, sent_at);
+    }
+
+    fn take(&self, transport_sequence_number: u16) -> Option<Instant> {
+        self.send_times
+            .lock()
+            .expect("send_times poisoned")
+            .remove(&transport_sequence_number)
+    }
+}
+
+/// Parses a `TransportLayerCc` RTCP feedback packet into (sequence_number,
+/// arrival_time) pairs, using the packet's own reference time plus each
+/// entry's 250us-resolution receive delta.
+fn parse_arrivals(feedback: &TransportLayerCc) -> Vec<(u16, Duration)> {
+    let base_time = Duration::from_micros(feedback.reference_time as u64 * 64);
+    let mut arrivals = Vec::new();
+    let mut running = base_time;
+    let mut sequence = feedback.base_sequence_number;
+
+    for delta in &feedback.recv_deltas {
+        // RecvDelta ticks are 250us units per the TWCC draft.
+        let delta_us = delta.delta as i64 * 250;
+        running = if delta_us >= 0 {
+            running + Duration::from_micros(delta_us as u64)
+        } else {
+            running.saturating_sub(Duration::from_micros((-delta_us) as u64))
+        };
+        arrivals.push((sequence, running));
+        sequence = sequence.wrapping_add(1);
+    }
+
+    arrivals
+}
+
+/// Feeds one TWCC feedback packet through `tracker`/`estimator`, computing
+/// `d(i) = (arrival_i - arrival_{i-1}) - (send_i - send_{i-1})` for each
+/// consecutive pair of packets it has a recorded send time for.
+pub fn on_transport_feedback(
+    packet: &dyn Packet,
+    tracker: &SendTimeTracker,
+    estimator: &mut GccEstimator,
+) {
+    let Some(feedback) = packet.as_any().downcast_ref::<TransportLayerCc>() else {
+        return;
+    };
+
+    estimator.note_feedback_received(Instant::now());
+
+    // `recv_deltas` only has an entry per packet TWCC reports as received;
+    // the gap against `packet_status_count` (every sequence number this
+    // feedback packet covers) is the fraction it reports as lost.
+    if feedback.packet_status_count > 0 {
+        let loss_ratio = 1.0 - (feedback.recv_deltas.len() as f64 / feedback.packet_status_count as f64);
+        estimator.on_loss_ratio(loss_ratio);
+    }
+
+    let arrivals = parse_arrivals(feedback);
+
+    let mut previous: Option<(Instant, Duration)> = None; // (send_time, arrival)
+    for (sequence, arrival) in arrivals {
+        // `take` removes the entry, so a sequence already reported by an
+        // earlier (or reordered-but-already-processed) feedback packet is
+        // silently skipped here rather than double-counted; one that was
+        // never recorded (send_time already evicted, or this sequence
+        // number wrapped back onto a still-pending one) is skipped the same
+        // way.
+        let Some(send_time) = tracker.take(sequence) else {
+            continue;
+        };
+
+        if let Some((prev_send, prev_arrival)) = previous {
+            let send_delta = send_time.duration_since(prev_send).as_secs_f64() * 1000.0;
+            let arrival_delta = (arrival.as_secs_f64() - prev_arrival.as_secs_f64()) * 1000.0;
+            let delay_variation_ms = arrival_delta - send_delta;
+
+            let now = Instant::now();
+            let state = estimator.on_delay_variation(delay_variation_ms, now);
+            debug!("[BWE] d(i)={delay_variation_ms:.3}ms state={state:?} target={}bps", estimator.target_bitrate_bps());
+        }
+
+        previous = Some((send_time, arrival));
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// RFC 7273 clock reference, advertised in SDP via `a=ts-refclk:`/
+/// `a=mediaclk:` so both peers can align presentation timestamps against a
+/// shared wall-clock instead of independent per-stream RTP timelines.
+///
+/// This only carries an NTP reference clock (`ts-refclk:ntp=...`); PTP
+/// domains are part of RFC 7273 too but aren't needed for a browser peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferenceClock {
+    /// NTP timestamp (whole seconds since 1900-01-01) the SDP carrying this
+    /// clock was generated at.
+    pub ntp_timestamp: u64,
+}
+
+const NTP_UNIX_EPOCH_OFFSET_SECS: u64 = 2_208_988_800;
+
+impl ReferenceClock {
+    /// Captures the current wall-clock time as an NTP-era reference clock.
+    pub fn now() -> Self {
+        let unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            ntp_timestamp: unix.as_secs() + NTP_UNIX_EPOCH_OFFSET_SECS,
+        }
+    }
+
+    /// Renders the `a=ts-refclk:`/`a=mediaclk:` session-level attribute pair
+    /// to append to an outgoing offer/answer's SDP.
+    pub fn to_sdp_lines(self) -> String {
+        format!(
+            "a=ts-refclk:ntp={}\r\na=mediaclk:direct=0\r\n",
+            self.ntp_timestamp
+        )
+    }
+
+    /// Parses the `a=ts-refclk:ntp=<seconds>` attribute out of a remote SDP,
+    /// if the peer advertised one.
+    pub fn parse_from_sdp(sdp: &str) -> Option<Self> {
+        sdp.lines().find_map(|line| {
+            let value = line.trim().strip_prefix("a=ts-refclk:ntp=")?;
+            value.trim().parse().ok().map(|ntp_timestamp| Self { ntp_timestamp })
+        })
+    }
+}
+
+/// Computes the offset (in milliseconds) between a remote peer's reference
+/// clock and ours, to apply to locally-generated presentation timestamps so
+/// both sides converge on the same wall-clock.
+///
+/// This is inherently best-effort: if the remote peer never advertises a
+/// reference clock, callers simply never apply an offset and playback
+/// proceeds against independent RTP timelines rather than waiting for a
+/// convergence that may never come - the bound is whatever timeout already
+/// governs the offer/answer exchange itself (`send_offer`'s 30s answer
+/// wait), not anything clock-specific.
+pub fn recover_timebase_offset_ms(local: ReferenceClock, remote: ReferenceClock) -> i64 {
+    (remote.ntp_timestamp as i64 - local.ntp_timestamp as i64) * 1000
+}
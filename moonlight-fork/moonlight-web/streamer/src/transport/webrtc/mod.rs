@@ -1,7 +1,10 @@
 use std::{
     future::ready,
     pin::Pin,
-    sync::{Arc, Weak},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, Instant},
 };
 
@@ -11,10 +14,10 @@ use common::{
     StreamSettings,
     api_bindings::{
         RtcIceCandidate, RtcSdpType, RtcSessionDescription, StreamClientMessage,
-        StreamServerMessage, StreamSignalingMessage, TransportChannelId,
+        StreamSignalingMessage, TransportChannelId,
     },
     config::{PortRange, WebRtcConfig},
-    ipc::{ServerIpcMessage, StreamerIpcMessage},
+    ipc::ServerIpcMessage,
 };
 use log::{debug, error, info, trace, warn};
 use moonlight_common::stream::{
@@ -63,7 +66,7 @@ use crate::{
         webrtc::{
             audio::{WebRtcAudio, register_audio_codecs},
             sender::register_header_extensions,
-            video::{WebRtcVideo, register_video_codecs},
+            video::{VideoCodec, WebRtcVideo, register_video_codecs},
         },
     },
 };
@@ -71,16 +74,31 @@ use crate::{
 pub const TIMEOUT_DURATION: Duration = Duration::from_secs(10);
 
 mod audio;
+mod bwe;
+mod clockref;
 mod sender;
+mod signaller;
+mod stats;
 mod video;
+mod whip;
+
+use signaller::{IpcSignaller, SignalEvent, Signaller};
 
 struct WebRtcInner {
     peer: Arc<RTCPeerConnection>,
     event_sender: Sender<TransportEvent>,
+    signaller: Arc<dyn Signaller>,
     general_channel: Arc<RTCDataChannel>,
     stats_channel: Mutex<Option<Arc<RTCDataChannel>>>,
     video: Mutex<WebRtcVideo>,
     audio: Mutex<WebRtcAudio>,
+    // Delay-based adaptive bitrate (GCC-style), fed by TWCC RTCP feedback
+    // read off any track's RTCP reader.
+    bwe: Mutex<bwe::GccEstimator>,
+    bwe_send_times: Arc<bwe::SendTimeTracker>,
+    // Published target bitrate from `bwe`, read by `TrackLocalSender` to
+    // admission-control non-important frames without taking the `bwe` lock.
+    bwe_target_bitrate_bps: Arc<AtomicU64>,
     // Timeout / Terminate
     pub timeout_terminate_request: Mutex<Option<Instant>>,
     // Renegotiation mutex to prevent concurrent offer sends (race condition fix)
@@ -145,10 +163,11 @@ pub async fn new(
     );
 
     // -- Register media codecs
-    // TODO: register them based on the sdp
+    // H.264 is the broadest-compatibility default until a per-stream
+    // preference is threaded down from the caller's config.
     let mut api_media = MediaEngine::default();
     register_audio_codecs(&mut api_media).expect("failed to register audio codecs");
-    register_video_codecs(&mut api_media).expect("failed to register video codecs");
+    register_video_codecs(&mut api_media, VideoCodec::H264).expect("failed to register video codecs");
     register_header_extensions(&mut api_media).expect("failed to register header extensions");
 
     // -- Build Api
@@ -165,6 +184,7 @@ pub async fn new(
         .build();
 
     let (event_sender, event_receiver) = channel::<TransportEvent>(20);
+    let signaller: Arc<dyn Signaller> = Arc::new(IpcSignaller::new(event_sender.clone()));
 
     let peer = Arc::new(api.new_peer_connection(rtc_config).await?);
 
@@ -174,6 +194,7 @@ pub async fn new(
     let this_owned = Arc::new(WebRtcInner {
         peer: peer.clone(),
         event_sender,
+        signaller: signaller.clone(),
         general_channel,
         stats_channel: Mutex::new(None),
         video: Mutex::new(WebRtcVideo::new(
@@ -190,6 +211,9 @@ pub async fn new(
         renegotiating: Mutex::new(()),
         answer_received: Notify::new(),
         created_at: Instant::now(),
+        bwe: Mutex::new(bwe::GccEstimator::new(2_000_000, 250_000, 2_000_000)),
+        bwe_send_times: Arc::new(bwe::SendTimeTracker::default()),
+        bwe_target_bitrate_bps: Arc::new(AtomicU64::new(2_000_000)),
     });
     info!("[Streamer T+0ms] WebRTC peer created");
 
@@ -227,6 +251,24 @@ pub async fn new(
 
     drop(peer);
 
+    stats::spawn_stats_loop(this.clone());
+
+    // Drives inbound signaling for `Signaller` implementations that have
+    // their own pull-based event loop (e.g. a room-based signaller) rather
+    // than being pushed into via `on_ws_message`/`on_ipc_message`.
+    spawn({
+        let this = this.clone();
+        let signaller = signaller.clone();
+        async move {
+            while let Some(event) = signaller.recv().await {
+                let Some(this) = this.upgrade() else {
+                    break;
+                };
+                this.handle_signal_event(event).await;
+            }
+        }
+    });
+
     Ok((
         WebRTCTransportSender {
             inner: this_owned.clone(),
@@ -358,45 +400,97 @@ impl WebRtcInner {
     }
 
     // -- Handle Signaling
-    async fn send_answer(&self) -> bool {
+
+    /// Applies a remote SDP description (offer or answer) arriving over
+    /// either the IPC/WebSocket signaling path or the HTTP WHIP/WHEP path
+    /// (see `whip.rs`), returning its SDP type so the caller can decide
+    /// whether a local answer needs to be gathered in response.
+    async fn apply_remote_description(
+        &self,
+        description: RtcSessionDescription,
+    ) -> Result<RTCSdpType, String> {
+        info!("[SDP {}] Applying remote {:?} (signaling={:?}, ICE={:?})",
+              self.t_plus(), description.ty, self.peer.signaling_state(), self.peer.ice_connection_state());
+        log_sdp("[SDP]", &description.sdp);
+
+        if let Some(remote_clock) = clockref::ReferenceClock::parse_from_sdp(&description.sdp) {
+            let offset_ms = clockref::recover_timebase_offset_ms(clockref::ReferenceClock::now(), remote_clock);
+            info!("[SDP {}] Recovered RFC 7273 reference clock offset: {offset_ms}ms", self.t_plus());
+
+            self.video.lock().await.set_reference_clock_offset_ms(offset_ms);
+            self.audio.lock().await.set_reference_clock_offset_ms(offset_ms);
+        } else {
+            debug!("[SDP {}] Remote SDP carried no RFC 7273 reference clock, using independent timelines",
+                   self.t_plus());
+        }
+
+        let description = match &description.ty {
+            RtcSdpType::Offer => RTCSessionDescription::offer(description.sdp),
+            RtcSdpType::Answer => RTCSessionDescription::answer(description.sdp),
+            RtcSdpType::Pranswer => RTCSessionDescription::pranswer(description.sdp),
+            other => return Err(format!("Unknown SDP type: {other:?}")),
+        };
+
+        let description = description.map_err(|err| format!("Invalid RTCSessionDescription: {err:?}"))?;
+        let remote_ty = description.sdp_type;
+
+        self.peer
+            .set_remote_description(description)
+            .await
+            .map_err(|err| format!("Failed to set remote description: {err:?}"))?;
+
+        info!("[SDP {}] set_remote_description OK (signaling={:?}, ICE={:?})",
+              self.t_plus(), self.peer.signaling_state(), self.peer.ice_connection_state());
+
+        Ok(remote_ty)
+    }
+
+    /// Creates and applies a local SDP answer to the current remote offer,
+    /// returning it so the caller can deliver it over whichever transport it
+    /// arrived on (IPC/WebSocket message, or an HTTP WHIP/WHEP response body).
+    async fn gather_local_answer(&self) -> Result<RtcSessionDescription, String> {
         info!("[ANSWER {}] Creating answer (signaling={:?}, ICE={:?})",
               self.t_plus(), self.peer.signaling_state(), self.peer.ice_connection_state());
 
-        let local_description = match self.peer.create_answer(None).await {
-            Err(err) => {
-                error!("[ANSWER] Failed to create answer: {err:?}");
-                return false;
-            }
-            Ok(value) => value,
-        };
+        let mut local_description = self
+            .peer
+            .create_answer(None)
+            .await
+            .map_err(|err| format!("Failed to create answer: {err:?}"))?;
+
+        // Advertise our reference clock (RFC 7273) so the remote peer can
+        // align presentation timestamps with us instead of relying solely
+        // on independent per-stream RTP timelines.
+        local_description.sdp.push_str(&clockref::ReferenceClock::now().to_sdp_lines());
 
         log_sdp("[ANSWER]", &local_description.sdp);
 
         info!("[ANSWER {}] Calling set_local_description (signaling={:?})", self.t_plus(), self.peer.signaling_state());
-        if let Err(err) = self
-            .peer
+        self.peer
             .set_local_description(local_description.clone())
             .await
-        {
-            error!("[ANSWER] Failed to set local description: {err:?}");
-            return false;
-        }
+            .map_err(|err| format!("Failed to set local description: {err:?}"))?;
+
         info!("[ANSWER {}] set_local_description OK (signaling={:?}, ICE={:?})",
               self.t_plus(), self.peer.signaling_state(), self.peer.ice_connection_state());
 
-        if let Err(err) = self
-            .event_sender
-            .send(TransportEvent::SendIpc(StreamerIpcMessage::WebSocket(
-                StreamServerMessage::WebRtc(StreamSignalingMessage::Description(
-                    RtcSessionDescription {
-                        ty: from_webrtc_sdp(local_description.sdp_type),
-                        sdp: local_description.sdp,
-                    },
-                )),
-            )))
-            .await
-        {
-            error!("[ANSWER] Failed to send answer via WebSocket: {err:?}");
+        Ok(RtcSessionDescription {
+            ty: from_webrtc_sdp(local_description.sdp_type),
+            sdp: local_description.sdp,
+        })
+    }
+
+    async fn send_answer(&self) -> bool {
+        let answer = match self.gather_local_answer().await {
+            Ok(answer) => answer,
+            Err(err) => {
+                error!("[ANSWER] {err}");
+                return false;
+            }
+        };
+
+        if let Err(err) = self.signaller.send_description(answer).await {
+            error!("[ANSWER] Failed to send answer: {err}");
             return false;
         }
 
@@ -417,7 +511,7 @@ impl WebRtcInner {
             ice_restart: false,
             ..Default::default()
         };
-        let local_description = match self.peer.create_offer(Some(offer_options)).await {
+        let mut local_description = match self.peer.create_offer(Some(offer_options)).await {
             Err(err) => {
                 error!("[OFFER] Failed to create offer: {err:?}");
                 return false;
@@ -425,6 +519,9 @@ impl WebRtcInner {
             Ok(value) => value,
         };
 
+        // Advertise our reference clock (RFC 7273), same as `gather_local_answer`.
+        local_description.sdp.push_str(&clockref::ReferenceClock::now().to_sdp_lines());
+
         // webrtc-rs generates new ICE credentials when switching from answerer to offerer
         // role, even with ice_restart=false. This triggers an ICE restart on the browser
         // side (Chrome sees changed credentials → restarts ICE). The restart causes a brief
@@ -458,18 +555,14 @@ impl WebRtcInner {
               self.peer.ice_gathering_state());
 
         if let Err(err) = self
-            .event_sender
-            .send(TransportEvent::SendIpc(StreamerIpcMessage::WebSocket(
-                StreamServerMessage::WebRtc(StreamSignalingMessage::Description(
-                    RtcSessionDescription {
-                        ty: from_webrtc_sdp(local_description.sdp_type),
-                        sdp: local_description.sdp,
-                    },
-                )),
-            )))
+            .signaller
+            .send_description(RtcSessionDescription {
+                ty: from_webrtc_sdp(local_description.sdp_type),
+                sdp: local_description.sdp,
+            })
             .await
         {
-            error!("[OFFER] Failed to send offer via WebSocket: {err:?}");
+            error!("[OFFER] Failed to send offer: {err}");
             return false;
         };
 
@@ -489,6 +582,52 @@ impl WebRtcInner {
         }
     }
 
+    /// Core negotiation state machine entry point: applies one inbound
+    /// `SignalEvent` regardless of which `Signaller` delivered it.
+    async fn handle_signal_event(&self, event: SignalEvent) {
+        match event {
+            SignalEvent::Description(description) => {
+                let remote_ty = match self.apply_remote_description(description).await {
+                    Ok(remote_ty) => remote_ty,
+                    Err(err) => {
+                        error!("[SIGNAL] {err}");
+                        return;
+                    }
+                };
+
+                if remote_ty == RTCSdpType::Offer {
+                    info!("[SIGNAL {}] Remote SDP was OFFER, creating answer...", self.t_plus());
+                    let result = self.send_answer().await;
+                    info!("[SIGNAL {}] send_answer returned: {} (ICE={:?})", self.t_plus(), result, self.peer.ice_connection_state());
+                } else if remote_ty == RTCSdpType::Answer {
+                    info!("[SIGNAL {}] Remote SDP was ANSWER, notifying renegotiation waiter (ICE={:?})",
+                          self.t_plus(), self.peer.ice_connection_state());
+                    self.answer_received.notify_one();
+                }
+            }
+            SignalEvent::AddIceCandidate(candidate) => {
+                info!("[SIGNAL] Received remote ICE candidate: {} ufrag={:?} (ICE={:?})",
+                      candidate.candidate, candidate.username_fragment,
+                      self.peer.ice_connection_state());
+
+                if let Err(err) = self
+                    .peer
+                    .add_ice_candidate(RTCIceCandidateInit {
+                        candidate: candidate.candidate.clone(),
+                        sdp_mid: candidate.sdp_mid.clone(),
+                        sdp_mline_index: candidate.sdp_mline_index,
+                        username_fragment: candidate.username_fragment.clone(),
+                    })
+                    .await
+                {
+                    error!("[SIGNAL] Failed to add ICE candidate: {err:?} (candidate={})", candidate.candidate);
+                } else {
+                    info!("[SIGNAL] Added remote ICE candidate OK (ICE={:?})", self.peer.ice_connection_state());
+                }
+            }
+        }
+    }
+
     async fn on_ws_message(&self, message: StreamClientMessage) {
         match message {
             StreamClientMessage::StartStream {
@@ -505,6 +644,11 @@ impl WebRtcInner {
             } => {
                 info!("[WS {}] StartStream: {}x{}@{}fps bitrate={}", self.t_plus(), width, height, fps, bitrate);
 
+                {
+                    let mut bwe = self.bwe.lock().await;
+                    bwe.set_max_bitrate_bps(bitrate as u64);
+                }
+
                 let video_supported_formats = SupportedVideoFormats::from_bits(video_supported_formats).unwrap_or_else(|| {
                     warn!("[WS] Failed to deserialize SupportedVideoFormats: {video_supported_formats}, falling back to only H264");
                     SupportedVideoFormats::H264
@@ -536,66 +680,61 @@ impl WebRtcInner {
                     error!("[WS] Failed to send StartStream: {err}");
                 }
             }
-            StreamClientMessage::WebRtc(StreamSignalingMessage::Description(description)) => {
-                info!("[WS {}] Received SDP {:?} (signaling={:?}, ICE={:?})",
-                      self.t_plus(), description.ty, self.peer.signaling_state(), self.peer.ice_connection_state());
-                log_sdp("[WS]", &description.sdp);
-
-                let description = match &description.ty {
-                    RtcSdpType::Offer => RTCSessionDescription::offer(description.sdp),
-                    RtcSdpType::Answer => RTCSessionDescription::answer(description.sdp),
-                    RtcSdpType::Pranswer => RTCSessionDescription::pranswer(description.sdp),
-                    _ => {
-                        error!("[WS] Unknown SDP type: {:?}", description.ty);
-                        return;
-                    }
-                };
-
-                let Ok(description) = description else {
-                    error!("[WS] Invalid RTCSessionDescription");
-                    return;
-                };
-
-                let remote_ty = description.sdp_type;
+            StreamClientMessage::ReconfigureStream {
+                bitrate,
+                fps,
+                width,
+                height,
+                video_supported_formats,
+                hdr,
+            } => {
+                info!("[WS {}] ReconfigureStream: {}x{}@{}fps bitrate={} hdr={}",
+                      self.t_plus(), width, height, fps, bitrate, hdr);
 
-                if let Err(err) = self.peer.set_remote_description(description).await {
-                    error!("[WS] Failed to set remote description: {err:?}");
-                    return;
+                {
+                    let mut bwe = self.bwe.lock().await;
+                    bwe.set_max_bitrate_bps(bitrate as u64);
                 }
 
-                info!("[WS {}] set_remote_description OK (signaling={:?}, ICE={:?})",
-                      self.t_plus(), self.peer.signaling_state(), self.peer.ice_connection_state());
+                let video_supported_formats = SupportedVideoFormats::from_bits(video_supported_formats).unwrap_or_else(|| {
+                    warn!("[WS] Failed to deserialize SupportedVideoFormats: {video_supported_formats}, falling back to only H264");
+                    SupportedVideoFormats::H264
+                });
 
-                if remote_ty == RTCSdpType::Offer {
-                    info!("[WS {}] Remote SDP was OFFER, creating answer...", self.t_plus());
-                    let result = self.send_answer().await;
-                    info!("[WS {}] send_answer returned: {} (ICE={:?})", self.t_plus(), result, self.peer.ice_connection_state());
-                } else if remote_ty == RTCSdpType::Answer {
-                    info!("[WS {}] Remote SDP was ANSWER, notifying renegotiation waiter (ICE={:?})",
-                          self.t_plus(), self.peer.ice_connection_state());
-                    self.answer_received.notify_one();
-                }
-            }
-            StreamClientMessage::WebRtc(StreamSignalingMessage::AddIceCandidate(description)) => {
-                info!("[WS] Received remote ICE candidate: {} ufrag={:?} (ICE={:?})",
-                      description.candidate, description.username_fragment,
-                      self.peer.ice_connection_state());
+                // Only a codec or HDR toggle changes the negotiated media
+                // topology; bitrate/fps/resolution within it go to
+                // `WebRtcVideo::reconfigure` (which only logs them), skipping
+                // the offer/answer dance (and the ICE-restart hiccup
+                // described in `send_offer`).
+                let needs_renegotiation = {
+                    let video = self.video.lock().await;
+                    video.supported_formats() != video_supported_formats || video.is_hdr() != hdr
+                };
 
-                if let Err(err) = self
-                    .peer
-                    .add_ice_candidate(RTCIceCandidateInit {
-                        candidate: description.candidate.clone(),
-                        sdp_mid: description.sdp_mid.clone(),
-                        sdp_mline_index: description.sdp_mline_index,
-                        username_fragment: description.username_fragment.clone(),
-                    })
-                    .await
                 {
-                    error!("[WS] Failed to add ICE candidate: {err:?} (candidate={})", description.candidate);
+                    let mut video = self.video.lock().await;
+                    video.reconfigure(width, height, fps, bitrate as u64);
+                }
+
+                if needs_renegotiation {
+                    info!("[WS {}] ReconfigureStream changes codec/HDR topology, renegotiating...", self.t_plus());
+                    {
+                        let mut video = self.video.lock().await;
+                        video.set_codecs(video_supported_formats).await;
+                        video.set_hdr(hdr);
+                    }
+                    let result = self.send_offer().await;
+                    info!("[WS {}] ReconfigureStream renegotiation returned: {result}", self.t_plus());
                 } else {
-                    info!("[WS] Added remote ICE candidate OK (ICE={:?})", self.peer.ice_connection_state());
+                    info!("[WS {}] ReconfigureStream applied in-band, no renegotiation needed", self.t_plus());
                 }
             }
+            StreamClientMessage::WebRtc(StreamSignalingMessage::Description(description)) => {
+                self.handle_signal_event(SignalEvent::Description(description)).await;
+            }
+            StreamClientMessage::WebRtc(StreamSignalingMessage::AddIceCandidate(candidate)) => {
+                self.handle_signal_event(SignalEvent::AddIceCandidate(candidate)).await;
+            }
             _ => {}
         }
     }
@@ -645,22 +784,15 @@ impl WebRtcInner {
         info!("[ICE-CAND] Sending local candidate: {} (ICE={:?})",
               candidate_json.candidate, self.peer.ice_connection_state());
 
-        let message =
-            StreamServerMessage::WebRtc(StreamSignalingMessage::AddIceCandidate(RtcIceCandidate {
-                candidate: candidate_json.candidate.clone(),
-                sdp_mid: candidate_json.sdp_mid,
-                sdp_mline_index: candidate_json.sdp_mline_index,
-                username_fragment: candidate_json.username_fragment,
-            }));
+        let candidate = RtcIceCandidate {
+            candidate: candidate_json.candidate.clone(),
+            sdp_mid: candidate_json.sdp_mid,
+            sdp_mline_index: candidate_json.sdp_mline_index,
+            username_fragment: candidate_json.username_fragment,
+        };
 
-        if let Err(err) = self
-            .event_sender
-            .send(TransportEvent::SendIpc(StreamerIpcMessage::WebSocket(
-                message,
-            )))
-            .await
-        {
-            error!("[ICE-CAND] Failed to send ICE candidate to browser: {err:?}");
+        if let Err(err) = self.signaller.send_ice_candidate(candidate).await {
+            error!("[ICE-CAND] Failed to send ICE candidate to browser: {err}");
         }
     }
 
@@ -731,6 +863,25 @@ impl WebRtcInner {
         };
     }
 
+    /// Feeds one incoming RTCP packet (read off any track's RTCP reader)
+    /// through the TWCC-based bandwidth estimator. When the target bitrate
+    /// changes, it is published to `bwe_target_bitrate_bps` and handed to
+    /// `WebRtcVideo::set_target_bitrate_bps`, which only logs it; the host's
+    /// encoder is not told.
+    async fn on_rtcp_packet(self: &Arc<Self>, packet: Box<dyn webrtc::rtcp::packet::Packet + Send + Sync>) {
+        let mut bwe = self.bwe.lock().await;
+        let before = bwe.target_bitrate_bps();
+        bwe::on_transport_feedback(packet.as_ref(), &self.bwe_send_times, &mut bwe);
+        let after = bwe.target_bitrate_bps();
+        drop(bwe);
+
+        if after != before {
+            self.bwe_target_bitrate_bps.store(after, Ordering::Relaxed);
+            let mut video = self.video.lock().await;
+            video.set_target_bitrate_bps(after);
+        }
+    }
+
     async fn close_stats(&self) {
         let mut stats = self.stats_channel.lock().await;
 
@@ -822,7 +973,9 @@ impl TransportSender for WebRTCTransportSender {
     async fn send_audio_sample(&self, data: &[u8]) -> Result<(), TransportError> {
         let mut audio = self.inner.audio.lock().await;
 
-        audio.send_audio_sample(data).await;
+        // No PTS is available from this callback today, so audio falls back
+        // to its own running sample-clock for RTP timestamping.
+        audio.send_audio_sample(data, None).await;
 
         Ok(())
     }
@@ -1,7 +1,10 @@
 use std::{
     collections::VecDeque,
-    sync::{Arc, Weak},
-    time::{SystemTime, UNIX_EPOCH},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU16, AtomicU32, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::anyhow;
@@ -20,9 +23,13 @@ use webrtc::{
         extension::{
             HeaderExtension, abs_send_time_extension::AbsSendTimeExtension,
             playout_delay_extension::PlayoutDelayExtension,
+            transport_cc_extension::TransportCcExtension,
         },
     },
-    rtp_transceiver::rtp_codec::{RTCRtpHeaderExtensionCapability, RTPCodecType},
+    rtp_transceiver::{
+        RTCRtpSender,
+        rtp_codec::{RTCRtpCodecParameters, RTCRtpHeaderExtensionCapability, RTPCodecType},
+    },
     sdp::extmap::ABS_SEND_TIME_URI,
     track::track_local::{
         TrackLocal, track_local_static_rtp::TrackLocalStaticRTP,
@@ -30,8 +37,49 @@ use webrtc::{
     },
 };
 
+use crate::transport::webrtc::bwe::{SendTimeTracker, TRANSPORT_CC_URI};
+
+/// How much of the current BWE target bitrate's worth of non-important
+/// frames we're willing to queue before admission control starts dropping
+/// new ones - a small jitter-buffer's worth of budget rather than letting
+/// the queue grow unboundedly behind a congested link.
+const QUEUE_BUDGET_WINDOW: Duration = Duration::from_millis(200);
+
 const PLAYOUT_DELAY_URI: &str = "http://www.webrtc.org/experiments/rtp-hdrext/playout-delay";
 
+/// Playout-delay bounds (milliseconds) carried by the `playout-delay`
+/// header extension, either as a track's default policy or a per-frame
+/// override passed to `send_samples`. Interactive screen control wants
+/// `ZERO` (the latency-minimizing behavior this track used to hardcode);
+/// a playback-style stream can widen the range to smooth over jitter at
+/// the cost of latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayoutDelayMs {
+    pub min_ms: u16,
+    pub max_ms: u16,
+}
+
+impl PlayoutDelayMs {
+    pub const ZERO: Self = Self { min_ms: 0, max_ms: 0 };
+
+    /// The extension encodes both bounds as 12-bit, 10ms-granularity units.
+    fn to_extension_units(self) -> (u16, u16) {
+        let to_units = |ms: u16| (ms / 10).min(4095);
+        (to_units(self.min_ms), to_units(self.max_ms))
+    }
+
+    fn pack(self) -> u32 {
+        (self.min_ms as u32) | ((self.max_ms as u32) << 16)
+    }
+
+    fn unpack(bits: u32) -> Self {
+        Self {
+            min_ms: (bits & 0xFFFF) as u16,
+            max_ms: (bits >> 16) as u16,
+        }
+    }
+}
+
 pub fn register_header_extensions(api_media: &mut MediaEngine) -> Result<(), webrtc::Error> {
     api_media.register_header_extension(
         RTCRtpHeaderExtensionCapability {
@@ -63,6 +111,23 @@ pub fn register_header_extensions(api_media: &mut MediaEngine) -> Result<(), web
         None,
     )?;
 
+    // Required for transport-wide congestion control-based bandwidth
+    // estimation (see `bwe.rs`).
+    api_media.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: TRANSPORT_CC_URI.to_string(),
+        },
+        RTPCodecType::Video,
+        None,
+    )?;
+    api_media.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: TRANSPORT_CC_URI.to_string(),
+        },
+        RTPCodecType::Audio,
+        None,
+    )?;
+
     Ok(())
 }
 
@@ -75,6 +140,11 @@ where
     channel_queue_size: usize,
     new_samples_notify: Arc<Notify>,
     queue: Arc<Mutex<VecDeque<FrameSamples<Track>>>>,
+    transport_sequence: Arc<AtomicU16>,
+    send_time_tracker: Option<Arc<SendTimeTracker>>,
+    target_bitrate_bps: Option<Arc<AtomicU64>>,
+    playout_delay_policy: Arc<AtomicU32>,
+    rtp_sender: Option<Arc<RTCRtpSender>>,
 }
 
 struct FrameSamples<Track>
@@ -82,6 +152,8 @@ where
     Track: TrackLike,
 {
     important: bool,
+    byte_len: usize,
+    playout_delay: Option<PlayoutDelayMs>,
     samples: Vec<Track::Sample>,
 }
 
@@ -96,9 +168,32 @@ where
             channel_queue_size,
             new_samples_notify: Default::default(),
             queue: Default::default(),
+            transport_sequence: Default::default(),
+            send_time_tracker: None,
+            target_bitrate_bps: None,
+            playout_delay_policy: Arc::new(AtomicU32::new(PlayoutDelayMs::ZERO.pack())),
+            rtp_sender: None,
         }
     }
 
+    /// Sets the default playout-delay policy applied to frames that don't
+    /// pass their own hint to `send_samples`.
+    pub fn set_playout_delay_policy(&mut self, policy: PlayoutDelayMs) {
+        self.playout_delay_policy.store(policy.pack(), Ordering::Relaxed);
+    }
+
+    /// Enables transport-wide congestion control on this track: every
+    /// outgoing packet is tagged with a transport sequence number and its
+    /// send time recorded in `tracker`, so incoming TWCC feedback can be
+    /// matched back up to it (see `bwe.rs`). `target_bitrate_bps` is the
+    /// shared cell the estimator's current target is published to - used by
+    /// `send_samples` to admission-control non-important frames against the
+    /// actual available bandwidth instead of a fixed queue depth.
+    pub fn set_bandwidth_estimation(&mut self, tracker: Arc<SendTimeTracker>, target_bitrate_bps: Arc<AtomicU64>) {
+        self.send_time_tracker = Some(tracker);
+        self.target_bitrate_bps = Some(target_bitrate_bps);
+    }
+
     pub async fn create_track(
         &mut self,
         track: Track,
@@ -123,11 +218,22 @@ where
 
         let new_samples_notify = self.new_samples_notify.clone();
         let queue = Arc::downgrade(&self.queue);
+        let transport_sequence = self.transport_sequence.clone();
+        let send_time_tracker = self.send_time_tracker.clone();
+        let playout_delay_policy = self.playout_delay_policy.clone();
         info!("[TRACK-SENDER] Spawning sample_sender task...");
         self.runtime.spawn({
             let track = track.clone();
             async move {
-                sample_sender(track, &new_samples_notify, queue).await;
+                sample_sender(
+                    track,
+                    &new_samples_notify,
+                    queue,
+                    transport_sequence,
+                    send_time_tracker,
+                    playout_delay_policy,
+                )
+                .await;
             }
         });
         info!("[TRACK-SENDER] sample_sender task spawned");
@@ -150,6 +256,8 @@ where
         info!("[TRACK-SENDER] Peer signaling state AFTER add_track: {:?}", peer.signaling_state());
         info!("[TRACK-SENDER] Peer ICE connection state AFTER add_track: {:?}", peer.ice_connection_state());
 
+        self.rtp_sender = Some(track_sender.clone());
+
         // Read incoming RTCP packets
         // Before these packets are returned they are processed by interceptors. For things
         // like NACK this needs to be called.
@@ -169,25 +277,59 @@ where
         Ok(())
     }
 
-    /// Returns if the frame will be delivered
-    pub async fn send_samples(&self, samples: Vec<Track::Sample>, important: bool) -> bool {
-        let mut queue = self.queue.lock().await;
+    /// Returns the codec parameters the peer's SDP answer actually accepted
+    /// for this track, once negotiation has completed. `None` before
+    /// `create_track` has run or before the peer has answered.
+    pub async fn negotiated_codec(&self) -> Option<RTCRtpCodecParameters> {
+        let sender = self.rtp_sender.as_ref()?;
+        sender.get_parameters().await.rtp_parameters.codecs.into_iter().next()
+    }
 
-        let result = if important {
-            queue.push_front(FrameSamples { important, samples });
-            true
-        } else {
-            if queue.len() > self.channel_queue_size {
-                return false;
-            }
+    /// Returns if the frame will be delivered. `playout_delay` overrides the
+    /// sender's default policy (see `set_playout_delay_policy`) for this
+    /// frame only, e.g. to request near-zero delay for one interactive
+    /// input-driven frame within an otherwise buffered, playback-style
+    /// stream.
+    pub async fn send_samples(
+        &self,
+        samples: Vec<Track::Sample>,
+        important: bool,
+        playout_delay: Option<PlayoutDelayMs>,
+    ) -> bool {
+        let byte_len: usize = samples.iter().map(Track::sample_len).sum();
+        let mut queue = self.queue.lock().await;
 
-            queue.push_front(FrameSamples { important, samples });
-            true
-        };
+        if !important && !self.admits_ordinary_frame(&queue, byte_len) {
+            return false;
+        }
 
+        queue.push_front(FrameSamples { important, byte_len, playout_delay, samples });
         self.new_samples_notify.notify_waiters();
 
-        result
+        true
+    }
+
+    /// Whether a non-important frame of `byte_len` bytes can be enqueued
+    /// right now. Once a BWE target bitrate is available (via
+    /// `set_bandwidth_estimation`), this admits it only if the queue's
+    /// already-pending non-important bytes plus this frame stay within
+    /// `QUEUE_BUDGET_WINDOW`'s worth of that target bitrate; otherwise it
+    /// falls back to the original fixed queue-depth check.
+    fn admits_ordinary_frame(&self, queue: &VecDeque<FrameSamples<Track>>, byte_len: usize) -> bool {
+        let target_bitrate_bps = self
+            .target_bitrate_bps
+            .as_ref()
+            .map(|target| target.load(Ordering::Relaxed))
+            .filter(|bps| *bps > 0);
+
+        match target_bitrate_bps {
+            Some(target_bitrate_bps) => {
+                let budget_bytes = (target_bitrate_bps as f64 / 8.0 * QUEUE_BUDGET_WINDOW.as_secs_f64()) as usize;
+                let queued_bytes: usize = queue.iter().filter(|frame| !frame.important).map(|frame| frame.byte_len).sum();
+                queued_bytes + byte_len <= budget_bytes
+            }
+            None => queue.len() <= self.channel_queue_size,
+        }
     }
 
     /// Returns if the frame will be delivered
@@ -206,6 +348,9 @@ async fn sample_sender<Track>(
     track: Arc<Track>,
     new_samples_notify: &Notify,
     queue: Weak<Mutex<VecDeque<FrameSamples<Track>>>>,
+    transport_sequence: Arc<AtomicU16>,
+    send_time_tracker: Option<Arc<SendTimeTracker>>,
+    playout_delay_policy: Arc<AtomicU32>,
 ) where
     Track: TrackLike,
 {
@@ -233,19 +378,28 @@ async fn sample_sender<Track>(
         let now_secs = now.as_secs() as f64 + now.subsec_nanos() as f64 * 1e-9;
         let abs_send_time: u64 = (now_secs * 262_144.0) as u64;
 
+        let playout_delay = frame
+            .playout_delay
+            .unwrap_or_else(|| PlayoutDelayMs::unpack(playout_delay_policy.load(Ordering::Relaxed)));
+        let (playout_delay_min, playout_delay_max) = playout_delay.to_extension_units();
+
         for sample in frame.samples {
-            if let Err(err) = track
-                .write_with_extensions(
-                    sample,
-                    &[
-                        HeaderExtension::PlayoutDelay(PlayoutDelayExtension::new(0, 0)),
-                        HeaderExtension::AbsSendTime(AbsSendTimeExtension {
-                            timestamp: abs_send_time,
-                        }),
-                    ],
-                )
-                .await
-            {
+            let mut extensions = vec![
+                HeaderExtension::PlayoutDelay(PlayoutDelayExtension::new(playout_delay_min, playout_delay_max)),
+                HeaderExtension::AbsSendTime(AbsSendTimeExtension {
+                    timestamp: abs_send_time,
+                }),
+            ];
+
+            if let Some(tracker) = &send_time_tracker {
+                let sequence = transport_sequence.fetch_add(1, Ordering::Relaxed);
+                tracker.record_sent(sequence, Instant::now());
+                extensions.push(HeaderExtension::TransportCc(TransportCcExtension {
+                    transport_sequence: sequence,
+                }));
+            }
+
+            if let Err(err) = track.write_with_extensions(sample, &extensions).await {
                 warn!("[Stream]: track.write_sample failed: {err}");
             }
         }
@@ -262,6 +416,10 @@ pub trait TrackLike: Send + Sync + 'static {
     ) -> impl Future<Output = Result<(), anyhow::Error>> + Send;
 
     fn track(self: Arc<Self>) -> Arc<dyn TrackLocal + Send + Sync + 'static>;
+
+    /// Payload size of one sample, used to weigh the BWE-driven admission
+    /// budget in `send_samples`.
+    fn sample_len(sample: &Self::Sample) -> usize;
 }
 
 impl TrackLike for TrackLocalStaticSample {
@@ -280,6 +438,10 @@ impl TrackLike for TrackLocalStaticSample {
     fn track(self: Arc<Self>) -> Arc<dyn TrackLocal + Send + Sync + 'static> {
         self
     }
+
+    fn sample_len(sample: &Self::Sample) -> usize {
+        sample.data.len()
+    }
 }
 
 pub struct SequencedTrackLocalStaticRTP {
@@ -331,4 +493,8 @@ impl TrackLike for SequencedTrackLocalStaticRTP {
     fn track(self: Arc<Self>) -> Arc<dyn TrackLocal + Send + Sync + 'static> {
         self.track.clone()
     }
+
+    fn sample_len(sample: &Self::Sample) -> usize {
+        sample.payload.len()
+    }
 }
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use common::{
+    api_bindings::{RtcIceCandidate, RtcSessionDescription, StreamServerMessage, StreamSignalingMessage},
+    ipc::StreamerIpcMessage,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::transport::TransportEvent;
+
+/// A signaling event arriving from the remote peer, independent of the wire
+/// protocol used to deliver it.
+#[derive(Debug, Clone)]
+pub enum SignalEvent {
+    Description(RtcSessionDescription),
+    AddIceCandidate(RtcIceCandidate),
+}
+
+/// Delivers local SDP/ICE signaling to the remote peer and surfaces inbound
+/// signaling events, decoupling `WebRtcInner`'s negotiation state machine
+/// from the wire protocol used to exchange them.
+///
+/// The default implementation is `IpcSignaller`, speaking the existing
+/// `StreamClientMessage`/`StreamServerMessage` protocol over the IPC bridge
+/// to the browser's WebSocket connection. A room-based signaller
+/// (LiveKit-style token/room join, a Janus-style plugin handshake, ...) can
+/// implement this trait instead, without any change to how offers, answers,
+/// and ICE candidates are negotiated.
+#[async_trait]
+pub trait Signaller: Send + Sync {
+    /// Sends a local SDP offer/answer to the remote peer.
+    async fn send_description(&self, description: RtcSessionDescription) -> Result<(), String>;
+
+    /// Sends a local ICE candidate to the remote peer.
+    async fn send_ice_candidate(&self, candidate: RtcIceCandidate) -> Result<(), String>;
+
+    /// Waits for the next inbound signaling event. Returns `None` once the
+    /// signaling channel is closed for good.
+    async fn recv(&self) -> Option<SignalEvent>;
+}
+
+/// Default `Signaller`: the existing proprietary protocol carried over the
+/// streamer's IPC bridge.
+///
+/// Inbound events for this implementation normally arrive via
+/// `WebRtcInner::on_ws_message` (itself driven by the generic
+/// `TransportEvents::on_ipc_message` push), which decodes them and calls
+/// straight into the negotiation state machine; `recv()` only exists to
+/// satisfy the `Signaller` contract and never resolves here.
+pub struct IpcSignaller {
+    event_sender: Sender<TransportEvent>,
+}
+
+impl IpcSignaller {
+    pub fn new(event_sender: Sender<TransportEvent>) -> Self {
+        Self { event_sender }
+    }
+}
+
+#[async_trait]
+impl Signaller for IpcSignaller {
+    async fn send_description(&self, description: RtcSessionDescription) -> Result<(), String> {
+        self.event_sender
+            .send(TransportEvent::SendIpc(StreamerIpcMessage::WebSocket(
+                StreamServerMessage::WebRtc(StreamSignalingMessage::Description(description)),
+            )))
+            .await
+            .map_err(|err| format!("Failed to send description via IPC: {err:?}"))
+    }
+
+    async fn send_ice_candidate(&self, candidate: RtcIceCandidate) -> Result<(), String> {
+        self.event_sender
+            .send(TransportEvent::SendIpc(StreamerIpcMessage::WebSocket(
+                StreamServerMessage::WebRtc(StreamSignalingMessage::AddIceCandidate(candidate)),
+            )))
+            .await
+            .map_err(|err| format!("Failed to send ICE candidate via IPC: {err:?}"))
+    }
+
+    async fn recv(&self) -> Option<SignalEvent> {
+        std::future::pending().await
+    }
+}
@@ -0,0 +1,124 @@
+use std::{sync::Weak, time::Duration};
+
+use log::{debug, trace, warn};
+use serde::Serialize;
+use tokio::{spawn, time::sleep};
+use webrtc::stats::{StatsReportType, StatsReport};
+
+use crate::transport::webrtc::WebRtcInner;
+
+/// How often a stats snapshot is collected and pushed over the stats
+/// data channel.
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Compact quality snapshot pushed to the frontend's live dashboard, and the
+/// prerequisite signal for any adaptive-bitrate logic.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSnapshot {
+    pub bytes_sent: u64,
+    pub packets_sent: u64,
+    pub round_trip_time_ms: Option<f64>,
+    pub nack_count: u64,
+    pub pli_count: u64,
+    pub jitter: Option<f64>,
+    pub outbound_bitrate_bps: Option<f64>,
+    pub selected_candidate_pair: Option<SelectedCandidatePair>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedCandidatePair {
+    pub local_candidate_id: String,
+    pub remote_candidate_id: String,
+    pub current_round_trip_time: Option<f64>,
+}
+
+/// Spawns the background collection loop. Holds only a `Weak<WebRtcInner>`
+/// so the loop exits on its own once the peer is dropped.
+pub fn spawn_stats_loop(inner: Weak<WebRtcInner>) {
+    spawn(async move {
+        loop {
+            let Some(inner) = inner.upgrade() else {
+                debug!("[STATS] Stopping stats loop: peer deallocated");
+                return;
+            };
+
+            inner.collect_and_send_stats().await;
+
+            drop(inner);
+            sleep(STATS_INTERVAL).await;
+        }
+    });
+}
+
+/// Builds a [`StatsSnapshot`] out of a raw `RTCPeerConnection::get_stats()`
+/// report by walking the outbound-rtp, remote-inbound-rtp, candidate-pair,
+/// and transport entries.
+pub fn build_snapshot(report: &StatsReport) -> StatsSnapshot {
+    let mut snapshot = StatsSnapshot::default();
+    let mut selected_pair_id: Option<String> = None;
+
+    for stat in report.reports.values() {
+        match stat {
+            StatsReportType::OutboundRTP(outbound) => {
+                snapshot.bytes_sent += outbound.bytes_sent;
+                snapshot.packets_sent += outbound.packets_sent;
+                snapshot.nack_count += outbound.nack_count as u64;
+                snapshot.pli_count += outbound.pli_count as u64;
+                if outbound.frames_per_second > 0.0 {
+                    snapshot.outbound_bitrate_bps =
+                        Some(snapshot.outbound_bitrate_bps.unwrap_or(0.0) + outbound.bytes_sent as f64 * 8.0);
+                }
+            }
+            StatsReportType::RemoteInboundRTP(remote_inbound) => {
+                snapshot.round_trip_time_ms = remote_inbound
+                    .round_trip_time
+                    .map(|rtt| rtt * 1000.0)
+                    .or(snapshot.round_trip_time_ms);
+                snapshot.jitter = Some(remote_inbound.jitter).or(snapshot.jitter);
+            }
+            StatsReportType::Transport(transport) => {
+                selected_pair_id = transport.selected_candidate_pair_id.clone();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(pair_id) = selected_pair_id {
+        if let Some(StatsReportType::CandidatePair(pair)) = report.reports.get(&pair_id) {
+            snapshot.selected_candidate_pair = Some(SelectedCandidatePair {
+                local_candidate_id: pair.local_candidate_id.clone(),
+                remote_candidate_id: pair.remote_candidate_id.clone(),
+                current_round_trip_time: Some(pair.current_round_trip_time),
+            });
+        }
+    }
+
+    snapshot
+}
+
+impl WebRtcInner {
+    pub(super) async fn collect_and_send_stats(&self) {
+        let stats_channel = self.stats_channel.lock().await.clone();
+        let Some(stats_channel) = stats_channel else {
+            trace!("[STATS] No stats channel open yet, skipping collection");
+            return;
+        };
+
+        let report = self.peer.get_stats().await;
+        let snapshot = build_snapshot(&report);
+
+        let payload = match serde_json::to_vec(&snapshot) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("[STATS] Failed to serialize stats snapshot: {err:?}");
+                return;
+            }
+        };
+
+        if let Err(err) = stats_channel.send(&bytes::Bytes::from(payload)).await {
+            warn!("[STATS] Failed to send stats snapshot: {err:?}");
+        }
+    }
+}
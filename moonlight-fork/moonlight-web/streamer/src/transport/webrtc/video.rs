@@ -0,0 +1,259 @@
+use std::sync::{Arc, Weak};
+
+use bytes::Bytes;
+use log::{error, info, warn};
+use moonlight_common::stream::{
+    bindings::{DecodeResult, SupportedVideoFormats, VideoDecodeUnit},
+    video::VideoSetup,
+};
+use tokio::runtime::Handle;
+use webrtc::{
+    api::media_engine::{MIME_TYPE_H264, MIME_TYPE_VP8, MIME_TYPE_VP9, MediaEngine},
+    peer_connection::RTCPeerConnection,
+    rtp::{self, header::Header},
+    rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType},
+    track::track_local::track_local_static_rtp::TrackLocalStaticRTP,
+};
+
+use crate::transport::webrtc::{
+    WebRtcInner,
+    sender::{PlayoutDelayMs, SequencedTrackLocalStaticRTP, TrackLocalSender},
+};
+
+/// AV1 isn't part of `webrtc`'s built-in MIME type constants; its registered
+/// media type per RFC is simply `video/AV1`.
+const MIME_TYPE_AV1: &str = "video/AV1";
+
+/// Video RTP clock rate mandated for all of H.264/VP8/VP9/AV1 by their
+/// respective WebRTC payload format RFCs.
+const VIDEO_CLOCK_RATE: u32 = 90_000;
+
+/// A WebRTC-negotiable video codec, in the same spirit as a
+/// `--video-codec one of: vp9, vp8, h264` CLI selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoCodec {
+    H264,
+    Vp8,
+    Vp9,
+    Av1,
+}
+
+impl VideoCodec {
+    fn mime_type(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => MIME_TYPE_H264,
+            VideoCodec::Vp8 => MIME_TYPE_VP8,
+            VideoCodec::Vp9 => MIME_TYPE_VP9,
+            VideoCodec::Av1 => MIME_TYPE_AV1,
+        }
+    }
+
+    fn payload_type(self) -> u8 {
+        match self {
+            VideoCodec::H264 => 102,
+            VideoCodec::Vp8 => 96,
+            VideoCodec::Vp9 => 98,
+            VideoCodec::Av1 => 45,
+        }
+    }
+
+    fn sdp_fmtp_line(self) -> &'static str {
+        match self {
+            // Constrained baseline, packetization-mode=1: the broadest
+            // browser/hardware-decoder compatibility.
+            VideoCodec::H264 => {
+                "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42e01f"
+            }
+            VideoCodec::Vp8 => "",
+            VideoCodec::Vp9 => "profile-id=0",
+            VideoCodec::Av1 => "profile=0;level-idx=5;tier=0",
+        }
+    }
+
+    /// The registration/offer priority order for this transport, with
+    /// `preferred` moved to the front and the rest kept in a fixed,
+    /// broadest-compatibility-first fallback order.
+    fn priority_order(preferred: VideoCodec) -> Vec<VideoCodec> {
+        let mut order = vec![VideoCodec::H264, VideoCodec::Vp8, VideoCodec::Vp9, VideoCodec::Av1];
+        order.retain(|codec| *codec != preferred);
+        order.insert(0, preferred);
+        order
+    }
+
+    /// Maps the moonlight-side bitstream format the host is actually
+    /// encoding to the matching already-registered WebRTC video codec, since
+    /// this transport relays the host's encoded bitstream as-is rather than
+    /// transcoding it. Only H.264 is mapped; any other format set yields
+    /// `None`, and `setup` then uses H.264.
+    fn from_supported_formats(formats: SupportedVideoFormats) -> Option<VideoCodec> {
+        if formats.contains(SupportedVideoFormats::H264) {
+            Some(VideoCodec::H264)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn register_video_codecs(
+    media_engine: &mut MediaEngine,
+    preferred: VideoCodec,
+) -> Result<(), webrtc::Error> {
+    for codec in VideoCodec::priority_order(preferred) {
+        media_engine.register_codec(
+            RTCRtpCodecParameters {
+                capability: RTCRtpCodecCapability {
+                    mime_type: codec.mime_type().to_owned(),
+                    clock_rate: VIDEO_CLOCK_RATE,
+                    channels: 0,
+                    sdp_fmtp_line: codec.sdp_fmtp_line().to_owned(),
+                    rtcp_feedback: vec![],
+                },
+                payload_type: codec.payload_type(),
+                ..Default::default()
+            },
+            RTPCodecType::Video,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub struct WebRtcVideo {
+    sender: TrackLocalSender<SequencedTrackLocalStaticRTP>,
+    supported_formats: SupportedVideoFormats,
+    active_codec: Option<VideoCodec>,
+    hdr: bool,
+    reference_clock_offset_ms: i64,
+    rtp_timestamp: u32,
+}
+
+impl WebRtcVideo {
+    pub fn new(runtime: Handle, peer: Weak<RTCPeerConnection>, channel_queue_size: usize) -> Self {
+        Self {
+            sender: TrackLocalSender::new(runtime, peer, channel_queue_size),
+            supported_formats: SupportedVideoFormats::H264,
+            active_codec: None,
+            hdr: false,
+            reference_clock_offset_ms: 0,
+            rtp_timestamp: 0,
+        }
+    }
+
+    pub fn set_reference_clock_offset_ms(&mut self, offset_ms: i64) {
+        self.reference_clock_offset_ms = offset_ms;
+    }
+
+    /// Sets the playout-delay bounds advertised for frames sent from now on;
+    /// see `TrackLocalSender::set_playout_delay_policy`.
+    pub fn set_playout_delay_policy(&mut self, policy: PlayoutDelayMs) {
+        self.sender.set_playout_delay_policy(policy);
+    }
+
+    pub fn supported_formats(&self) -> SupportedVideoFormats {
+        self.supported_formats
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        self.hdr
+    }
+
+    pub fn set_hdr(&mut self, hdr: bool) {
+        self.hdr = hdr;
+    }
+
+    /// Records which moonlight video bitstream format the client negotiated
+    /// with the host, so track (re)creation in `setup` can pick the matching
+    /// WebRTC codec rather than assuming H.264.
+    pub async fn set_codecs(&mut self, formats: SupportedVideoFormats) {
+        self.supported_formats = formats;
+        self.active_codec = VideoCodec::from_supported_formats(formats);
+    }
+
+    /// Receives an in-band resolution/framerate/bitrate change that doesn't
+    /// require renegotiation (see `ReconfigureStream` in `mod.rs`). It only
+    /// logs the new parameters; nothing is forwarded to the host's encoder.
+    pub fn reconfigure(&mut self, width: u32, height: u32, fps: u32, bitrate_bps: u64) {
+        info!(
+            "[VIDEO] Reconfigured to {}x{}@{}fps, {} bps (encoder-side; no WebRTC renegotiation needed)",
+            width, height, fps, bitrate_bps
+        );
+    }
+
+    /// Called when the TWCC-based bandwidth estimator's target bitrate
+    /// changes. It only logs the new target; nothing is forwarded to the
+    /// host's encoder.
+    pub fn set_target_bitrate_bps(&mut self, bitrate_bps: u64) {
+        info!("[VIDEO] New target bitrate from bandwidth estimator: {bitrate_bps} bps");
+    }
+
+    pub async fn setup(&mut self, inner: &Arc<WebRtcInner>, setup: VideoSetup) -> bool {
+        info!("[VIDEO-SETUP {}] ========== VIDEO SETUP STARTING ==========", inner.t_plus());
+
+        let codec = self.active_codec.unwrap_or(VideoCodec::H264);
+        info!("[VIDEO-SETUP] Using codec {codec:?} for video track (setup={setup:?})");
+
+        let inner_for_rtcp = Arc::downgrade(inner);
+        if let Err(err) = self
+            .sender
+            .create_track(
+                SequencedTrackLocalStaticRTP::from(TrackLocalStaticRTP::new(
+                    RTCRtpCodecCapability {
+                        mime_type: codec.mime_type().to_owned(),
+                        clock_rate: VIDEO_CLOCK_RATE,
+                        channels: 0,
+                        sdp_fmtp_line: codec.sdp_fmtp_line().to_owned(),
+                        rtcp_feedback: vec![],
+                    },
+                    "video".to_string(),
+                    "moonlight".to_string(),
+                )),
+                move |packet| {
+                    let inner = inner_for_rtcp.clone();
+                    tokio::spawn(async move {
+                        if let Some(inner) = inner.upgrade() {
+                            inner.on_rtcp_packet(packet).await;
+                        }
+                    });
+                },
+            )
+            .await
+        {
+            error!("[VIDEO-SETUP] FAILED to create video track: {err:?}");
+            return false;
+        };
+
+        info!("[VIDEO-SETUP {}] Video track created successfully", inner.t_plus());
+
+        let renegotiation_result = inner.send_offer().await;
+        if !renegotiation_result {
+            warn!("[VIDEO-SETUP] RENEGOTIATION FAILED! Video was added but renegotiation failed.");
+        } else {
+            info!("[VIDEO-SETUP] Renegotiation succeeded");
+        }
+
+        info!("[VIDEO-SETUP {}] ========== VIDEO SETUP COMPLETE ==========", inner.t_plus());
+        renegotiation_result
+    }
+
+    pub async fn send_decode_unit<'a>(&mut self, unit: &VideoDecodeUnit<'a>) -> DecodeResult {
+        // Moonlight delivers decode units at its own frame rate; since we
+        // relay the host's bitstream as-is (no local re-encoding), we derive
+        // the RTP clock by advancing it one frame's worth of 90kHz ticks per
+        // unit rather than resampling a wallclock, keeping it monotonic even
+        // under jitter.
+        self.rtp_timestamp = self.rtp_timestamp.wrapping_add(VIDEO_CLOCK_RATE / 60);
+
+        let packet = rtp::packet::Packet {
+            header: Header {
+                marker: true,
+                timestamp: self.rtp_timestamp,
+                ..Default::default()
+            },
+            payload: Bytes::copy_from_slice(unit.data()),
+        };
+
+        self.sender.send_samples(vec![packet], true, None).await;
+
+        DecodeResult::Ok
+    }
+}
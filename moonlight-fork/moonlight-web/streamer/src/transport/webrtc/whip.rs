@@ -0,0 +1,93 @@
+use std::{collections::HashMap, sync::Arc};
+
+use common::api_bindings::{RtcSdpType, RtcSessionDescription};
+use log::error;
+use tokio::sync::Mutex;
+use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
+
+use super::WebRtcInner;
+
+/// Registry of in-progress WHIP (ingest) / WHEP (egress) sessions, keyed by
+/// the resource id handed back as the `Location` of the initial `POST`
+/// response. Trickled ICE candidates (`PATCH`) and teardown (`DELETE`) are
+/// addressed to that same resource.
+///
+/// This only implements the signaling exchange itself, built on
+/// `WebRtcInner::apply_remote_description`/`gather_local_answer` (shared
+/// with the IPC/WebSocket path) - translating HTTP requests/responses to and
+/// from these calls is left to whatever server binary hosts the streamer.
+#[derive(Default)]
+pub struct WhipSessions {
+    sessions: Mutex<HashMap<String, Arc<WebRtcInner>>>,
+}
+
+impl WhipSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles the initial `POST` of a remote SDP offer: applies it to
+    /// `session`, gathers the local answer, and registers the session under
+    /// `resource_id` for later `PATCH`/`DELETE` calls. Returns the SDP that
+    /// should be sent back as the response body.
+    pub async fn create_session(
+        &self,
+        resource_id: String,
+        session: Arc<WebRtcInner>,
+        offer_sdp: String,
+    ) -> Result<String, String> {
+        session
+            .apply_remote_description(RtcSessionDescription {
+                ty: RtcSdpType::Offer,
+                sdp: offer_sdp,
+            })
+            .await?;
+
+        let answer = session.gather_local_answer().await?;
+
+        self.sessions.lock().await.insert(resource_id, session);
+
+        Ok(answer.sdp)
+    }
+
+    /// Handles a trickled ICE candidate `PATCH`ed to a session's resource URL.
+    pub async fn add_ice_candidate(
+        &self,
+        resource_id: &str,
+        candidate: RTCIceCandidateInit,
+    ) -> Result<(), String> {
+        let session = self.session(resource_id).await?;
+
+        session
+            .peer
+            .add_ice_candidate(candidate)
+            .await
+            .map_err(|err| format!("Failed to add ICE candidate: {err:?}"))
+    }
+
+    /// Handles session teardown on `DELETE`, closing the underlying peer
+    /// connection and forgetting the resource.
+    pub async fn close_session(&self, resource_id: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .lock()
+            .await
+            .remove(resource_id)
+            .ok_or_else(|| format!("Unknown WHIP/WHEP resource: {resource_id}"))?;
+
+        if let Err(err) = session.peer.close().await {
+            error!("[WHIP] Failed to close peer connection for {resource_id}: {err:?}");
+        }
+
+        Ok(())
+    }
+
+    async fn session(&self, resource_id: &str) -> Result<Arc<WebRtcInner>, String> {
+        self.sessions
+            .lock()
+            .await
+            .get(resource_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown WHIP/WHEP resource: {resource_id}"))
+    }
+}
@@ -1,4 +1,5 @@
-use actix_web::{get, post, web::Json, HttpResponse};
+use actix_web::{get, post, web::Json, web::Query, HttpResponse};
+use base64::{engine::general_purpose, Engine as _};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
@@ -7,9 +8,47 @@ use serde::{Deserialize, Serialize};
 
 const XDOTOOL_SERVER_URL: &str = "http://127.0.0.1:9091";
 
-#[derive(Debug, Serialize, Deserialize)]
+const MIME_TEXT_PLAIN: &str = "text/plain";
+
+/// How long xdotool-server is asked to hold a `/clipboard/watch` request
+/// open waiting for a change before answering with "no change yet".
+const LONG_POLL_WINDOW_SECS: u64 = 30;
+
+/// Clipboard payload carried between the browser, this proxy, and
+/// xdotool-server. `mime`/`bytes` is the general form (any clipboard
+/// target, base64-encoded); `text` is kept as a backward-compatible
+/// shortcut for plain-text content so older clients don't need to base64
+/// encode anything.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ClipboardData {
-    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bytes: Option<String>,
+}
+
+impl ClipboardData {
+    /// Fills in `mime`/`bytes` from `text` when a client only sent the
+    /// backward-compatible plain-text shortcut, so downstream code can
+    /// always rely on `mime`/`bytes` being present.
+    fn normalized(self) -> Self {
+        if self.mime.is_some() && self.bytes.is_some() {
+            return self;
+        }
+        match self.text {
+            Some(text) => {
+                let bytes = general_purpose::STANDARD.encode(text.as_bytes());
+                ClipboardData {
+                    text: Some(text),
+                    mime: Some(MIME_TEXT_PLAIN.to_string()),
+                    bytes: Some(bytes),
+                }
+            }
+            None => self,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,20 +56,42 @@ pub struct ClipboardError {
     pub error: String,
 }
 
-/// GET /api/clipboard - Read clipboard from remote desktop
-#[get("/clipboard")]
-pub async fn get_clipboard() -> HttpResponse {
-    let client = match reqwest::Client::builder()
+/// A clipboard change as reported by a `/clipboard/watch` long-poll:
+/// the new content plus a monotonically increasing `seq` the caller should
+/// pass back as `since` on its next watch request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipboardUpdate {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub data: ClipboardData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatchQuery {
+    /// The last `seq` this caller has already seen; the watch only returns
+    /// once xdotool-server's clipboard sequence moves past it.
+    #[serde(default)]
+    since: u64,
+}
+
+fn clipboard_client() -> Result<reqwest::Client, HttpResponse> {
+    reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
+        .map_err(|e| {
             warn!("[Clipboard] Failed to create HTTP client: {}", e);
-            return HttpResponse::InternalServerError().json(ClipboardError {
+            HttpResponse::InternalServerError().json(ClipboardError {
                 error: format!("Failed to create HTTP client: {}", e),
-            });
-        }
+            })
+        })
+}
+
+/// GET /api/clipboard - Read clipboard from remote desktop
+#[get("/clipboard")]
+pub async fn get_clipboard() -> HttpResponse {
+    let client = match clipboard_client() {
+        Ok(c) => c,
+        Err(response) => return response,
     };
 
     match client.get(format!("{}/clipboard", XDOTOOL_SERVER_URL)).send().await {
@@ -38,7 +99,12 @@ pub async fn get_clipboard() -> HttpResponse {
             if response.status().is_success() {
                 match response.json::<ClipboardData>().await {
                     Ok(data) => {
-                        info!("[Clipboard] GET OK: {} chars from xdotool-server", data.text.len());
+                        let data = data.normalized();
+                        info!(
+                            "[Clipboard] GET OK: {} ({} base64 bytes) from xdotool-server",
+                            data.mime.as_deref().unwrap_or(MIME_TEXT_PLAIN),
+                            data.bytes.as_ref().map(String::len).unwrap_or(0)
+                        );
                         HttpResponse::Ok().json(data)
                     }
                     Err(e) => {
@@ -67,19 +133,13 @@ pub async fn get_clipboard() -> HttpResponse {
 /// POST /api/clipboard - Write clipboard to remote desktop
 #[post("/clipboard")]
 pub async fn post_clipboard(Json(data): Json<ClipboardData>) -> HttpResponse {
-    let client = match reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-    {
+    let client = match clipboard_client() {
         Ok(c) => c,
-        Err(e) => {
-            warn!("[Clipboard] Failed to create HTTP client: {}", e);
-            return HttpResponse::InternalServerError().json(ClipboardError {
-                error: format!("Failed to create HTTP client: {}", e),
-            });
-        }
+        Err(response) => return response,
     };
 
+    let data = data.normalized();
+
     match client
         .post(format!("{}/clipboard", XDOTOOL_SERVER_URL))
         .json(&data)
@@ -88,7 +148,11 @@ pub async fn post_clipboard(Json(data): Json<ClipboardData>) -> HttpResponse {
     {
         Ok(response) => {
             if response.status().is_success() {
-                info!("[Clipboard] POST OK: wrote {} chars to xdotool-server", data.text.len());
+                info!(
+                    "[Clipboard] POST OK: wrote {} bytes of {} to xdotool-server",
+                    data.bytes.as_ref().map(String::len).unwrap_or(0),
+                    data.mime.as_deref().unwrap_or(MIME_TEXT_PLAIN)
+                );
                 HttpResponse::Ok().body("ok")
             } else {
                 warn!("[Clipboard] xdotool-server returned status: {}", response.status());
@@ -105,3 +169,120 @@ pub async fn post_clipboard(Json(data): Json<ClipboardData>) -> HttpResponse {
         }
     }
 }
+
+/// GET /api/clipboard/targets - List the MIME types xdotool-server currently
+/// has clipboard content available in, so the frontend can request the
+/// richest one instead of assuming plain text.
+#[get("/clipboard/targets")]
+pub async fn get_clipboard_targets() -> HttpResponse {
+    let client = match clipboard_client() {
+        Ok(c) => c,
+        Err(response) => return response,
+    };
+
+    match client
+        .get(format!("{}/clipboard/targets", XDOTOOL_SERVER_URL))
+        .send()
+        .await
+    {
+        Ok(response) => {
+            if response.status().is_success() {
+                match response.json::<Vec<String>>().await {
+                    Ok(targets) => {
+                        info!("[Clipboard] targets OK: {} available", targets.len());
+                        HttpResponse::Ok().json(targets)
+                    }
+                    Err(e) => {
+                        warn!("[Clipboard] Failed to parse targets response: {}", e);
+                        HttpResponse::InternalServerError().json(ClipboardError {
+                            error: format!("Failed to parse clipboard targets response: {}", e),
+                        })
+                    }
+                }
+            } else {
+                warn!("[Clipboard] xdotool-server returned status: {}", response.status());
+                HttpResponse::InternalServerError().json(ClipboardError {
+                    error: format!("xdotool-server error: {}", response.status()),
+                })
+            }
+        }
+        Err(e) => {
+            warn!("[Clipboard] Failed to connect to xdotool-server: {}", e);
+            HttpResponse::ServiceUnavailable().json(ClipboardError {
+                error: format!("xdotool-server not available: {}", e),
+            })
+        }
+    }
+}
+
+/// GET /api/clipboard/watch?since=N - Long-poll for a clipboard change past
+/// `since`. Returns `200` with the new `ClipboardUpdate` as soon as
+/// xdotool-server's clipboard sequence advances, or `304 Not Modified` once
+/// the long-poll window elapses with no change, so the frontend can just
+/// reconnect in a loop instead of blindly polling `GET /clipboard`.
+#[get("/clipboard/watch")]
+pub async fn watch_clipboard(query: Query<WatchQuery>) -> HttpResponse {
+    let client = match reqwest::Client::builder()
+        // Longer than xdotool-server's own long-poll window, so we don't
+        // time the connection out from under an in-flight, well-behaved
+        // long-poll that's about to answer "no change yet".
+        .timeout(std::time::Duration::from_secs(LONG_POLL_WINDOW_SECS + 5))
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("[Clipboard] Failed to create HTTP client: {}", e);
+            return HttpResponse::InternalServerError().json(ClipboardError {
+                error: format!("Failed to create HTTP client: {}", e),
+            });
+        }
+    };
+
+    let result = client
+        .get(format!(
+            "{}/clipboard/watch?since={}&timeout={}",
+            XDOTOOL_SERVER_URL, query.since, LONG_POLL_WINDOW_SECS
+        ))
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+            HttpResponse::NotModified().finish()
+        }
+        Ok(response) if response.status().is_success() => {
+            match response.json::<ClipboardUpdate>().await {
+                Ok(update) => {
+                    let update = ClipboardUpdate {
+                        seq: update.seq,
+                        data: update.data.normalized(),
+                    };
+                    info!("[Clipboard] watch: new content at seq {}", update.seq);
+                    HttpResponse::Ok().json(update)
+                }
+                Err(e) => {
+                    warn!("[Clipboard] Failed to parse watch response: {}", e);
+                    HttpResponse::InternalServerError().json(ClipboardError {
+                        error: format!("Failed to parse clipboard watch response: {}", e),
+                    })
+                }
+            }
+        }
+        Ok(response) => {
+            warn!("[Clipboard] xdotool-server returned status: {}", response.status());
+            HttpResponse::InternalServerError().json(ClipboardError {
+                error: format!("xdotool-server error: {}", response.status()),
+            })
+        }
+        // A timed-out long-poll (no change within our window) is the
+        // expected steady state, not an error: tell the frontend to just
+        // reconnect rather than surfacing a connectivity failure.
+        Err(e) if e.is_timeout() => HttpResponse::NotModified().finish(),
+        Err(e) => {
+            warn!("[Clipboard] Failed to connect to xdotool-server: {}", e);
+            HttpResponse::ServiceUnavailable().json(ClipboardError {
+                error: format!("xdotool-server not available: {}", e),
+            })
+        }
+    }
+}